@@ -0,0 +1,67 @@
+//! Benchmarks of the extraction pipeline stages on a synthetic transcript
+//! that holds every edge case the parser handles, spread over several pages.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use lopdf::Document;
+use scrape_sfu_transcript::parse::process_chunks;
+use scrape_sfu_transcript::pdf::{combine_page_chunks, extract_page_chunks};
+use scrape_sfu_transcript::synthetic::{render, student, Layout, StudentConfig};
+use scrape_sfu_transcript::{Chunk, Transcript};
+
+fn synthetic_transcript() -> Document {
+    let config = StudentConfig {
+        terms: 12,
+        courses_per_term: 5,
+        edge_cases: true,
+        ..StudentConfig::default()
+    };
+    render(&student(&config), &Layout::default())
+}
+
+fn simplified_pages(doc: &Document) -> Vec<Vec<Chunk>> {
+    extract_page_chunks(doc)
+        .unwrap()
+        .into_iter()
+        .map(|page| page.into_iter().map(Chunk::simplify).collect())
+        .collect()
+}
+
+fn pipeline(c: &mut Criterion) {
+    let doc = synthetic_transcript();
+    let raw = extract_page_chunks(&doc).unwrap();
+    let pages = simplified_pages(&doc);
+    let combined = combine_page_chunks(pages.clone()).unwrap();
+    // Guard against benchmarking a document the parser rejects early.
+    Transcript::from_document(&doc).expect("the synthetic transcript parses");
+
+    c.bench_function("extract_page_chunks", |b| {
+        b.iter(|| extract_page_chunks(black_box(&doc)).unwrap());
+    });
+    c.bench_function("simplify", |b| {
+        b.iter_batched(
+            || raw.clone(),
+            |raw| -> Vec<Vec<Chunk>> {
+                raw.into_iter()
+                    .map(|page| page.into_iter().map(Chunk::simplify).collect())
+                    .collect()
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    c.bench_function("combine_page_chunks", |b| {
+        b.iter_batched(
+            || pages.clone(),
+            |pages| combine_page_chunks(pages).unwrap(),
+            BatchSize::SmallInput,
+        );
+    });
+    c.bench_function("process_chunks", |b| {
+        b.iter(|| process_chunks(black_box(&combined)).unwrap());
+    });
+    c.bench_function("from_document", |b| {
+        b.iter(|| Transcript::from_document(black_box(&doc)).unwrap());
+    });
+}
+
+criterion_group!(benches, pipeline);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+//! Parses arbitrary bytes as a transcript PDF, strictly and leniently.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use scrape_sfu_transcript::{ParseOptions, Transcript};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Transcript::from_bytes(data);
+    let lenient = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let _ = Transcript::from_bytes_with(data, &lenient);
+});
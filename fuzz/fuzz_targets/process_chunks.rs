@@ -0,0 +1,32 @@
+//! Processes arbitrary chunk trees, skipping the PDF decoding so that the
+//! fuzzer explores the row handling directly.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use scrape_sfu_transcript::error::Diagnostics;
+use scrape_sfu_transcript::parse::{process_chunks, process_chunks_with};
+use scrape_sfu_transcript::Chunk;
+
+// Mirrors `Chunk`, which does not implement `Arbitrary` itself.
+#[derive(Arbitrary, Debug)]
+enum Input {
+    Chunks(Vec<Input>),
+    String(String),
+}
+
+impl From<Input> for Chunk {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::Chunks(v) => Self::Chunks(v.into_iter().map(Self::from).collect()),
+            Input::String(s) => Self::String(s),
+        }
+    }
+}
+
+fuzz_target!(|inputs: Vec<Input>| {
+    let chunks: Vec<Chunk> = inputs.into_iter().map(Chunk::from).collect();
+    let _ = process_chunks(&chunks);
+    let _ = process_chunks_with(&chunks, &mut Diagnostics::new(true));
+});
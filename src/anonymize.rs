@@ -0,0 +1,200 @@
+//! Strategies for replacing real student IDs with anonymized ones.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+#[cfg(feature = "vault")]
+use crate::vault::{Recipient, Vault};
+
+/// The number of bytes of the HMAC digest kept in derived IDs. Eight bytes
+/// (16 hex digits) keeps IDs readable while making collisions within any
+/// realistic cohort vanishingly unlikely.
+const HMAC_ID_BYTES: usize = 8;
+
+/// The message whose HMAC identifies a key. It cannot be mistaken for the ID
+/// of a student, as student IDs are numbers.
+const FINGERPRINT_MESSAGE: &str = "scrape-sfu-transcript key check";
+
+/// Assigns anonymized IDs to students.
+#[derive(Clone, Debug)]
+pub enum Anonymizer {
+    /// Numbers students consecutively starting from `first`, in the order
+    /// the transcripts are processed.
+    Sequential { first: usize },
+    /// Derives a stable pseudonymous ID from the real student ID with
+    /// HMAC-SHA256 under a secret key, so re-runs produce the same IDs
+    /// without keeping any mapping.
+    Hmac { key: Vec<u8> },
+    /// Looks up or assigns IDs in a persistent mapping from real to
+    /// anonymized IDs.
+    Mapped(IdMap),
+    /// Assigns IDs with `inner` and seals the real ID behind each in
+    /// `vault`, encrypted to `recipient`, for later re-identification.
+    #[cfg(feature = "vault")]
+    Vaulted {
+        inner: Box<Anonymizer>,
+        vault: Vault,
+        recipient: Recipient,
+    },
+}
+
+impl Anonymizer {
+    /// Returns the anonymized ID for the student with `real_id`, which is
+    /// the `position`th transcript processed.
+    pub fn anonymize(&mut self, position: usize, real_id: &str) -> String {
+        match self {
+            Self::Sequential { first } => (*first + position).to_string(),
+            Self::Hmac { key } => hmac_id(key, real_id),
+            Self::Mapped(map) => map.get_or_assign(real_id),
+            #[cfg(feature = "vault")]
+            Self::Vaulted {
+                inner,
+                vault,
+                recipient,
+            } => {
+                let id = inner.anonymize(position, real_id);
+                vault.seal(recipient, &id, real_id);
+                id
+            }
+        }
+    }
+
+    /// Persists any state needed to reproduce the assigned IDs later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a mapping or vault file cannot be written.
+    pub fn finish(&self) -> io::Result<()> {
+        match self {
+            Self::Mapped(map) => map.save(),
+            #[cfg(feature = "vault")]
+            Self::Vaulted { inner, vault, .. } => {
+                inner.finish()?;
+                vault.save()
+            }
+            Self::Sequential { .. } | Self::Hmac { .. } => Ok(()),
+        }
+    }
+}
+
+/// A persistent mapping from real student IDs to incrementing anonymized IDs,
+/// stored as a two column CSV of real and anonymized IDs. Existing
+/// assignments are reused so that longitudinal joins across runs stay valid.
+///
+/// Because the file re-identifies students, it is only ever written with
+/// permissions restricted to the owner.
+#[derive(Clone, Debug)]
+pub struct IdMap {
+    path: PathBuf,
+    ids: BTreeMap<String, usize>,
+    next: usize,
+    modified: bool,
+}
+
+impl IdMap {
+    /// Loads the mapping at `path`, or starts an empty one if the file does
+    /// not exist yet. New IDs continue after the largest assigned ID, or
+    /// start at `first` for an empty mapping.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing file cannot be read or is malformed.
+    pub fn load<P: AsRef<Path>>(path: P, first: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut ids = BTreeMap::new();
+        if path.exists() {
+            let mut reader = csv::Reader::from_path(&path)?;
+            for record in reader.deserialize() {
+                let (real, anonymized): (String, usize) = record?;
+                ids.insert(real, anonymized);
+            }
+        }
+        let next = ids.values().max().map_or(first, |max| max + 1);
+        Ok(Self {
+            path,
+            ids,
+            next,
+            modified: false,
+        })
+    }
+
+    /// Returns the anonymized ID previously assigned to `real_id`, assigning
+    /// the next free ID if there is none.
+    pub fn get_or_assign(&mut self, real_id: &str) -> String {
+        if let Some(id) = self.ids.get(real_id) {
+            return id.to_string();
+        }
+        let id = self.next;
+        self.next += 1;
+        self.ids.insert(real_id.to_string(), id);
+        self.modified = true;
+        id.to_string()
+    }
+
+    /// Writes the mapping back to its file if any IDs were assigned. The
+    /// file is replaced atomically so a failed write never loses existing
+    /// assignments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.modified {
+            return Ok(());
+        }
+        let staged = self.path.with_extension("tmp");
+        let mut writer = csv::Writer::from_writer(create_private(&staged)?);
+        writer.write_record(["real_id", "anonymized_id"])?;
+        for (real, anonymized) in &self.ids {
+            writer.write_record([real.as_str(), anonymized.to_string().as_str()])?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(staged, &self.path)
+    }
+}
+
+// Creates a file readable only by its owner, for files holding real
+// student IDs.
+pub(crate) fn create_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// A short check value identifying `key` without revealing it, for
+/// confirming that runs on different machines share the same key and so
+/// derive the same IDs.
+#[must_use]
+pub fn key_fingerprint(key: &[u8]) -> String {
+    hmac_id(key, FINGERPRINT_MESSAGE)
+}
+
+/// Derives the pseudonymous ID of `real_id` as the leading hex digits of its
+/// HMAC-SHA256 under `key`.
+///
+/// # Panics
+///
+/// Never panics in practice, as HMAC accepts keys of any length.
+#[must_use]
+pub fn hmac_id(key: &[u8], real_id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(real_id.as_bytes());
+    mac.finalize().into_bytes()[..HMAC_ID_BYTES]
+        .iter()
+        .fold(String::new(), |mut id, byte| {
+            let _ = write!(id, "{byte:02x}");
+            id
+        })
+}
@@ -0,0 +1,259 @@
+//! Auditing transcripts against degree requirements, such as required
+//! courses, unit thresholds, and WQB units, defined in TOML or JSON.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use serde::{de, Deserialize, Serialize};
+
+use crate::grades;
+use crate::model::{Course, Designation, Outcome, Requirement, RequirementStatus, StudentInfo};
+use crate::prerequisites::normalize;
+
+/// One requirement of a degree. A requirement listing courses but no units
+/// is met by completing `choose` of the courses, or all of them without
+/// `choose`. A requirement with `units` is met by earning that many units
+/// from the courses passing every given filter: the listed courses, the
+/// subjects, the lowest course number, and the WQB designation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequirementRule {
+    pub name: String,
+    #[serde(default)]
+    pub courses: Vec<String>,
+    pub choose: Option<usize>,
+    pub units: Option<f64>,
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    /// The lowest course number that counts, such as 300 for upper division
+    /// courses.
+    pub minimum_level: Option<u32>,
+    pub designation: Option<Designation>,
+}
+
+/// The requirements of a degree, and the minimum grade that courses must be
+/// passed with to count toward them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DegreeRequirements {
+    pub minimum_grade: Option<String>,
+    pub requirements: Vec<RequirementRule>,
+}
+
+/// The outcome of auditing a student against one requirement.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AuditedRequirement {
+    #[serde(flatten)]
+    pub requirement: Requirement,
+    /// The listed courses that were not completed, when the requirement is
+    /// not satisfied.
+    pub missing: Vec<String>,
+}
+
+// A completed course that may count toward requirements, as its subject and
+// course number with the units it earned and its designations.
+struct Credit {
+    name: String,
+    subject: String,
+    number: String,
+    units: f64,
+    designations: Vec<Designation>,
+}
+
+impl Credit {
+    fn level(&self) -> Option<u32> {
+        let digits: String = self
+            .number
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    }
+}
+
+impl RequirementRule {
+    fn counts(&self, credit: &Credit) -> bool {
+        (self.courses.is_empty() || self.courses.contains(&credit.name))
+            && (self.subjects.is_empty() || self.subjects.contains(&credit.subject))
+            && self
+                .minimum_level
+                .is_none_or(|minimum| credit.level().is_some_and(|level| level >= minimum))
+            && self
+                .designation
+                .is_none_or(|designation| credit.designations.contains(&designation))
+    }
+
+    fn audit(&self, credits: &[Credit]) -> AuditedRequirement {
+        let counted: Vec<&Credit> = credits.iter().filter(|c| self.counts(c)).collect();
+        let missing: Vec<String> = self
+            .courses
+            .iter()
+            .filter(|course| !counted.iter().any(|credit| credit.name == **course))
+            .cloned()
+            .collect();
+        let (satisfied, units_used, units_needed) = if let Some(required) = self.units {
+            // Summing from 0.0 rather than with sum() avoids a -0 total.
+            let used = counted
+                .iter()
+                .fold(0.0, |total, credit| total + credit.units);
+            (
+                used >= required,
+                Some(used),
+                Some((required - used).max(0.0)),
+            )
+        } else {
+            let choose = self.choose.unwrap_or(self.courses.len());
+            (self.courses.len() - missing.len() >= choose, None, None)
+        };
+        AuditedRequirement {
+            requirement: Requirement {
+                name: self.name.clone(),
+                status: if satisfied {
+                    RequirementStatus::Satisfied
+                } else {
+                    RequirementStatus::NotSatisfied
+                },
+                units_required: self.units,
+                units_used,
+                units_needed,
+            },
+            missing: if satisfied { Vec::new() } else { missing },
+        }
+    }
+}
+
+impl DegreeRequirements {
+    /// Reads requirements from a JSON object such as `{"minimum_grade":
+    /// "C-", "requirements": [{"name": "Writing", "designation": "W",
+    /// "units": 6}]}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader does not hold such an object, or if
+    /// the requirements are invalid.
+    pub fn from_json<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader::<_, Self>(reader)?.validated()
+    }
+
+    /// Reads requirements from TOML, with each requirement as a
+    /// `[[requirements]]` table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not such a document, or if the
+    /// requirements are invalid.
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str::<Self>(text)?.validated()
+    }
+
+    // Checks that every requirement can be met and the minimum grade has
+    // grade points, and normalizes the course names and subjects.
+    fn validated<E: de::Error>(mut self) -> Result<Self, E> {
+        if let Some(grade) = &self.minimum_grade {
+            if grades::grade_points(grade.trim()).is_none() {
+                return Err(E::custom(format!(
+                    "minimum grade {grade} has no grade points"
+                )));
+            }
+        }
+        for rule in &mut self.requirements {
+            if rule.courses.is_empty() && rule.units.is_none() {
+                return Err(E::custom(format!(
+                    "requirement {} needs courses or units",
+                    rule.name
+                )));
+            }
+            if rule
+                .choose
+                .is_some_and(|choose| choose > rule.courses.len())
+            {
+                return Err(E::custom(format!(
+                    "requirement {} chooses more courses than it lists",
+                    rule.name
+                )));
+            }
+            for course in &mut rule.courses {
+                *course = normalize(course);
+            }
+            for subject in &mut rule.subjects {
+                *subject = normalize(subject);
+            }
+        }
+        Ok(self)
+    }
+
+    // Whether a completed course counts toward requirements. Grades without
+    // grade points, such as P and TR, meet any minimum grade.
+    fn passes(&self, course: &Course) -> bool {
+        let minimum = self
+            .minimum_grade
+            .as_deref()
+            .and_then(|grade| grades::grade_points(grade.trim()));
+        course.outcome == Outcome::Completed
+            && minimum.is_none_or(|minimum| {
+                grades::grade_points(&course.grade).is_none_or(|points| points >= minimum)
+            })
+    }
+
+    // The courses the student completed, each counted once however many
+    // times it was passed with the most units and every designation of its
+    // attempts, and the transfer credits as their SFU equivalents where known.
+    fn credits(&self, student: &StudentInfo) -> Vec<Credit> {
+        let transfers = student
+            .transfers
+            .iter()
+            .filter(|transfer| self.passes(&transfer.course))
+            .map(|transfer| {
+                let course = &transfer.course;
+                let (subject, number) = match &transfer.equivalent {
+                    Some(equivalent) => (&equivalent.subject, &equivalent.id),
+                    None => (&course.subject, &course.id),
+                };
+                let units = transfer.units.or(course.units_earned);
+                (subject, number, units, course.designations.as_slice())
+            });
+        let courses = student
+            .semesters
+            .iter()
+            .flat_map(|semester| &semester.courses)
+            .filter(|course| self.passes(course))
+            .map(|course| {
+                let designations = course.designations.as_slice();
+                (
+                    &course.subject,
+                    &course.id,
+                    course.units_earned,
+                    designations,
+                )
+            });
+        let mut credits: BTreeMap<String, Credit> = BTreeMap::new();
+        for (subject, number, units, designations) in transfers.chain(courses) {
+            let name = normalize(&format!("{subject} {number}"));
+            let credit = credits.entry(name.clone()).or_insert_with(|| Credit {
+                name,
+                subject: normalize(subject),
+                number: number.trim().to_string(),
+                units: 0.0,
+                designations: Vec::new(),
+            });
+            credit.units = credit.units.max(units.unwrap_or_default());
+            for designation in designations {
+                if !credit.designations.contains(designation) {
+                    credit.designations.push(*designation);
+                }
+            }
+        }
+        credits.into_values().collect()
+    }
+
+    /// Audits the student against each requirement, in the order they are
+    /// defined. A course may count toward any number of requirements.
+    #[must_use]
+    pub fn audit(&self, student: &StudentInfo) -> Vec<AuditedRequirement> {
+        let credits = self.credits(student);
+        self.requirements
+            .iter()
+            .map(|rule| rule.audit(&credits))
+            .collect()
+    }
+}
@@ -0,0 +1,95 @@
+//! A cache of parsed transcripts keyed by the SHA-256 of the PDF, so that
+//! re-running a batch only parses the files that are new or have changed.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::anonymize::create_private;
+use crate::model::{Identity, StudentInfo};
+use crate::ParseOptions;
+
+#[derive(Serialize, Deserialize)]
+struct Entry<S, I> {
+    fingerprint: String,
+    student: S,
+    // The identity is never serialized with the student, but is kept so
+    // that cached output can still be checked for it.
+    #[serde(default)]
+    identity: I,
+}
+
+/// A directory holding one JSON file per parsed transcript, named by the
+/// SHA-256 of the PDF. Entries record the version of this crate and the
+/// parse options they were produced with, and are ignored when either
+/// differs, so upgrading or changing options never reuses stale results.
+/// Entries hold real student IDs and names, so they are only readable by
+/// their owner.
+pub struct ParseCache {
+    dir: PathBuf,
+    fingerprint: String,
+}
+
+impl ParseCache {
+    /// Opens the cache in `dir`, creating the directory if needed, for
+    /// transcripts parsed with `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn open<P: AsRef<Path>>(dir: P, options: &ParseOptions) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            fingerprint: format!(
+                "{} lenient={} strict={} page_order={:?} gpa_tolerance={:?} vocabulary={}",
+                env!("CARGO_PKG_VERSION"),
+                options.lenient,
+                options.strict,
+                options.page_order,
+                options.gpa_tolerance,
+                serde_json::to_string(&options.vocabulary)?
+            ),
+        })
+    }
+
+    fn entry_path(&self, digest: &str) -> PathBuf {
+        self.dir.join(format!("{digest}.json"))
+    }
+
+    /// The cached student for the PDF with the given SHA-256 digest, if any.
+    /// Unreadable or outdated entries are treated as missing.
+    #[must_use]
+    pub fn get(&self, digest: &str) -> Option<StudentInfo> {
+        let file = File::open(self.entry_path(digest)).ok()?;
+        let entry: Entry<StudentInfo, Identity> =
+            serde_json::from_reader(BufReader::new(file)).ok()?;
+        (entry.fingerprint == self.fingerprint).then_some(StudentInfo {
+            identity: entry.identity,
+            ..entry.student
+        })
+    }
+
+    /// Stores the student parsed from the PDF with the given SHA-256 digest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written.
+    pub fn insert(&self, digest: &str, student: &StudentInfo) -> io::Result<()> {
+        // Entries are written under a temporary name and renamed into place,
+        // so an interrupted run never leaves a truncated entry behind.
+        let path = self.entry_path(digest);
+        let staged = path.with_extension(format!("{}.tmp", std::process::id()));
+        let entry = Entry {
+            fingerprint: self.fingerprint.clone(),
+            student,
+            identity: &student.identity,
+        };
+        let mut writer = BufWriter::new(create_private(&staged)?);
+        serde_json::to_writer(&mut writer, &entry)?;
+        writer.flush()?;
+        fs::rename(staged, path)
+    }
+}
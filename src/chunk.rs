@@ -0,0 +1,88 @@
+use std::io::{self, Write};
+
+/// A `Chunk` is the raw text structure of a transcript as it appears in the
+/// PDF content stream. Text blocks are nested sequences of strings, where the
+/// nesting mirrors the row and column layout of the original document.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Chunk {
+    Chunks(Vec<Chunk>),
+    String(String),
+}
+
+impl Chunk {
+    #[must_use]
+    pub fn get_contained(&self) -> Option<&[Self]> {
+        match self {
+            Self::String(_) => None,
+            Self::Chunks(v) => Some(v.as_slice()),
+        }
+    }
+
+    #[must_use]
+    pub fn get_string(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            Self::Chunks(_) => None,
+        }
+    }
+
+    /// Returns every string within the chunk in order, descending into
+    /// nested chunks.
+    #[must_use]
+    pub fn strings(&self) -> Vec<&str> {
+        match self {
+            Self::String(s) => vec![s.as_str()],
+            Self::Chunks(v) => v.iter().flat_map(Self::strings).collect(),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_chunks(&self) -> bool {
+        matches!(self, Self::Chunks(_))
+    }
+
+    // Simplification recursively transforms Chunks objects containing one
+    // element into the single element they contain for readability. Column
+    // structure is preserved because that can be useful for ensuring
+    // consistency when extracting the data later.
+    #[must_use]
+    pub fn simplify(self) -> Self {
+        match self {
+            Self::String(s) => Self::String(s.trim().to_string()),
+            Self::Chunks(v) => {
+                let mut fresh: Vec<Self> = v.into_iter().map(Self::simplify).collect();
+                if fresh.len() == 1 {
+                    fresh.remove(0)
+                } else {
+                    Self::Chunks(fresh)
+                }
+            }
+        }
+    }
+
+    /// Writes the chunk as an indented tree in which every node is labelled
+    /// with its index path (e.g. `12.0.3` for the fourth element of the
+    /// first element of chunk 12), so that the positions the parser relies
+    /// on can be located.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    pub fn write_tree<W: Write>(&self, writer: &mut W, path: &str) -> io::Result<()> {
+        self.write_tree_at(writer, path, 0)
+    }
+
+    fn write_tree_at<W: Write>(&self, writer: &mut W, path: &str, depth: usize) -> io::Result<()> {
+        let indent = "  ".repeat(depth);
+        match self {
+            Self::String(s) => writeln!(writer, "{indent}[{path}] {s:?}"),
+            Self::Chunks(v) => {
+                writeln!(writer, "{indent}[{path}] ({} elements)", v.len())?;
+                for (i, chunk) in v.iter().enumerate() {
+                    chunk.write_tree_at(writer, &format!("{path}.{i}"), depth + 1)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! Parsing of the `ToUnicode` `CMap`s embedded alongside PDF fonts.
+//!
+//! A `ToUnicode` `CMap` maps the character codes shown by a font back to
+//! Unicode text. Fonts that carry one often use custom glyph orders, so
+//! decoding their strings with the base encoding produces garbage.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Hex(Vec<u8>),
+    ArrayStart,
+    ArrayEnd,
+    Word(String),
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+// An odd number of digits is padded with a trailing zero, as in PDF hex
+// strings.
+fn decode_hex(digits: &[u8]) -> Vec<u8> {
+    let nibbles: Vec<u8> = digits.iter().copied().filter_map(hex_value).collect();
+    nibbles
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn tokenize(data: &[u8]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'%' => {
+                while i < data.len() && !matches!(data[i], b'\r' | b'\n') {
+                    i += 1;
+                }
+            }
+            b'<' if data.get(i + 1) == Some(&b'<') => {
+                tokens.push(Token::Word("<<".to_string()));
+                i += 2;
+            }
+            b'>' if data.get(i + 1) == Some(&b'>') => {
+                tokens.push(Token::Word(">>".to_string()));
+                i += 2;
+            }
+            b'<' => {
+                let start = i + 1;
+                let end = data[start..]
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .map_or(data.len(), |offset| start + offset);
+                tokens.push(Token::Hex(decode_hex(&data[start..end])));
+                i = end + 1;
+            }
+            b'[' => {
+                tokens.push(Token::ArrayStart);
+                i += 1;
+            }
+            b']' => {
+                tokens.push(Token::ArrayEnd);
+                i += 1;
+            }
+            b'(' => {
+                // Literal strings only occur in the CMap header, so their
+                // contents are skipped.
+                let mut depth = 0;
+                while i < data.len() {
+                    match data[i] {
+                        b'\\' => i += 1,
+                        b'(' => depth += 1,
+                        b')' => depth -= 1,
+                        _ => {}
+                    }
+                    i += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            b if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                i += 1;
+                while i < data.len()
+                    && !data[i].is_ascii_whitespace()
+                    && !matches!(data[i], b'<' | b'>' | b'[' | b']' | b'(' | b'/' | b'%')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(
+                    String::from_utf8_lossy(&data[start..i]).into_owned(),
+                ));
+            }
+        }
+    }
+    tokens
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | u32::from(byte))
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| (u16::from(pair[0]) << 8) | u16::from(pair.get(1).copied().unwrap_or(0)))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+// Increments the last UTF-16 code unit of a destination, which is how
+// `bfrange` entries with a single destination advance through a range.
+fn offset_text(bytes: &[u8], offset: u32) -> String {
+    let mut bytes = bytes.to_vec();
+    if bytes.len() >= 2 {
+        let last = bytes.len() - 2;
+        let unit = (u32::from(bytes[last]) << 8) | u32::from(bytes[last + 1]);
+        let [_, _, high, low] = unit.wrapping_add(offset).to_be_bytes();
+        bytes[last] = high;
+        bytes[last + 1] = low;
+    } else if let Some(last) = bytes.last_mut() {
+        *last = last.wrapping_add(u8::try_from(offset).unwrap_or(u8::MAX));
+    }
+    utf16_text(&bytes)
+}
+
+/// The code space and mappings of a `ToUnicode` `CMap`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToUnicodeMap {
+    // The (low, high) bounds of each code space range, keyed by byte width.
+    code_space: BTreeMap<usize, Vec<(u32, u32)>>,
+    // Mapped text keyed by (byte width, code).
+    mappings: BTreeMap<(usize, u32), String>,
+}
+
+impl ToUnicodeMap {
+    /// Parses the decompressed contents of a `ToUnicode` stream. Entries
+    /// that cannot be understood are skipped.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Self {
+        let tokens = tokenize(data);
+        let mut map = Self::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::Word(word) if word == "begincodespacerange" => {
+                    i += 1;
+                    while let [Token::Hex(low), Token::Hex(high), ..] = &tokens[i..] {
+                        map.code_space
+                            .entry(low.len())
+                            .or_default()
+                            .push((code_value(low), code_value(high)));
+                        i += 2;
+                    }
+                }
+                Token::Word(word) if word == "beginbfchar" => {
+                    i += 1;
+                    while let [Token::Hex(code), Token::Hex(text), ..] = &tokens[i..] {
+                        map.mappings
+                            .insert((code.len(), code_value(code)), utf16_text(text));
+                        i += 2;
+                    }
+                }
+                Token::Word(word) if word == "beginbfrange" => {
+                    i += 1;
+                    i += map.parse_ranges(&tokens[i..]);
+                }
+                _ => i += 1,
+            }
+        }
+        map
+    }
+
+    // Parses `bfrange` entries until the end of the section, returning the
+    // number of tokens consumed.
+    fn parse_ranges(&mut self, tokens: &[Token]) -> usize {
+        let mut i = 0;
+        loop {
+            let [Token::Hex(low), Token::Hex(high), rest @ ..] = &tokens[i..] else {
+                return i;
+            };
+            let width = low.len();
+            // Ranges never span more than two bytes of codes, which bounds
+            // the work done for malformed ranges.
+            let (low, high) = (code_value(low), code_value(high));
+            let high = high.min(low.saturating_add(0xFFFF));
+            match rest {
+                [Token::Hex(text), ..] => {
+                    for code in low..=high {
+                        self.mappings
+                            .insert((width, code), offset_text(text, code - low));
+                    }
+                    i += 3;
+                }
+                [Token::ArrayStart, ..] => {
+                    i += 3;
+                    let mut code = low;
+                    while let Some(Token::Hex(text)) = tokens.get(i) {
+                        if code <= high {
+                            self.mappings.insert((width, code), utf16_text(text));
+                        }
+                        code = code.saturating_add(1);
+                        i += 1;
+                    }
+                    if tokens.get(i) == Some(&Token::ArrayEnd) {
+                        i += 1;
+                    }
+                }
+                _ => return i,
+            }
+        }
+    }
+
+    /// Whether the `CMap` defines any mappings at all.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    // The byte width of the code starting at `bytes`. Without a code space,
+    // the widths of the mapped codes are tried instead.
+    fn code_width(&self, bytes: &[u8]) -> usize {
+        let in_code_space = self.code_space.iter().find(|(&width, ranges)| {
+            bytes.len() >= width && {
+                let code = code_value(&bytes[..width]);
+                ranges
+                    .iter()
+                    .any(|&(low, high)| (low..=high).contains(&code))
+            }
+        });
+        if let Some((&width, _)) = in_code_space {
+            return width;
+        }
+        (1..=bytes.len().min(4))
+            .find(|&width| {
+                self.mappings
+                    .contains_key(&(width, code_value(&bytes[..width])))
+            })
+            .unwrap_or_else(|| self.code_space.keys().next().copied().unwrap_or(1))
+    }
+
+    /// Decodes the bytes of a shown string. Codes without a mapping are
+    /// replaced with U+FFFD.
+    #[must_use]
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        self.decode_codes(bytes, |rest| self.code_width(rest))
+    }
+
+    /// Decodes the bytes of a shown string as codes of a fixed byte width,
+    /// as used by composite fonts.
+    #[must_use]
+    pub fn decode_with_width(&self, bytes: &[u8], width: usize) -> String {
+        self.decode_codes(bytes, |_| width.max(1))
+    }
+
+    /// Decodes UTF-16BE bytes, as shown by fonts using the predefined
+    /// Unicode `CMap`s.
+    #[must_use]
+    pub fn decode_utf16(bytes: &[u8]) -> String {
+        utf16_text(bytes)
+    }
+
+    /// Encodes text as the codes that map to each of its characters, or
+    /// `None` if a character has no code.
+    #[must_use]
+    pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        self.encode_codes(text, |_| true)
+    }
+
+    /// Encodes text as codes of a fixed byte width, as used by composite
+    /// fonts.
+    #[must_use]
+    pub fn encode_with_width(&self, text: &str, width: usize) -> Option<Vec<u8>> {
+        self.encode_codes(text, |code_width| code_width == width)
+    }
+
+    // Several codes can map to the same character, in which case the
+    // narrowest and lowest is used.
+    fn encode_codes(&self, text: &str, accept_width: impl Fn(usize) -> bool) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            let c: &str = c.encode_utf8(&mut buffer);
+            let (&(width, code), _) = self
+                .mappings
+                .iter()
+                .find(|(&(width, _), mapped)| accept_width(width) && mapped.as_str() == c)?;
+            bytes.extend_from_slice(code.to_be_bytes().get(4usize.checked_sub(width)?..)?);
+        }
+        Some(bytes)
+    }
+
+    fn decode_codes(&self, bytes: &[u8], code_width: impl Fn(&[u8]) -> usize) -> String {
+        let mut text = String::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            // Empty codes in a malformed code space would never advance.
+            let width = code_width(rest).clamp(1, rest.len());
+            let code = code_value(&rest[..width]);
+            match self.mappings.get(&(width, code)) {
+                Some(mapped) => text.push_str(mapped),
+                None => text.push(char::REPLACEMENT_CHARACTER),
+            }
+            rest = &rest[width..];
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmap(body: &str) -> ToUnicodeMap {
+        ToUnicodeMap::parse(
+            format!(
+                "/CIDInit /ProcSet findresource begin\n\
+                 begincmap\n\
+                 1 begincodespacerange <00> <FF> endcodespacerange\n\
+                 {body}\n\
+                 endcmap"
+            )
+            .as_bytes(),
+        )
+    }
+
+    #[test]
+    fn bfchar_entries_map_single_codes() {
+        let map = cmap("2 beginbfchar <01> <0043> <02> <004D> endbfchar");
+        assert_eq!(map.decode(&[1, 2, 1]), "CMC");
+        assert_eq!(map.decode(&[3]), "\u{FFFD}");
+        assert_eq!(map.encode("MC"), Some(vec![2, 1]));
+    }
+
+    #[test]
+    fn bfrange_entries_with_an_array_map_each_code() {
+        let map = cmap("1 beginbfrange <10> <12> [<0050> <0054> <0058>] endbfrange");
+        assert_eq!(map.decode(&[0x10, 0x11, 0x12]), "PTX");
+        assert_eq!(map.decode(&[0x13]), "\u{FFFD}");
+    }
+
+    #[test]
+    fn bfrange_entries_with_one_destination_increment_it() {
+        let map = cmap("1 beginbfrange <20> <22> <0041> endbfrange");
+        assert_eq!(map.decode(&[0x20, 0x21, 0x22]), "ABC");
+    }
+
+    #[test]
+    fn surrogate_pairs_decode_to_one_character() {
+        let map = cmap(
+            "1 beginbfchar <01> <D835DC00> endbfchar\n\
+             1 beginbfrange <02> <03> <D835DC00> endbfrange",
+        );
+        assert_eq!(map.decode(&[1]), "\u{1D400}");
+        assert_eq!(map.decode(&[2, 3]), "\u{1D400}\u{1D401}");
+    }
+
+    #[test]
+    fn two_byte_code_spaces_are_read_as_such() {
+        let map = ToUnicodeMap::parse(
+            b"1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+              1 beginbfchar <0102> <0041> endbfchar",
+        );
+        assert_eq!(map.decode(&[1, 2]), "A");
+        assert_eq!(map.decode(&[1, 3]), "\u{FFFD}");
+    }
+
+    #[test]
+    fn malformed_cmaps_do_not_panic() {
+        let inputs: [&[u8]; 6] = [
+            b"beginbfchar <01 ",
+            b"beginbfrange <FF> <00> <0041> endbfrange",
+            b"beginbfrange <00> <FFFFFFFF> [<0041> endbfrange",
+            b"begincodespacerange <> <> endcodespacerange beginbfchar <> <> endbfchar",
+            b"beginbfchar <0G1> <0041 (unterminated \\",
+            b"\xff\xfe[[]]<<>>%",
+        ];
+        for input in inputs {
+            let map = ToUnicodeMap::parse(input);
+            let _ = map.decode(&[0, 1, 2, 0xFF]);
+            let _ = map.decode_with_width(&[0, 1, 2], 2);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Aggregation of a batch of students into cohort-level summaries.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::model::{term_key, StudentInfo};
+
+/// The number of students whose current plan is `plan`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PlanCount {
+    pub plan: String,
+    pub students: usize,
+}
+
+/// The state of an entry cohort a given number of terms after entry.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TrajectoryPoint {
+    /// The first term of the students in the cohort, e.g. "2017 Fall".
+    pub cohort: String,
+    /// The number of terms since entry, counting the entry term as 1.
+    pub term_number: usize,
+    /// The students in the cohort with at least this many terms.
+    pub students: usize,
+    /// The mean cumulative GPA of those students after this term, over the
+    /// students with a GPA.
+    pub mean_cgpa: Option<f64>,
+    /// The mean units earned by those students up to and including this
+    /// term, counting transfer credits from the start. This traces the
+    /// cohort's credit accumulation curve.
+    pub mean_units_earned: f64,
+}
+
+/// Summaries of a batch of students: how many are in each plan, and how
+/// each entry cohort's GPA and earned units develop term by term.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CohortReport {
+    pub students: usize,
+    pub plans: Vec<PlanCount>,
+    pub trajectories: Vec<TrajectoryPoint>,
+}
+
+#[derive(Debug, Default)]
+struct Accumulator {
+    students: usize,
+    cgpa_sum: f64,
+    cgpa_students: u32,
+    units_sum: f64,
+}
+
+/// Builds a [`CohortReport`] from students added one at a time, so batches
+/// can be summarized while they are exported.
+#[derive(Debug, Default)]
+pub struct CohortBuilder {
+    students: usize,
+    plans: BTreeMap<String, usize>,
+    // Keyed by the chronological key and label of the entry term, and the
+    // term number.
+    points: BTreeMap<((u32, usize), String, usize), Accumulator>,
+}
+
+impl CohortBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a student to the summaries. Students without any SFU terms only
+    /// count toward the plans.
+    pub fn add(&mut self, student: &StudentInfo) {
+        self.students += 1;
+        let plan = student.current_plan().map_or("", |plan| plan.name.as_str());
+        *self.plans.entry(plan.to_string()).or_default() += 1;
+
+        let Some(entry) = student.semesters.first() else {
+            return;
+        };
+        let cohort = (
+            term_key(&entry.year, &entry.term),
+            format!("{} {}", entry.year, entry.term),
+        );
+        let mut units: f64 = student
+            .transfers
+            .iter()
+            .filter_map(|transfer| transfer.units)
+            .sum();
+        for (index, semester) in student.semesters.iter().enumerate() {
+            units += semester
+                .courses
+                .iter()
+                .filter_map(|course| course.units_earned)
+                .sum::<f64>();
+            let point = self
+                .points
+                .entry((cohort.0, cohort.1.clone(), index + 1))
+                .or_default();
+            point.students += 1;
+            point.units_sum += units;
+            if let Some(cgpa) = semester.cgpa {
+                point.cgpa_sum += cgpa;
+                point.cgpa_students += 1;
+            }
+        }
+    }
+
+    /// Completes the report, with plans ordered by name and trajectories by
+    /// entry term and term number.
+    #[must_use]
+    pub fn finish(self) -> CohortReport {
+        let plans = self
+            .plans
+            .into_iter()
+            .map(|(plan, students)| PlanCount { plan, students })
+            .collect();
+        let trajectories = self
+            .points
+            .into_iter()
+            .map(|((_, cohort, term_number), point)| TrajectoryPoint {
+                cohort,
+                term_number,
+                students: point.students,
+                mean_cgpa: (point.cgpa_students > 0)
+                    .then(|| point.cgpa_sum / f64::from(point.cgpa_students)),
+                mean_units_earned: point.units_sum / count(point.students),
+            })
+            .collect();
+        CohortReport {
+            students: self.students,
+            plans,
+            trajectories,
+        }
+    }
+}
+
+// Every accumulated point has at least one student, and batches are far
+// smaller than `u32::MAX`.
+fn count(students: usize) -> f64 {
+    f64::from(u32::try_from(students).unwrap_or(u32::MAX))
+}
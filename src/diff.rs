@@ -0,0 +1,191 @@
+//! Comparison of two transcripts of the same student, such as snapshots
+//! pulled a term apart, to find what changed between them.
+
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use serde::Serialize;
+
+use crate::model::{Course, StudentInfo};
+
+/// How a course differs between the two transcripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The grade differs, such as a grade recorded for a course that was in
+    /// progress or a grade changed on appeal.
+    GradeRevised,
+    /// Another detail differs, such as the units earned or the title.
+    Changed,
+}
+
+impl ChangeKind {
+    /// The name of the kind as written in the output formats.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::GradeRevised => "grade_revised",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// A course or transfer credit that differs between the two transcripts.
+/// Transfer credits have no year or term.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CourseChange {
+    pub kind: ChangeKind,
+    pub year: Option<String>,
+    pub term: Option<String>,
+    pub subject: String,
+    pub id: String,
+    /// The course in the earlier transcript, unless it was added.
+    pub before: Option<Course>,
+    /// The course in the later transcript, unless it was removed.
+    pub after: Option<Course>,
+}
+
+/// The differences between an earlier and a later transcript.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TranscriptDiff {
+    /// Terms that only appear in the later transcript, e.g. "2020 Fall".
+    pub new_terms: Vec<String>,
+    /// Terms that only appear in the earlier transcript.
+    pub removed_terms: Vec<String>,
+    /// Courses and transfer credits that were added, removed, or changed,
+    /// with transfer credits first and then in term order.
+    pub courses: Vec<CourseChange>,
+}
+
+impl TranscriptDiff {
+    /// Whether the transcripts hold the same terms and courses.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.new_terms.is_empty() && self.removed_terms.is_empty() && self.courses.is_empty()
+    }
+}
+
+// Identifies a course by its term (empty for transfer credits), subject,
+// course ID, and the number of earlier courses with the same key, so that
+// repeated entries within a term are paired in order.
+type CourseKey = ((u32, usize), String, String, String, String, usize);
+
+fn course_map(student: &StudentInfo) -> BTreeMap<CourseKey, &Course> {
+    let transfers = student
+        .transfers
+        .iter()
+        .map(|transfer| ((0, 0), "", "", &transfer.course));
+    let courses = student.semesters.iter().flat_map(|semester| {
+        semester.courses.iter().map(|course| {
+            (
+                semester.term_key(),
+                semester.year.as_str(),
+                semester.term.as_str(),
+                course,
+            )
+        })
+    });
+    let mut map = BTreeMap::new();
+    for (term_key, year, term, course) in transfers.chain(courses) {
+        let mut occurrence = 0;
+        loop {
+            let key = (
+                term_key,
+                year.to_string(),
+                term.to_string(),
+                course.subject.clone(),
+                course.id.clone(),
+                occurrence,
+            );
+            if let Entry::Vacant(entry) = map.entry(key) {
+                entry.insert(course);
+                break;
+            }
+            occurrence += 1;
+        }
+    }
+    map
+}
+
+// Whether the details printed for a course other than its grade agree.
+// Attempt numbers are left out, as they change whenever a retake is added.
+fn same_details(earlier: &Course, later: &Course) -> bool {
+    earlier.title == later.title
+        && earlier.units_attempted == later.units_attempted
+        && earlier.units_earned == later.units_earned
+        && earlier.designations == later.designations
+}
+
+fn term_labels(student: &StudentInfo) -> BTreeMap<(u32, usize), String> {
+    student
+        .semesters
+        .iter()
+        .map(|semester| {
+            (
+                semester.term_key(),
+                format!("{} {}", semester.year, semester.term),
+            )
+        })
+        .collect()
+}
+
+/// Compares an earlier transcript of a student with a later one.
+#[must_use]
+pub fn diff(before: &StudentInfo, after: &StudentInfo) -> TranscriptDiff {
+    let before_terms = term_labels(before);
+    let after_terms = term_labels(after);
+    let new_terms = after_terms
+        .iter()
+        .filter(|(key, _)| !before_terms.contains_key(key))
+        .map(|(_, label)| label.clone())
+        .collect();
+    let removed_terms = before_terms
+        .iter()
+        .filter(|(key, _)| !after_terms.contains_key(key))
+        .map(|(_, label)| label.clone())
+        .collect();
+
+    let before_courses = course_map(before);
+    let after_courses = course_map(after);
+    let mut keys: Vec<&CourseKey> = before_courses.keys().chain(after_courses.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let courses = keys
+        .into_iter()
+        .filter_map(|key| {
+            let earlier = before_courses.get(key).copied();
+            let later = after_courses.get(key).copied();
+            let kind = match (earlier, later) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(earlier), Some(later)) if earlier.grade != later.grade => {
+                    ChangeKind::GradeRevised
+                }
+                (Some(earlier), Some(later)) if !same_details(earlier, later) => {
+                    ChangeKind::Changed
+                }
+                _ => return None,
+            };
+            let (_, year, term, subject, id, _) = key;
+            let label = |value: &String| Some(value.clone()).filter(|v| !v.is_empty());
+            Some(CourseChange {
+                kind,
+                year: label(year),
+                term: label(term),
+                subject: subject.clone(),
+                id: id.clone(),
+                before: earlier.cloned(),
+                after: later.cloned(),
+            })
+        })
+        .collect();
+
+    TranscriptDiff {
+        new_terms,
+        removed_terms,
+        courses,
+    }
+}
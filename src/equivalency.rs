@@ -0,0 +1,94 @@
+//! Tables mapping the courses of other institutions to the SFU courses they
+//! are articulated as, so that transfer credits can be analyzed as their SFU
+//! equivalents.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use serde::Deserialize;
+
+use crate::model::{Equivalent, StudentInfo};
+
+#[derive(Deserialize)]
+struct Row {
+    institution: String,
+    subject: String,
+    number: String,
+    sfu_subject: String,
+    sfu_number: String,
+}
+
+// Institutions and courses are matched ignoring case and surrounding space.
+fn normalize(value: &str) -> String {
+    value.trim().to_uppercase()
+}
+
+/// SFU equivalents of transfer courses, keyed by institution, subject, and
+/// course number.
+#[derive(Clone, Debug, Default)]
+pub struct EquivalencyTable {
+    equivalents: HashMap<(String, String, String), Equivalent>,
+}
+
+impl EquivalencyTable {
+    /// Reads a table from CSV with the header
+    /// `institution,subject,number,sfu_subject,sfu_number`, e.g.
+    /// `Langara College,CPSC,1150,CMPT,120`. Rows with an empty institution
+    /// apply to courses from any institution without a row of their own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CSV is malformed or lacks any of the columns.
+    pub fn from_csv<R: Read>(reader: R) -> csv::Result<Self> {
+        let mut equivalents = HashMap::new();
+        for row in csv::Reader::from_reader(reader).deserialize() {
+            let row: Row = row?;
+            let key = (
+                normalize(&row.institution),
+                normalize(&row.subject),
+                normalize(&row.number),
+            );
+            let equivalent = Equivalent {
+                subject: row.sfu_subject.trim().to_string(),
+                id: row.sfu_number.trim().to_string(),
+            };
+            equivalents.insert(key, equivalent);
+        }
+        Ok(Self { equivalents })
+    }
+
+    /// The SFU equivalent of a course from `institution`, falling back to
+    /// rows for any institution.
+    #[must_use]
+    pub fn equivalent(
+        &self,
+        institution: Option<&str>,
+        subject: &str,
+        number: &str,
+    ) -> Option<&Equivalent> {
+        let (subject, number) = (normalize(subject), normalize(number));
+        let lookup = |institution: &str| {
+            let key = (institution.to_string(), subject.clone(), number.clone());
+            self.equivalents.get(&key)
+        };
+        institution
+            .map(normalize)
+            .filter(|institution| !institution.is_empty())
+            .and_then(|institution| lookup(&institution))
+            .or_else(|| lookup(""))
+    }
+
+    /// Sets the equivalent of every transfer credit of the student that the
+    /// table lists, returning how many were found.
+    pub fn apply(&self, student: &mut StudentInfo) -> usize {
+        let mut found = 0;
+        for transfer in &mut student.transfers {
+            let course = &transfer.course;
+            transfer.equivalent = self
+                .equivalent(transfer.school.as_deref(), &course.subject, &course.id)
+                .cloned();
+            found += usize::from(transfer.equivalent.is_some());
+        }
+        found
+    }
+}
@@ -0,0 +1,163 @@
+use thiserror::Error;
+
+/// Errors that can occur while loading and parsing a transcript.
+#[derive(Debug, Error)]
+pub enum TranscriptError {
+    /// The transcript could not be read.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The PDF itself could not be loaded or decoded.
+    #[error("PDF error: {0}")]
+    Pdf(#[from] lopdf::Error),
+
+    /// A PESC transcript is not well-formed XML.
+    #[error("XML error: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    /// A page other than the last did not end with the SFU footer banner.
+    #[error("Footer banner not found near the end of page {page}")]
+    FooterNotFound { page: usize },
+
+    /// A grade outside of the known grade vocabulary was found.
+    #[error("Unknown grade `{value}` in row: {row}")]
+    UnknownGrade { value: String, row: String },
+
+    /// A required section of the transcript could not be located.
+    #[error("{0} section not found")]
+    MissingSection(&'static str),
+
+    /// A section was located but its contents do not have the expected shape.
+    #[error("{0} section is malformed")]
+    MalformedSection(&'static str),
+
+    /// The pages of the document could not be put into the requested order.
+    #[error("Cannot order pages: {0}")]
+    PageOrder(String),
+
+    /// A row within a section has fewer columns than expected.
+    #[error("Malformed row in {section} section: {row}")]
+    MalformedRow { section: &'static str, row: String },
+
+    /// A GPA computed from the courses differs from the one printed on the
+    /// transcript by more than the tolerance.
+    #[error(
+        "Computed {kind} GPA {computed:.3} differs from the printed {printed:.2} in {year} {term}"
+    )]
+    GpaMismatch {
+        year: String,
+        term: String,
+        kind: &'static str,
+        computed: f64,
+        printed: f64,
+    },
+
+    /// The units earned across the parsed courses and transfer credits do
+    /// not add up to the units passed printed at the end of the transcript,
+    /// as when rows were dropped.
+    #[error("Parsed courses earn {parsed:.2} units, but the transcript totals {printed:.2}")]
+    UnitTotalMismatch { parsed: f64, printed: f64 },
+}
+
+/// The broad kinds of failure, so that callers can tell them apart without
+/// matching every error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The file could not be read or is not a valid PDF or XML document.
+    Unreadable,
+    /// The PDF does not have the layout of a transcript.
+    Layout,
+    /// The transcript has the expected layout, but its contents fail a
+    /// sanity check.
+    Validation,
+}
+
+impl TranscriptError {
+    /// The kind of failure the error is.
+    #[must_use]
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Io(_) | Self::Pdf(_) | Self::Xml(_) => ErrorCategory::Unreadable,
+            Self::UnknownGrade { .. }
+            | Self::GpaMismatch { .. }
+            | Self::UnitTotalMismatch { .. } => ErrorCategory::Validation,
+            Self::FooterNotFound { .. }
+            | Self::MissingSection(_)
+            | Self::MalformedSection(_)
+            | Self::PageOrder(_)
+            | Self::MalformedRow { .. } => ErrorCategory::Layout,
+        }
+    }
+}
+
+/// Decides whether anomalies found while parsing abort the parse or are
+/// recorded as warnings so that the remaining data can still be extracted.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    lenient: bool,
+    strict_grades: bool,
+    warnings: Vec<String>,
+}
+
+impl Diagnostics {
+    /// Creates diagnostics that either abort on the first anomaly (`false`)
+    /// or record anomalies as warnings (`true`).
+    #[must_use]
+    pub fn new(lenient: bool) -> Self {
+        Self {
+            lenient,
+            strict_grades: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Makes unknown grades anomalies like any other (`true`), instead of
+    /// always recording them as warnings so that the course can be kept with
+    /// its grade marked invalid.
+    #[must_use]
+    pub fn with_strict_grades(mut self, strict: bool) -> Self {
+        self.strict_grades = strict;
+        self
+    }
+
+    /// Reports a recoverable anomaly. In lenient mode it is recorded as a
+    /// warning and parsing continues; otherwise it is returned as an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns `err` unless the diagnostics are lenient.
+    pub fn report(&mut self, err: TranscriptError) -> Result<(), TranscriptError> {
+        if self.lenient {
+            self.warnings.push(err.to_string());
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Reports a grade outside of the grade vocabulary, which is only an
+    /// anomaly when grades are strict and is otherwise recorded as a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns `err` if grades are strict and the diagnostics are not
+    /// lenient.
+    pub fn report_grade(&mut self, err: TranscriptError) -> Result<(), TranscriptError> {
+        if self.strict_grades {
+            self.report(err)
+        } else {
+            self.warnings.push(err.to_string());
+            Ok(())
+        }
+    }
+
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    #[must_use]
+    pub fn into_warnings(self) -> Vec<String> {
+        self.warnings
+    }
+}
@@ -0,0 +1,361 @@
+//! Writers that serialize parsed transcripts into the supported output
+//! formats. Every exporter receives students one at a time along with their
+//! anonymized ID, so batches can be written without holding every transcript
+//! in memory. The real student ID is never written.
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "avro")]
+pub mod avro;
+pub mod cohort;
+pub mod csv;
+#[cfg(feature = "duckdb")]
+pub mod duckdb;
+pub mod edi;
+pub mod html;
+pub mod json;
+pub mod manifest;
+pub mod markdown;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod pesc;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod progress;
+#[cfg(feature = "proto")]
+pub mod proto;
+pub mod risk;
+pub mod sql;
+pub mod sqlite;
+pub mod table;
+pub mod xlsx;
+
+use std::collections::BTreeSet;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::model::{
+    Course, Credential, Designation, Equivalent, GroupUnits, HonourRoll, Milestone, Note, Plan,
+    Semester, StudentInfo, TermType, Transfer,
+};
+
+/// The version of the output layouts, written with every record (or once
+/// per file, where a format has a place for it) so downstream loaders can
+/// detect format changes. Bump it whenever a column or field is added,
+/// removed, renamed, or changes meaning.
+pub const SCHEMA_VERSION: u32 = 8;
+
+pub trait Exporter {
+    /// Writes the records for one student under the anonymized `new_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()>;
+
+    /// Completes the output once all students have been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// The serializable view of a student with the real student ID replaced by
+/// the anonymized one.
+#[derive(Clone, Debug, Serialize)]
+pub struct AnonymizedStudent<'a> {
+    pub schema_version: u32,
+    pub id: &'a str,
+    pub plans: &'a [Plan],
+    pub transfers: &'a [Transfer],
+    pub semesters: &'a [Semester],
+    pub credentials: &'a [Credential],
+    pub milestones: &'a [Milestone],
+    pub notes: &'a [Note],
+    pub unit_totals: &'a [GroupUnits],
+    pub warnings: &'a [String],
+}
+
+impl<'a> AnonymizedStudent<'a> {
+    #[must_use]
+    pub fn new(new_id: &'a str, student: &'a StudentInfo) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: new_id,
+            plans: &student.plans,
+            transfers: &student.transfers,
+            semesters: &student.semesters,
+            credentials: &student.credentials,
+            milestones: &student.milestones,
+            notes: &student.notes,
+            unit_totals: &student.unit_totals,
+            warnings: &student.warnings,
+        }
+    }
+}
+
+/// One row of the "long" layout: a single transfer credit or SFU course.
+/// Transfer credits have no year or term, and only transfer credits can have
+/// a school, apart from courses taken on exchange. The plan is the one in
+/// effect during the course's term, while transfer credits are listed under
+/// the plan the student was admitted to.
+#[derive(Clone, Copy, Debug)]
+pub struct LongRecord<'a> {
+    pub id: &'a str,
+    pub plan: &'a str,
+    pub year: Option<&'a str>,
+    pub term: Option<&'a str>,
+    pub term_type: Option<TermType>,
+    pub career: Option<&'a str>,
+    pub academic_group: Option<&'a str>,
+    pub course: &'a Course,
+    /// The SFU equivalent of a transfer credit.
+    pub equivalent: Option<&'a Equivalent>,
+    /// The transfer institution, or the host institution of an exchange term.
+    pub school: Option<&'a str>,
+    pub is_transfer: bool,
+    pub term_gpa: Option<f64>,
+    pub cumulative_gpa: Option<f64>,
+    /// The GPAs as printed on the transcript rather than computed.
+    pub printed_term_gpa: Option<f64>,
+    pub printed_cumulative_gpa: Option<f64>,
+    pub standing: Option<&'a str>,
+    pub honour_roll: Option<HonourRoll>,
+    pub transfer_units: Option<f64>,
+    pub credentials: &'a [Credential],
+}
+
+/// Describes credentials in a single field as "program (date)" entries
+/// separated by semicolons.
+#[must_use]
+pub fn describe_credentials(credentials: &[Credential]) -> String {
+    credentials
+        .iter()
+        .map(|credential| match &credential.conferred {
+            Some(date) => format!("{} ({date})", credential.program),
+            None => credential.program.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Describes the WQB designations of a course in a single field as their
+/// transcript tags separated by semicolons, e.g. "W; B-Hum".
+#[must_use]
+pub fn describe_designations(designations: &[Designation]) -> String {
+    designations
+        .iter()
+        .map(|designation| designation.tag())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Describes the plan history of a student in a single field, with the term
+/// each change took effect, e.g. "CMPTMAJ, then CMPTHON (from 2019 Spring)".
+#[must_use]
+pub fn describe_plans(plans: &[Plan]) -> String {
+    plans
+        .iter()
+        .map(|plan| match (&plan.effective_year, &plan.effective_term) {
+            (Some(year), Some(term)) => format!("{} (from {year} {term})", plan.name),
+            _ => plan.name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", then ")
+}
+
+/// Describes a course by its subject and number, noting which attempt it
+/// is when the course was taken before, e.g. "CMPT 120 (attempt 2)".
+#[must_use]
+pub fn describe_course(course: &Course) -> String {
+    let name = format!("{} {}", course.subject, course.id);
+    if course.attempt > 1 {
+        format!("{name} (attempt {})", course.attempt)
+    } else {
+        name
+    }
+}
+
+/// The grade of a course, or "In progress" for one without a grade yet.
+#[must_use]
+pub fn describe_grade(course: &Course) -> &str {
+    if course.grade.is_empty() {
+        "In progress"
+    } else {
+        &course.grade
+    }
+}
+
+/// Summarizes the GPAs, kind, standing, and honour roll of a term on one
+/// line, e.g. "Term GPA 3.50 · Cumulative GPA 3.21 · Good Standing".
+#[must_use]
+pub fn describe_term(semester: &Semester) -> String {
+    let gpa = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let mut parts = vec![
+        format!("Term GPA {}", gpa(semester.gpa)),
+        format!("Cumulative GPA {}", gpa(semester.cgpa)),
+    ];
+    match semester.term_type {
+        TermType::Academic => {}
+        TermType::CoOp => parts.push("Co-op work term".to_string()),
+        TermType::Exchange => parts.push(match &semester.host_institution {
+            Some(host) => format!("Exchange at {host}"),
+            None => "Exchange".to_string(),
+        }),
+    }
+    parts.extend(semester.standing.clone());
+    parts.extend(semester.honour_roll.map(|honour_roll| {
+        match honour_roll {
+            HonourRoll::DeansHonourRoll => "Dean's Honour Roll",
+            HonourRoll::PresidentsHonourRoll => "President's Honour Roll",
+        }
+        .to_string()
+    }));
+    parts.join(" · ")
+}
+
+/// Formats a time as `YYYY-MM-DDThh:mm:ssZ`, finding the date from the days
+/// since the epoch with the civil calendar algorithm of Howard Hinnant.
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+/// Pairs each semester with the name of the plan in effect during it.
+pub fn semester_plans(student: &StudentInfo) -> impl Iterator<Item = (&Semester, &str)> {
+    let first_plan = student.first_plan().map_or("", |plan| plan.name.as_str());
+    student.semesters.iter().scan(first_plan, |plan, semester| {
+        if let Some(change) = student.plans.iter().rfind(|p| p.starts_in(semester)) {
+            *plan = change.name.as_str();
+        }
+        Some((semester, *plan))
+    })
+}
+
+/// Flattens a student into the rows of the long layout, with transfer
+/// credits first followed by courses in transcript order.
+pub fn long_records<'a>(
+    new_id: &'a str,
+    student: &'a StudentInfo,
+) -> impl Iterator<Item = LongRecord<'a>> {
+    let first_plan = student.first_plan().map_or("", |plan| plan.name.as_str());
+    let transfers = student.transfers.iter().map(move |transfer| LongRecord {
+        id: new_id,
+        plan: first_plan,
+        year: None,
+        term: None,
+        term_type: None,
+        career: None,
+        academic_group: None,
+        course: &transfer.course,
+        equivalent: transfer.equivalent.as_ref(),
+        school: transfer.school.as_deref(),
+        is_transfer: true,
+        term_gpa: None,
+        cumulative_gpa: None,
+        printed_term_gpa: None,
+        printed_cumulative_gpa: None,
+        standing: None,
+        honour_roll: None,
+        transfer_units: transfer.units,
+        credentials: &student.credentials,
+    });
+    let courses = semester_plans(student).flat_map(move |(semester, plan)| {
+        semester.courses.iter().map(move |course| LongRecord {
+            id: new_id,
+            plan,
+            year: Some(semester.year.as_str()),
+            term: Some(semester.term.as_str()),
+            term_type: Some(semester.term_type),
+            career: semester.career.as_deref(),
+            academic_group: semester.academic_group.as_deref(),
+            course,
+            equivalent: None,
+            school: semester.host_institution.as_deref(),
+            is_transfer: false,
+            term_gpa: semester.gpa,
+            cumulative_gpa: semester.cgpa,
+            printed_term_gpa: semester.printed_gpa,
+            printed_cumulative_gpa: semester.printed_cgpa,
+            standing: semester.standing.as_deref(),
+            honour_roll: semester.honour_roll,
+            transfer_units: None,
+            credentials: &student.credentials,
+        })
+    });
+    transfers.chain(courses)
+}
+
+/// Identifies a row of the long layout across runs by the anonymized ID,
+/// the year and term (none for transfer credits), the institution (that of
+/// a transfer credit, or the host of an exchange term), and the subject and
+/// number of its course. The institution tells apart transfer credits of
+/// the same course from different schools, such as `ENGL 1XX`.
+pub type RowKey = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+);
+
+/// Removes the transfer credits and courses of `student` whose rows under
+/// `new_id` are among `present`, such as the rows already in an output
+/// being appended to. Returns the number of rows removed.
+pub fn remove_present_rows(
+    student: &mut StudentInfo,
+    new_id: &str,
+    present: &BTreeSet<RowKey>,
+) -> usize {
+    let key = |year: Option<&str>, term: Option<&str>, school: Option<&str>, course: &Course| {
+        (
+            new_id.to_string(),
+            year.map(str::to_string),
+            term.map(str::to_string),
+            school.map(str::to_string),
+            course.subject.clone(),
+            course.id.clone(),
+        )
+    };
+    let before = long_records(new_id, student).count();
+    student.transfers.retain(|transfer| {
+        !present.contains(&key(
+            None,
+            None,
+            transfer.school.as_deref(),
+            &transfer.course,
+        ))
+    });
+    for semester in &mut student.semesters {
+        let (year, term) = (Some(semester.year.as_str()), Some(semester.term.as_str()));
+        let school = semester.host_institution.as_deref();
+        semester
+            .courses
+            .retain(|course| !present.contains(&key(year, term, school, course)));
+    }
+    before - long_records(new_id, student).count()
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Float64Builder, Int32Builder, ListBuilder, StringBuilder, UInt32Builder,
+};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+
+use crate::export::{describe_credentials, long_records, Exporter, LongRecord, SCHEMA_VERSION};
+use crate::model::{HonourRoll, StudentInfo, TermType};
+
+/// The typed schema of the long layout. The layout version is recorded both
+/// as the `schema_version` column and under the same key in the schema
+/// metadata.
+#[must_use]
+pub fn long_schema() -> SchemaRef {
+    let metadata = HashMap::from([("schema_version".to_string(), SCHEMA_VERSION.to_string())]);
+    let fields = vec![
+        Field::new("student_id", DataType::Utf8, false),
+        Field::new("program", DataType::Utf8, false),
+        Field::new("year", DataType::Int32, true),
+        Field::new("term", DataType::Utf8, true),
+        Field::new("subject", DataType::Utf8, false),
+        Field::new("course_id", DataType::Utf8, false),
+        Field::new("grade", DataType::Utf8, false),
+        Field::new("transfer_institution", DataType::Utf8, true),
+        Field::new("is_transfer", DataType::Boolean, false),
+        Field::new("term_gpa", DataType::Float64, true),
+        Field::new("cumulative_gpa", DataType::Float64, true),
+        Field::new("units_attempted", DataType::Float64, true),
+        Field::new("units_earned", DataType::Float64, true),
+        Field::new("title", DataType::Utf8, false),
+        Field::new("standing", DataType::Utf8, true),
+        Field::new("credentials", DataType::Utf8, true),
+        Field::new("transfer_units", DataType::Float64, true),
+        Field::new(
+            "designations",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            false,
+        ),
+        Field::new("outcome", DataType::Utf8, false),
+        Field::new("attempt", DataType::UInt32, false),
+        Field::new("is_latest_attempt", DataType::Boolean, false),
+        Field::new("honour_roll", DataType::Utf8, true),
+        Field::new("term_type", DataType::Utf8, true),
+        Field::new("grade_points", DataType::Float64, true),
+        Field::new("grade_valid", DataType::Boolean, false),
+        Field::new("printed_term_gpa", DataType::Float64, true),
+        Field::new("printed_cumulative_gpa", DataType::Float64, true),
+        Field::new("career", DataType::Utf8, true),
+        Field::new("academic_group", DataType::Utf8, true),
+        Field::new("equivalent_subject", DataType::Utf8, true),
+        Field::new("equivalent_number", DataType::Utf8, true),
+        Field::new("schema_version", DataType::UInt32, false),
+    ];
+    Arc::new(Schema::new_with_metadata(fields, metadata))
+}
+
+// The columns between `grade_valid` and `schema_version`, built from the
+// rows of one student at once rather than row by row.
+fn added_columns<'a>(records: &[LongRecord<'a>]) -> Vec<ArrayRef> {
+    let decimals = |value: fn(&LongRecord) -> Option<f64>| -> ArrayRef {
+        Arc::new(records.iter().map(value).collect::<Float64Array>())
+    };
+    let strings = |value: fn(&LongRecord<'a>) -> Option<&'a str>| -> ArrayRef {
+        Arc::new(records.iter().map(value).collect::<StringArray>())
+    };
+    vec![
+        decimals(|record| record.printed_term_gpa),
+        decimals(|record| record.printed_cumulative_gpa),
+        strings(|record| record.career),
+        strings(|record| record.academic_group),
+        strings(|record| {
+            record
+                .equivalent
+                .map(|equivalent| equivalent.subject.as_str())
+        }),
+        strings(|record| record.equivalent.map(|equivalent| equivalent.id.as_str())),
+    ]
+}
+
+/// Builds an Arrow record batch holding the long layout rows of one student.
+///
+/// # Errors
+///
+/// Returns an error if the columns do not match the schema.
+pub fn long_record_batch(
+    schema: &SchemaRef,
+    new_id: &str,
+    student: &StudentInfo,
+) -> io::Result<RecordBatch> {
+    let mut ids = StringBuilder::new();
+    let mut plans = StringBuilder::new();
+    let mut years = Int32Builder::new();
+    let mut terms = StringBuilder::new();
+    let mut subjects = StringBuilder::new();
+    let mut course_ids = StringBuilder::new();
+    let mut grades = StringBuilder::new();
+    let mut schools = StringBuilder::new();
+    let mut transfers = BooleanBuilder::new();
+    let mut gpas = Float64Builder::new();
+    let mut cgpas = Float64Builder::new();
+    let mut attempted = Float64Builder::new();
+    let mut earned = Float64Builder::new();
+    let mut titles = StringBuilder::new();
+    let mut standings = StringBuilder::new();
+    let mut credentials = StringBuilder::new();
+    let mut transfer_units = Float64Builder::new();
+    let mut designations = ListBuilder::new(StringBuilder::new());
+    let mut outcomes = StringBuilder::new();
+    let mut attempts = UInt32Builder::new();
+    let mut latest = BooleanBuilder::new();
+    let mut honour_rolls = StringBuilder::new();
+    let mut term_types = StringBuilder::new();
+    let mut grade_points = Float64Builder::new();
+    let mut valid = BooleanBuilder::new();
+    let mut versions = UInt32Builder::new();
+
+    let described = describe_credentials(&student.credentials);
+    let described = Some(described.as_str()).filter(|s| !s.is_empty());
+
+    let records: Vec<LongRecord> = long_records(new_id, student).collect();
+    for record in &records {
+        ids.append_value(record.id);
+        plans.append_value(record.plan);
+        years.append_option(record.year.and_then(|y| y.parse().ok()));
+        terms.append_option(record.term);
+        subjects.append_value(&record.course.subject);
+        course_ids.append_value(&record.course.id);
+        grades.append_value(&record.course.grade);
+        schools.append_option(record.school);
+        transfers.append_value(record.is_transfer);
+        gpas.append_option(record.term_gpa);
+        cgpas.append_option(record.cumulative_gpa);
+        attempted.append_option(record.course.units_attempted);
+        earned.append_option(record.course.units_earned);
+        titles.append_value(&record.course.title);
+        standings.append_option(record.standing);
+        credentials.append_option(described);
+        transfer_units.append_option(record.transfer_units);
+        designations.append_value(
+            record
+                .course
+                .designations
+                .iter()
+                .map(|designation| Some(designation.tag())),
+        );
+        outcomes.append_value(record.course.outcome.as_str());
+        attempts.append_value(record.course.attempt);
+        latest.append_value(record.course.is_latest_attempt);
+        honour_rolls.append_option(record.honour_roll.map(HonourRoll::as_str));
+        term_types.append_option(record.term_type.map(TermType::as_str));
+        grade_points.append_option(record.course.grade_points);
+        valid.append_value(record.course.grade_valid);
+        versions.append_value(SCHEMA_VERSION);
+    }
+
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(ids.finish()),
+        Arc::new(plans.finish()),
+        Arc::new(years.finish()),
+        Arc::new(terms.finish()),
+        Arc::new(subjects.finish()),
+        Arc::new(course_ids.finish()),
+        Arc::new(grades.finish()),
+        Arc::new(schools.finish()),
+        Arc::new(transfers.finish()),
+        Arc::new(gpas.finish()),
+        Arc::new(cgpas.finish()),
+        Arc::new(attempted.finish()),
+        Arc::new(earned.finish()),
+        Arc::new(titles.finish()),
+        Arc::new(standings.finish()),
+        Arc::new(credentials.finish()),
+        Arc::new(transfer_units.finish()),
+        Arc::new(designations.finish()),
+        Arc::new(outcomes.finish()),
+        Arc::new(attempts.finish()),
+        Arc::new(latest.finish()),
+        Arc::new(honour_rolls.finish()),
+        Arc::new(term_types.finish()),
+        Arc::new(grade_points.finish()),
+        Arc::new(valid.finish()),
+    ];
+    columns.extend(added_columns(&records));
+    columns.push(Arc::new(versions.finish()));
+    RecordBatch::try_new(Arc::clone(schema), columns).map_err(io::Error::other)
+}
+
+/// Writes the long layout rows to an Arrow IPC (Feather v2) file, which can
+/// be memory mapped by Arrow readers without any parsing.
+pub struct ArrowIpcExporter {
+    schema: SchemaRef,
+    writer: Option<FileWriter<File>>,
+}
+
+impl ArrowIpcExporter {
+    /// Creates the Arrow IPC file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let schema = long_schema();
+        let file = File::create(path)?;
+        let writer = FileWriter::try_new(file, &schema).map_err(io::Error::other)?;
+        Ok(Self {
+            schema,
+            writer: Some(writer),
+        })
+    }
+}
+
+impl Exporter for ArrowIpcExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let batch = long_record_batch(&self.schema, new_id, student)?;
+        match self.writer.as_mut() {
+            Some(writer) => writer.write(&batch).map_err(io::Error::other),
+            None => Err(io::Error::other("Arrow writer already finished")),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.writer.take() {
+            Some(mut writer) => writer.finish().map_err(io::Error::other),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use apache_avro::{Schema, Writer};
+use serde::Serialize;
+
+use crate::export::{describe_credentials, long_records, Exporter, SCHEMA_VERSION};
+use crate::model::{HonourRoll, StudentInfo, TermType};
+
+/// The Avro schema of the long layout, with the same fields as the Arrow
+/// schema. Optional fields are unions with `null`.
+pub const LONG_SCHEMA: &str = r#"{
+  "type": "record",
+  "name": "TranscriptRow",
+  "namespace": "ca.sfu.transcript",
+  "fields": [
+    {"name": "student_id", "type": "string"},
+    {"name": "program", "type": "string"},
+    {"name": "year", "type": ["null", "int"], "default": null},
+    {"name": "term", "type": ["null", "string"], "default": null},
+    {"name": "subject", "type": "string"},
+    {"name": "course_id", "type": "string"},
+    {"name": "grade", "type": "string"},
+    {"name": "transfer_institution", "type": ["null", "string"], "default": null},
+    {"name": "is_transfer", "type": "boolean"},
+    {"name": "term_gpa", "type": ["null", "double"], "default": null},
+    {"name": "cumulative_gpa", "type": ["null", "double"], "default": null},
+    {"name": "units_attempted", "type": ["null", "double"], "default": null},
+    {"name": "units_earned", "type": ["null", "double"], "default": null},
+    {"name": "title", "type": "string"},
+    {"name": "standing", "type": ["null", "string"], "default": null},
+    {"name": "credentials", "type": ["null", "string"], "default": null},
+    {"name": "transfer_units", "type": ["null", "double"], "default": null},
+    {"name": "designations", "type": {"type": "array", "items": "string"}},
+    {"name": "outcome", "type": "string"},
+    {"name": "attempt", "type": "long"},
+    {"name": "is_latest_attempt", "type": "boolean"},
+    {"name": "honour_roll", "type": ["null", "string"], "default": null},
+    {"name": "term_type", "type": ["null", "string"], "default": null},
+    {"name": "grade_points", "type": ["null", "double"], "default": null},
+    {"name": "grade_valid", "type": "boolean"},
+    {"name": "printed_term_gpa", "type": ["null", "double"], "default": null},
+    {"name": "printed_cumulative_gpa", "type": ["null", "double"], "default": null},
+    {"name": "career", "type": ["null", "string"], "default": null},
+    {"name": "academic_group", "type": ["null", "string"], "default": null},
+    {"name": "equivalent_subject", "type": ["null", "string"], "default": null},
+    {"name": "equivalent_number", "type": ["null", "string"], "default": null},
+    {"name": "schema_version", "type": "long"}
+  ]
+}"#;
+
+// The writer borrows its schema for as long as it lives, so the parsed
+// schema is kept for the whole run.
+fn long_schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::parse_str(LONG_SCHEMA).expect("the long schema is valid"))
+}
+
+#[derive(Serialize)]
+struct Row<'a> {
+    student_id: &'a str,
+    program: &'a str,
+    year: Option<i32>,
+    term: Option<&'a str>,
+    subject: &'a str,
+    course_id: &'a str,
+    grade: &'a str,
+    transfer_institution: Option<&'a str>,
+    is_transfer: bool,
+    term_gpa: Option<f64>,
+    cumulative_gpa: Option<f64>,
+    units_attempted: Option<f64>,
+    units_earned: Option<f64>,
+    title: &'a str,
+    standing: Option<&'a str>,
+    credentials: Option<&'a str>,
+    transfer_units: Option<f64>,
+    designations: Vec<&'static str>,
+    outcome: &'static str,
+    attempt: i64,
+    is_latest_attempt: bool,
+    honour_roll: Option<&'static str>,
+    term_type: Option<&'static str>,
+    grade_points: Option<f64>,
+    grade_valid: bool,
+    printed_term_gpa: Option<f64>,
+    printed_cumulative_gpa: Option<f64>,
+    career: Option<&'a str>,
+    academic_group: Option<&'a str>,
+    equivalent_subject: Option<&'a str>,
+    equivalent_number: Option<&'a str>,
+    schema_version: i64,
+}
+
+/// Writes the long layout rows to an Avro object container file, which
+/// carries its schema in its header. The layout version is also recorded
+/// under `schema_version` in the file metadata.
+pub struct AvroExporter {
+    writer: Writer<'static, BufWriter<File>>,
+}
+
+impl AvroExporter {
+    /// Creates the Avro file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut writer = Writer::new(long_schema(), file).map_err(io::Error::other)?;
+        writer
+            .add_user_metadata("schema_version".to_string(), SCHEMA_VERSION.to_string())
+            .map_err(io::Error::other)?;
+        Ok(Self { writer })
+    }
+}
+
+impl Exporter for AvroExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let described = describe_credentials(&student.credentials);
+        let described = Some(described.as_str()).filter(|s| !s.is_empty());
+        for record in long_records(new_id, student) {
+            let row = Row {
+                student_id: record.id,
+                program: record.plan,
+                year: record.year.and_then(|y| y.parse().ok()),
+                term: record.term,
+                subject: &record.course.subject,
+                course_id: &record.course.id,
+                grade: &record.course.grade,
+                transfer_institution: record.school,
+                is_transfer: record.is_transfer,
+                term_gpa: record.term_gpa,
+                cumulative_gpa: record.cumulative_gpa,
+                units_attempted: record.course.units_attempted,
+                units_earned: record.course.units_earned,
+                title: &record.course.title,
+                standing: record.standing,
+                credentials: described,
+                transfer_units: record.transfer_units,
+                designations: record
+                    .course
+                    .designations
+                    .iter()
+                    .map(|designation| designation.tag())
+                    .collect(),
+                outcome: record.course.outcome.as_str(),
+                attempt: i64::from(record.course.attempt),
+                is_latest_attempt: record.course.is_latest_attempt,
+                honour_roll: record.honour_roll.map(HonourRoll::as_str),
+                term_type: record.term_type.map(TermType::as_str),
+                grade_points: record.course.grade_points,
+                grade_valid: record.course.grade_valid,
+                printed_term_gpa: record.printed_term_gpa,
+                printed_cumulative_gpa: record.printed_cumulative_gpa,
+                career: record.career,
+                academic_group: record.academic_group,
+                equivalent_subject: record.equivalent.map(|e| e.subject.as_str()),
+                equivalent_number: record.equivalent.map(|e| e.id.as_str()),
+                schema_version: i64::from(SCHEMA_VERSION),
+            };
+            self.writer.append_ser(row).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! Writers for the cohort-level report produced alongside a batch export.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::cohort::CohortReport;
+use crate::export::csv::CsvOptions;
+use crate::export::SCHEMA_VERSION;
+
+/// The columns of the cohort report CSV. Each row is one measure, with the
+/// columns that do not apply to it left empty.
+pub const COHORT_COLUMNS: [&str; 7] = [
+    "Measure",
+    "Cohort",
+    "Plan",
+    "Term Number",
+    "Students",
+    "Value",
+    "Schema Version",
+];
+
+/// Writes the report as a tidy CSV with the measures `students_per_plan`,
+/// `mean_cgpa`, and `mean_units_earned`.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_cohort_csv<W: Write>(
+    writer: W,
+    report: &CohortReport,
+    options: CsvOptions,
+) -> io::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    if options.header {
+        writer.write_record(COHORT_COLUMNS)?;
+    }
+    let version = SCHEMA_VERSION.to_string();
+    for plan in &report.plans {
+        let students = plan.students.to_string();
+        writer.write_record([
+            "students_per_plan",
+            "",
+            &plan.plan,
+            "",
+            &students,
+            &students,
+            &version,
+        ])?;
+    }
+    for point in &report.trajectories {
+        let term_number = point.term_number.to_string();
+        let students = point.students.to_string();
+        let cgpa = point.mean_cgpa.map(|cgpa| format!("{cgpa:.2}"));
+        let units = format!("{:.2}", point.mean_units_earned);
+        writer.write_record([
+            "mean_cgpa",
+            &point.cohort,
+            "",
+            &term_number,
+            &students,
+            cgpa.as_deref().unwrap_or_default(),
+            &version,
+        ])?;
+        writer.write_record([
+            "mean_units_earned",
+            &point.cohort,
+            "",
+            &term_number,
+            &students,
+            &units,
+            &version,
+        ])?;
+    }
+    writer.flush()
+}
+
+#[derive(Serialize)]
+struct VersionedReport<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    report: &'a CohortReport,
+}
+
+/// Writes the report as a single JSON object.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_cohort_json<W: Write>(mut writer: W, report: &CohortReport) -> io::Result<()> {
+    let versioned = VersionedReport {
+        schema_version: SCHEMA_VERSION,
+        report,
+    };
+    serde_json::to_writer_pretty(&mut writer, &versioned)?;
+    writeln!(writer)
+}
@@ -0,0 +1,755 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Read, Write};
+
+use serde::Serialize;
+
+use crate::export::{
+    describe_credentials, describe_designations, long_records, Exporter, LongRecord, RowKey,
+    SCHEMA_VERSION,
+};
+use crate::model::{term_key, Course, HonourRoll, StudentInfo, TermType};
+use crate::stats::{self, GradeDistribution, DISTRIBUTION_GRADES};
+
+/// The name and value type of a column in the long CSV layout.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Column {
+    /// The short name selecting the column with `--columns`.
+    pub key: &'static str,
+    pub name: &'static str,
+    /// One of `string`, `integer`, `decimal`, or `boolean`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Whether the column may hold an empty or `None` value.
+    pub nullable: bool,
+    /// Whether the column is left out unless asked for.
+    pub optional: bool,
+}
+
+const fn column(
+    key: &'static str,
+    name: &'static str,
+    kind: &'static str,
+    nullable: bool,
+) -> Column {
+    Column {
+        key,
+        name,
+        kind,
+        nullable,
+        optional: false,
+    }
+}
+
+const fn optional(column: Column) -> Column {
+    Column {
+        optional: true,
+        ..column
+    }
+}
+
+/// The columns of the long CSV layout, in order. Optional columns are only
+/// written when chosen, and the rest make up [`default_long_columns`].
+pub const LONG_COLUMNS: [Column; 31] = [
+    column("id", "Student ID", "string", false),
+    column("program", "Program", "string", false),
+    column("year", "Year", "integer", true),
+    column("term", "Term", "string", true),
+    column("subject", "Subject", "string", false),
+    column("number", "Course ID", "string", false),
+    column("grade", "Grade", "string", false),
+    column("institution", "Transfer Institution", "string", true),
+    column("term_gpa", "Term GPA", "decimal", true),
+    column("cgpa", "Cumulative GPA", "decimal", true),
+    optional(column(
+        "printed_term_gpa",
+        "Printed Term GPA",
+        "decimal",
+        true,
+    )),
+    optional(column(
+        "printed_cgpa",
+        "Printed Cumulative GPA",
+        "decimal",
+        true,
+    )),
+    column("attempted", "Units Attempted", "decimal", true),
+    column("earned", "Units Earned", "decimal", true),
+    column("title", "Title", "string", false),
+    column("standing", "Academic Standing", "string", true),
+    column("credentials", "Credentials", "string", true),
+    column("transfer_units", "Transfer Units", "decimal", true),
+    column("designations", "Designations", "string", true),
+    column("outcome", "Outcome", "string", false),
+    column("attempt", "Attempt", "integer", false),
+    column("latest_attempt", "Latest Attempt", "boolean", false),
+    column("honour_roll", "Honour Roll", "string", true),
+    column("term_type", "Term Type", "string", true),
+    column("grade_points", "Grade Points", "decimal", true),
+    column("grade_valid", "Grade Valid", "boolean", false),
+    optional(column("career", "Career", "string", true)),
+    optional(column("academic_group", "Academic Group", "string", true)),
+    optional(column(
+        "equivalent_subject",
+        "Equivalent Subject",
+        "string",
+        true,
+    )),
+    optional(column(
+        "equivalent_number",
+        "Equivalent Course ID",
+        "string",
+        true,
+    )),
+    column("schema_version", "Schema Version", "integer", false),
+];
+
+/// Settings shared by the CSV layouts.
+#[derive(Clone, Copy, Debug)]
+pub struct CsvOptions {
+    /// The byte separating fields, such as `b','` or `b'\t'`.
+    pub delimiter: u8,
+    /// Whether to start the output with a header row.
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    fn writer<W: Write>(self, writer: W) -> csv::Writer<W> {
+        csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(writer)
+    }
+}
+
+/// Returns the position in [`LONG_COLUMNS`] of the column with the given
+/// key, such as `number` for the course ID.
+#[must_use]
+pub fn long_column(key: &str) -> Option<usize> {
+    LONG_COLUMNS.iter().position(|column| column.key == key)
+}
+
+/// The positions in [`LONG_COLUMNS`] of the columns written by default.
+#[must_use]
+pub fn default_long_columns() -> Vec<usize> {
+    (0..LONG_COLUMNS.len())
+        .filter(|&i| !LONG_COLUMNS[i].optional)
+        .collect()
+}
+
+/// An order for the rows of the long layout across a whole batch. Ties are
+/// broken by the remaining columns, so the order is fully deterministic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowOrder {
+    /// Chronologically, with transfer credits first, then by course.
+    Term,
+    /// By subject and course number, then chronologically.
+    Course,
+    /// From the most grade points to the least, with grades that carry none
+    /// last, then by course and term.
+    Grade,
+}
+
+/// A buffered row along with the values it is sorted by.
+struct SortedRow {
+    term: (u32, usize),
+    subject: String,
+    number: String,
+    grade_points: Option<f64>,
+    fields: Vec<String>,
+}
+
+impl SortedRow {
+    fn compare(&self, other: &Self, order: RowOrder) -> Ordering {
+        let term = || self.term.cmp(&other.term);
+        let course = || (&self.subject, &self.number).cmp(&(&other.subject, &other.number));
+        // Reversed, so that the most grade points come first and `None` last.
+        let grade = || {
+            other
+                .grade_points
+                .partial_cmp(&self.grade_points)
+                .unwrap_or(Ordering::Equal)
+        };
+        match order {
+            RowOrder::Term => term().then_with(course),
+            RowOrder::Course => course().then_with(term),
+            RowOrder::Grade => grade().then_with(course).then_with(term),
+        }
+        .then_with(|| self.fields.cmp(&other.fields))
+    }
+}
+
+/// Writes the "long" CSV layout with one row per transfer credit or course.
+pub struct LongCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    header_pending: bool,
+    columns: Vec<usize>,
+    order: Option<RowOrder>,
+    rows: Vec<SortedRow>,
+}
+
+impl<W: Write> LongCsvExporter<W> {
+    /// Creates an exporter that starts with a header row of the column names
+    /// when `options.header` is set.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self::with_columns(writer, options, default_long_columns())
+    }
+
+    /// Creates an exporter that only writes the given columns, as positions
+    /// in [`LONG_COLUMNS`], in the order given.
+    pub fn with_columns(writer: W, options: CsvOptions, columns: Vec<usize>) -> Self {
+        Self {
+            writer: options.writer(writer),
+            header_pending: options.header,
+            columns,
+            order: None,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Writes the rows of the whole batch in the given order instead of
+    /// student by student. The rows are then held until `finish`.
+    #[must_use]
+    pub fn sorted(mut self, order: RowOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.header_pending) {
+            self.writer
+                .write_record(self.columns.iter().map(|&i| LONG_COLUMNS[i].name))?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for LongCsvExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.write_header()?;
+        if self.order.is_none() {
+            return write_long_csv_columns(&mut self.writer, student, new_id, &self.columns);
+        }
+        for_each_long_row(student, new_id, |record, fields| {
+            self.rows.push(SortedRow {
+                term: match (record.year, record.term) {
+                    (Some(year), Some(term)) => term_key(year, term),
+                    _ => (0, 0),
+                },
+                subject: record.course.subject.clone(),
+                number: record.course.id.clone(),
+                grade_points: record.course.grade_points,
+                fields: self
+                    .columns
+                    .iter()
+                    .map(|&i| fields[i].to_string())
+                    .collect(),
+            });
+            Ok(())
+        })
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // An empty batch still produces the header.
+        self.write_header()?;
+        if let Some(order) = self.order {
+            let mut rows = std::mem::take(&mut self.rows);
+            rows.sort_by(|a, b| a.compare(b, order));
+            for row in rows {
+                self.writer.write_record(&row.fields)?;
+            }
+        }
+        self.writer.flush()
+    }
+}
+
+/// The columns that make up the [`RowKey`] of a row, by key.
+const ROW_KEY_COLUMNS: [&str; 6] = ["id", "year", "term", "institution", "subject", "number"];
+
+/// Reads the keys of the rows of an existing output of the long layout,
+/// written with the same options and columns, so that appending to it can
+/// leave out the rows it already has.
+///
+/// # Errors
+///
+/// Returns an error if the output cannot be read or starts with the header
+/// of other columns, or if the columns leave out part of the key.
+pub fn read_long_row_keys<R: Read>(
+    reader: R,
+    options: CsvOptions,
+    columns: &[usize],
+) -> io::Result<BTreeSet<RowKey>> {
+    let positions: Option<Vec<usize>> = ROW_KEY_COLUMNS
+        .iter()
+        .map(|&key| columns.iter().position(|&i| LONG_COLUMNS[i].key == key))
+        .collect();
+    let positions = positions.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Rows can only be matched with the {} columns",
+                ROW_KEY_COLUMNS.join(", ")
+            ),
+        )
+    })?;
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(false)
+        .from_reader(reader);
+    let mut keys = BTreeSet::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        if index == 0 && options.header {
+            let names = columns.iter().map(|&i| LONG_COLUMNS[i].name);
+            if !record.iter().eq(names) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "The existing output has other columns than the ones being written",
+                ));
+            }
+            continue;
+        }
+        let field = |part: usize| record.get(positions[part]).unwrap_or_default();
+        // Transfer credits have "None" for their year and term, and for an
+        // unknown institution, which SFU courses leave empty.
+        let optional_field =
+            |part: usize| Some(field(part).to_string()).filter(|f| !f.is_empty() && f != "None");
+        keys.insert((
+            field(0).to_string(),
+            optional_field(1),
+            optional_field(2),
+            optional_field(3),
+            field(4).to_string(),
+            field(5).to_string(),
+        ));
+    }
+    Ok(keys)
+}
+
+fn format_decimal(value: Option<f64>, missing: &str) -> String {
+    value.map_or_else(|| missing.to_string(), |v| format!("{v:.2}"))
+}
+
+/// Writes the transfer and course rows of one student in the long layout.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_long_csv<W: Write>(
+    writer: &mut csv::Writer<W>,
+    student: &StudentInfo,
+    new_id: &str,
+) -> io::Result<()> {
+    write_long_csv_columns(writer, student, new_id, &default_long_columns())
+}
+
+/// Writes the rows of one student in the long layout like
+/// [`write_long_csv`], with only the given columns of [`LONG_COLUMNS`].
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+///
+/// # Panics
+///
+/// Panics if a column is out of range of [`LONG_COLUMNS`].
+pub fn write_long_csv_columns<W: Write>(
+    writer: &mut csv::Writer<W>,
+    student: &StudentInfo,
+    new_id: &str,
+    columns: &[usize],
+) -> io::Result<()> {
+    for_each_long_row(student, new_id, |_, fields| {
+        Ok(writer.write_record(columns.iter().map(|&i| fields[i]))?)
+    })?;
+    writer.flush()
+}
+
+// Calls `write` with every row of one student in the long layout, as the
+// fields of all of `LONG_COLUMNS`.
+fn for_each_long_row(
+    student: &StudentInfo,
+    new_id: &str,
+    mut write: impl FnMut(&LongRecord, [&str; LONG_COLUMNS.len()]) -> io::Result<()>,
+) -> io::Result<()> {
+    let credentials = describe_credentials(&student.credentials);
+    let version = SCHEMA_VERSION.to_string();
+    for record in long_records(new_id, student) {
+        // Transfer credits are marked with "None" for the missing fields,
+        // while SFU courses leave the school empty.
+        let missing = if record.is_transfer { "None" } else { "" };
+        let gpa = format_decimal(record.term_gpa, missing);
+        let cgpa = format_decimal(record.cumulative_gpa, missing);
+        let printed = [record.printed_term_gpa, record.printed_cumulative_gpa]
+            .map(|value| format_decimal(value, missing));
+        let attempted = format_decimal(record.course.units_attempted, missing);
+        let earned = format_decimal(record.course.units_earned, missing);
+        let transfer_units = format_decimal(record.transfer_units, missing);
+        let grade_points = format_decimal(record.course.grade_points, "");
+        let fields: [&str; LONG_COLUMNS.len()] = [
+            new_id,
+            record.plan,
+            record.year.unwrap_or("None"),
+            record.term.unwrap_or("None"),
+            &record.course.subject,
+            &record.course.id,
+            &record.course.grade,
+            record.school.unwrap_or(missing),
+            &gpa,
+            &cgpa,
+            &printed[0],
+            &printed[1],
+            &attempted,
+            &earned,
+            &record.course.title,
+            record.standing.unwrap_or(missing),
+            &credentials,
+            &transfer_units,
+            &describe_designations(&record.course.designations),
+            record.course.outcome.as_str(),
+            &record.course.attempt.to_string(),
+            if record.course.is_latest_attempt {
+                "true"
+            } else {
+                "false"
+            },
+            record.honour_roll.map_or("", HonourRoll::as_str),
+            record.term_type.map_or("None", TermType::as_str),
+            &grade_points,
+            if record.course.grade_valid {
+                "true"
+            } else {
+                "false"
+            },
+            record.career.unwrap_or(missing),
+            record.academic_group.unwrap_or(missing),
+            record.equivalent.map_or(missing, |e| e.subject.as_str()),
+            record.equivalent.map_or(missing, |e| e.id.as_str()),
+            &version,
+        ];
+        write(&record, fields)?;
+    }
+    Ok(())
+}
+
+struct WideRow {
+    id: String,
+    plan: String,
+    credentials: String,
+    grades: BTreeMap<String, String>,
+}
+
+/// Writes the "wide" CSV layout with one row per student and one column per
+/// course holding the grade earned. Because the set of columns depends on
+/// every student, rows are buffered and only written by `finish`.
+pub struct WideCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    rows: Vec<WideRow>,
+    header: bool,
+}
+
+impl<W: Write> WideCsvExporter<W> {
+    /// Creates an exporter that starts with a header row naming the courses
+    /// when `options.header` is set.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self {
+            writer: options.writer(writer),
+            rows: Vec::new(),
+            header: options.header,
+        }
+    }
+}
+
+impl<W: Write> Exporter for WideCsvExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        // Repeated attempts at a course keep only the most recent grade, as
+        // records are ordered from transfers through to the latest term.
+        let grades = long_records(new_id, student)
+            .map(|record| {
+                (
+                    format!("{} {}", record.course.subject, record.course.id),
+                    record.course.grade.clone(),
+                )
+            })
+            .collect();
+        self.rows.push(WideRow {
+            id: new_id.to_string(),
+            plan: student
+                .current_plan()
+                .map(|plan| plan.name.clone())
+                .unwrap_or_default(),
+            credentials: describe_credentials(&student.credentials),
+            grades,
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let courses: BTreeSet<&str> = self
+            .rows
+            .iter()
+            .flat_map(|row| row.grades.keys().map(String::as_str))
+            .collect();
+
+        let header = ["Student ID", "Program", "Credentials", "Schema Version"]
+            .into_iter()
+            .chain(courses.iter().copied());
+        if self.header {
+            self.writer.write_record(header)?;
+        }
+        let version = SCHEMA_VERSION.to_string();
+        for row in &self.rows {
+            let cells = courses
+                .iter()
+                .map(|course| row.grades.get(*course).map_or("", String::as_str));
+            let fields = [
+                row.id.as_str(),
+                row.plan.as_str(),
+                row.credentials.as_str(),
+                version.as_str(),
+            ];
+            self.writer.write_record(fields.into_iter().chain(cells))?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// Writes one row per course across the whole batch, with the number of
+/// enrollments, a histogram of the grades, and the withdrawal and DFW rates.
+/// Transfer credits are left out. Rows are only written by `finish`, once
+/// every student has been seen.
+pub struct CourseCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    courses: BTreeMap<(String, String), GradeDistribution>,
+    header: bool,
+}
+
+impl<W: Write> CourseCsvExporter<W> {
+    /// Creates an exporter that starts with a header row when
+    /// `options.header` is set.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self {
+            writer: options.writer(writer),
+            courses: BTreeMap::new(),
+            header: options.header,
+        }
+    }
+}
+
+impl<W: Write> Exporter for CourseCsvExporter<W> {
+    fn write_student(&mut self, _new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        stats::add_by_course(&mut self.courses, student);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.header {
+            let header = ["Subject", "Course ID", "Enrollments"]
+                .into_iter()
+                .chain(DISTRIBUTION_GRADES)
+                .chain([
+                    "Other",
+                    "Mean Grade Points",
+                    "Withdrawal Rate",
+                    "DFW Rate",
+                    "Schema Version",
+                ]);
+            self.writer.write_record(header)?;
+        }
+        for ((subject, id), distribution) in &self.courses {
+            let counts = DISTRIBUTION_GRADES
+                .iter()
+                .map(|grade| distribution.count(grade).to_string());
+            let fields = [
+                subject.clone(),
+                id.clone(),
+                distribution.enrollments.to_string(),
+            ]
+            .into_iter()
+            .chain(counts)
+            .chain([
+                distribution.other.to_string(),
+                format_decimal(distribution.mean_grade_points(), ""),
+                format_rate(distribution.withdrawal_rate()),
+                format_rate(distribution.dfw_rate()),
+                SCHEMA_VERSION.to_string(),
+            ]);
+            self.writer.write_record(fields)?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// The columns of the GPA trajectory layout.
+pub const GPA_COLUMNS: [&str; 9] = [
+    "Student ID",
+    "Term Number",
+    "Year",
+    "Term",
+    "Term GPA",
+    "Cumulative GPA",
+    "Graded Units",
+    "Cumulative Graded Units",
+    "Schema Version",
+];
+
+// The grade points and attempted units of the courses that carry grade
+// points, which follow whatever grade scale was applied to them.
+fn graded_units(courses: &[Course]) -> (f64, f64) {
+    courses
+        .iter()
+        .filter_map(|c| Some((c.grade_points?, c.units_attempted?)))
+        .fold((0.0, 0.0), |(points, units), (grade, attempted)| {
+            (points + grade * attempted, units + attempted)
+        })
+}
+
+/// Writes the GPA trajectory of each student as a tidy time series, with a
+/// row per term numbered from 1 in chronological order. The GPAs are
+/// computed from the grade points of the courses written, so they follow
+/// the grade scale in use and cover only the courses that were kept. Terms
+/// without graded courses have no term GPA, and the cumulative GPA carries
+/// on from the terms before, counting only the latest attempt at a repeated
+/// course.
+pub struct GpaCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    header_pending: bool,
+}
+
+impl<W: Write> GpaCsvExporter<W> {
+    /// Creates an exporter that starts with a header row when
+    /// `options.header` is set.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self {
+            writer: options.writer(writer),
+            header_pending: options.header,
+        }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.header_pending) {
+            self.writer.write_record(GPA_COLUMNS)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for GpaCsvExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.write_header()?;
+        let version = SCHEMA_VERSION.to_string();
+        let (mut total_points, mut total_units) = (0.0, 0.0);
+        // A retaken course replaces the grade points of its earlier attempt
+        // in the cumulative GPA.
+        let mut counted: HashMap<(&str, &str), (f64, f64)> = HashMap::new();
+        for (index, semester) in student.semesters.iter().enumerate() {
+            let (points, units) = graded_units(&semester.courses);
+            for course in &semester.courses {
+                let key = (course.subject.as_str(), course.id.as_str());
+                let (points, units) = graded_units(std::slice::from_ref(course));
+                if let Some((earlier_points, earlier_units)) = counted.insert(key, (points, units))
+                {
+                    total_points -= earlier_points;
+                    total_units -= earlier_units;
+                }
+                total_points += points;
+                total_units += units;
+            }
+            let gpa = (units > 0.0).then(|| points / units);
+            let cgpa = (total_units > 0.0).then(|| total_points / total_units);
+            self.writer.write_record([
+                new_id,
+                &(index + 1).to_string(),
+                &semester.year,
+                &semester.term,
+                &format_decimal(gpa, ""),
+                &format_decimal(cgpa, ""),
+                &format_decimal(Some(units), ""),
+                &format_decimal(Some(total_units), ""),
+                &version,
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // An empty batch still produces the header.
+        self.write_header()?;
+        self.writer.flush()
+    }
+}
+
+/// The columns of the term load table.
+pub const TERM_LOAD_COLUMNS: [&str; 8] = [
+    "Student ID",
+    "Year",
+    "Term",
+    "Courses",
+    "Units Attempted",
+    "Units Earned",
+    "Withdrawals",
+    "Schema Version",
+];
+
+/// Writes the course load of every term of each student, with a row per
+/// student and term.
+pub struct TermLoadCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    header_pending: bool,
+}
+
+impl<W: Write> TermLoadCsvExporter<W> {
+    /// Creates an exporter that starts with a header row when
+    /// `options.header` is set.
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self {
+            writer: options.writer(writer),
+            header_pending: options.header,
+        }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.header_pending) {
+            self.writer.write_record(TERM_LOAD_COLUMNS)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for TermLoadCsvExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.write_header()?;
+        let version = SCHEMA_VERSION.to_string();
+        for semester in &student.semesters {
+            let load = semester.load();
+            self.writer.write_record([
+                new_id,
+                &semester.year,
+                &semester.term,
+                &load.courses.to_string(),
+                &format_decimal(Some(load.units_attempted), ""),
+                &format_decimal(Some(load.units_earned), ""),
+                &load.withdrawals.to_string(),
+                &version,
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+}
+
+fn format_rate(value: Option<f64>) -> String {
+    value.map_or_else(String::new, |v| format!("{v:.4}"))
+}
@@ -0,0 +1,159 @@
+use std::io;
+use std::path::Path;
+
+use duckdb::{params, Connection};
+
+use crate::export::{describe_credentials, long_records, Exporter, SCHEMA_VERSION};
+use crate::model::{HonourRoll, StudentInfo, TermType};
+
+/// The table holding the long layout rows.
+pub const TABLE: &str = "transcript_rows";
+
+// Rows are appended here first, as the appender cannot write lists, and
+// moved into the table once every student is written.
+const STAGING: &str = "transcript_rows_staging";
+
+// The columns of the long layout, named and typed as in the Arrow schema.
+const COLUMNS: [(&str, &str); 32] = [
+    ("student_id", "VARCHAR NOT NULL"),
+    ("program", "VARCHAR NOT NULL"),
+    ("year", "INTEGER"),
+    ("term", "VARCHAR"),
+    ("subject", "VARCHAR NOT NULL"),
+    ("course_id", "VARCHAR NOT NULL"),
+    ("grade", "VARCHAR NOT NULL"),
+    ("transfer_institution", "VARCHAR"),
+    ("is_transfer", "BOOLEAN NOT NULL"),
+    ("term_gpa", "DOUBLE"),
+    ("cumulative_gpa", "DOUBLE"),
+    ("units_attempted", "DOUBLE"),
+    ("units_earned", "DOUBLE"),
+    ("title", "VARCHAR NOT NULL"),
+    ("standing", "VARCHAR"),
+    ("credentials", "VARCHAR"),
+    ("transfer_units", "DOUBLE"),
+    ("designations", "VARCHAR[] NOT NULL"),
+    ("outcome", "VARCHAR NOT NULL"),
+    ("attempt", "UINTEGER NOT NULL"),
+    ("is_latest_attempt", "BOOLEAN NOT NULL"),
+    ("honour_roll", "VARCHAR"),
+    ("term_type", "VARCHAR"),
+    ("grade_points", "DOUBLE"),
+    ("grade_valid", "BOOLEAN NOT NULL"),
+    ("printed_term_gpa", "DOUBLE"),
+    ("printed_cumulative_gpa", "DOUBLE"),
+    ("career", "VARCHAR"),
+    ("academic_group", "VARCHAR"),
+    ("equivalent_subject", "VARCHAR"),
+    ("equivalent_number", "VARCHAR"),
+    ("schema_version", "UINTEGER NOT NULL"),
+];
+
+fn to_io(err: duckdb::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+/// Writes the long layout rows to the `transcript_rows` table of a `DuckDB`
+/// database, with the same typed columns as the Arrow schema.
+pub struct DuckDbExporter {
+    conn: Connection,
+}
+
+impl DuckDbExporter {
+    /// Opens or creates the `DuckDB` database at `path`, creating the table if
+    /// it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or the table cannot
+    /// be created.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let conn = Connection::open(path).map_err(to_io)?;
+        let definitions = |staged: bool| {
+            let columns: Vec<String> = COLUMNS
+                .iter()
+                .map(|(name, kind)| match *name {
+                    // Designations are staged as text, separated by spaces.
+                    "designations" if staged => format!("{name} VARCHAR NOT NULL"),
+                    _ => format!("{name} {kind}"),
+                })
+                .collect();
+            columns.join(", ")
+        };
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {TABLE} ({});
+             CREATE OR REPLACE TABLE {STAGING} ({});",
+            definitions(false),
+            definitions(true)
+        ))
+        .map_err(to_io)?;
+        Ok(Self { conn })
+    }
+}
+
+impl Exporter for DuckDbExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let described = describe_credentials(&student.credentials);
+        let described = Some(described.as_str()).filter(|s| !s.is_empty());
+        let mut appender = self.conn.appender(STAGING).map_err(to_io)?;
+        for record in long_records(new_id, student) {
+            let designations: Vec<&str> = record
+                .course
+                .designations
+                .iter()
+                .map(|designation| designation.tag())
+                .collect();
+            appender
+                .append_row(params![
+                    record.id,
+                    record.plan,
+                    record.year.and_then(|y| y.parse::<i32>().ok()),
+                    record.term,
+                    record.course.subject,
+                    record.course.id,
+                    record.course.grade,
+                    record.school,
+                    record.is_transfer,
+                    record.term_gpa,
+                    record.cumulative_gpa,
+                    record.course.units_attempted,
+                    record.course.units_earned,
+                    record.course.title,
+                    record.standing,
+                    described,
+                    record.transfer_units,
+                    designations.join(" "),
+                    record.course.outcome.as_str(),
+                    record.course.attempt,
+                    record.course.is_latest_attempt,
+                    record.honour_roll.map(HonourRoll::as_str),
+                    record.term_type.map(TermType::as_str),
+                    record.course.grade_points,
+                    record.course.grade_valid,
+                    record.printed_term_gpa,
+                    record.printed_cumulative_gpa,
+                    record.career,
+                    record.academic_group,
+                    record.equivalent.map(|equivalent| &equivalent.subject),
+                    record.equivalent.map(|equivalent| &equivalent.id),
+                    SCHEMA_VERSION,
+                ])
+                .map_err(to_io)?;
+        }
+        appender.flush().map_err(to_io)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.conn
+            .execute_batch(&format!(
+                "BEGIN;
+                 INSERT INTO {TABLE} SELECT * REPLACE (
+                     CASE WHEN designations = '' THEN []::VARCHAR[]
+                          ELSE string_split(designations, ' ') END AS designations
+                 ) FROM {STAGING};
+                 DROP TABLE {STAGING};
+                 COMMIT;"
+            ))
+            .map_err(to_io)
+    }
+}
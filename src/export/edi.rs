@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use crate::export::{utc_timestamp, Exporter};
+use crate::model::{Course, Semester, StudentInfo};
+
+/// The institution that issued the transcripts, and the sender of every
+/// transaction.
+const INSTITUTION: &str = "SIMON FRASER UNIVERSITY";
+
+/// The ID of the sender and receiver in the interchange envelope, padded to
+/// the fixed width the ISA segment requires.
+const PARTNER_ID: &str = "SFU";
+
+/// The control number of the interchange and of its one functional group.
+const CONTROL_NUMBER: u32 = 1;
+
+// Replaces the delimiters of the interchange, which can't be escaped, with
+// spaces.
+fn sanitize(value: &str) -> String {
+    value.replace(['*', '~', '>'], " ")
+}
+
+// The year and month a term starts in, as `CCYYMM`.
+fn session_start(semester: &Semester) -> Option<String> {
+    let month = match semester.term.as_str() {
+        "Spring" => "01",
+        "Summer" => "05",
+        "Fall" => "09",
+        _ => return None,
+    };
+    Some(format!("{}{month}", semester.year))
+}
+
+// A conferral date as `CCYYMMDD`, if it is an ISO date.
+fn award_date(conferred: &str) -> Option<String> {
+    let digits: String = conferred.chars().filter(char::is_ascii_digit).collect();
+    let iso = conferred.len() == 10 && conferred.as_bytes()[4] == b'-';
+    (iso && digits.len() == 8).then_some(digits)
+}
+
+fn units(value: Option<f64>) -> String {
+    value.map(|units| units.to_string()).unwrap_or_default()
+}
+
+/// Writes the students as TS130 Student Educational Record (Transcript)
+/// transactions of an ASC X12 interchange, the EDI format exchanged between
+/// registrars through SPEEDE.
+///
+/// The batch is one interchange holding one functional group, with a
+/// transaction set per student. Terms are written as `SES` segments followed
+/// by a `CRS` segment for each course, credentials as `DEG` segments without
+/// a degree code, which the transcript doesn't give, and the cumulative GPA
+/// as a `SUM` segment. Transfer credits follow under a session named for the
+/// institution they came from, with `T` as their basis for credit. The student
+/// is identified by their anonymized ID alone, with the required name given
+/// as `WITHHELD`.
+///
+/// Segments end in `~` and a line break, and elements are separated by `*`.
+/// Those characters and `>`, the sub-element separator, are replaced with
+/// spaces in values.
+pub struct EdiExporter<W: Write> {
+    writer: W,
+    created: String,
+    started: bool,
+    transactions: usize,
+    segments: usize,
+}
+
+impl<W: Write> EdiExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            created: utc_timestamp(SystemTime::now()),
+            started: false,
+            transactions: 0,
+            segments: 0,
+        }
+    }
+
+    // The creation date as `CCYYMMDD` and time as `HHMM`.
+    fn date_time(&self) -> (String, String) {
+        let date = self.created[..10].replace('-', "");
+        let time = self.created[11..16].replace(':', "");
+        (date, time)
+    }
+
+    // Writes a segment, leaving out trailing empty elements.
+    fn segment(&mut self, id: &str, elements: &[&str]) -> io::Result<()> {
+        let used = elements
+            .iter()
+            .rposition(|e| !e.is_empty())
+            .map_or(0, |i| i + 1);
+        write!(self.writer, "{id}")?;
+        for element in &elements[..used] {
+            write!(self.writer, "*{}", sanitize(element))?;
+        }
+        writeln!(self.writer, "~")?;
+        self.segments += 1;
+        Ok(())
+    }
+
+    fn start_interchange(&mut self) -> io::Result<()> {
+        if self.started {
+            return Ok(());
+        }
+        self.started = true;
+        let (date, time) = self.date_time();
+        writeln!(
+            self.writer,
+            "ISA*00*{:10}*00*{:10}*ZZ*{PARTNER_ID:15}*ZZ*{PARTNER_ID:15}*{}*{time}*U*00401*{CONTROL_NUMBER:09}*0*P*>~",
+            "",
+            "",
+            &date[2..],
+        )?;
+        let control = CONTROL_NUMBER.to_string();
+        self.segment(
+            "GS",
+            &[
+                "ED", PARTNER_ID, PARTNER_ID, &date, &time, &control, "X", "004010",
+            ],
+        )
+    }
+
+    fn course(&mut self, basis: &str, course: &Course) -> io::Result<()> {
+        let quality = match (course.grade_points, course.units_attempted) {
+            (Some(points), Some(units)) => format!("{:.2}", points * units),
+            _ => String::new(),
+        };
+        let repeat = if course.attempt > 1 { "R" } else { "" };
+        self.segment(
+            "CRS",
+            &[
+                basis,
+                "S",
+                &units(course.units_attempted),
+                &units(course.units_earned),
+                "",
+                &course.grade,
+                "",
+                "",
+                repeat,
+                &quality,
+                "",
+                "",
+                &course.subject,
+                &course.id,
+                &course.title,
+            ],
+        )
+    }
+
+    fn session(&mut self, semester: &Semester) -> io::Result<()> {
+        let start = session_start(semester).unwrap_or_default();
+        let name = format!("{} {}", semester.year, semester.term);
+        self.segment("SES", &[&start, "", &semester.year, "2", &name])?;
+        if let Some(gpa) = semester.gpa {
+            self.summary("N", &semester.courses.iter().collect::<Vec<_>>(), gpa)?;
+        }
+        for course in &semester.courses {
+            self.course("R", course)?;
+        }
+        Ok(())
+    }
+
+    // An academic summary of the courses, cumulative or for one term.
+    fn summary(&mut self, cumulative: &str, courses: &[&Course], gpa: f64) -> io::Result<()> {
+        let attempted: f64 = courses.iter().filter_map(|c| c.units_attempted).sum();
+        let earned: f64 = courses.iter().filter_map(|c| c.units_earned).sum();
+        let points: f64 = courses
+            .iter()
+            .filter_map(|c| Some(c.grade_points? * c.units_attempted?))
+            .sum();
+        self.segment(
+            "SUM",
+            &[
+                "S",
+                "",
+                cumulative,
+                "",
+                &attempted.to_string(),
+                &earned.to_string(),
+                "",
+                "",
+                &format!("{points:.2}"),
+                &format!("{gpa:.2}"),
+            ],
+        )
+    }
+
+    fn transfers(&mut self, student: &StudentInfo) -> io::Result<()> {
+        let mut schools: BTreeMap<&str, Vec<Course>> = BTreeMap::new();
+        for transfer in &student.transfers {
+            let school = transfer.school.as_deref().unwrap_or("Unknown Institution");
+            schools
+                .entry(school)
+                .or_default()
+                .push(transfer.credited_course());
+        }
+        for (school, courses) in schools {
+            self.segment("SES", &["", "", "", "2", school])?;
+            for course in &courses {
+                self.course("T", course)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for EdiExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.start_interchange()?;
+        self.transactions += 1;
+        self.segments = 0;
+        let control = format!("{:04}", self.transactions);
+        let (date, time) = self.date_time();
+        self.segment("ST", &["130", &control])?;
+        self.segment("BGN", &["00", new_id, &date, &time])?;
+        self.segment("N1", &["AS", INSTITUTION])?;
+        self.segment("N1", &["AT", INSTITUTION])?;
+        self.segment("IN1", &["1", "04", "LR", new_id])?;
+        self.segment("IN2", &["05", "WITHHELD"])?;
+        if let Some(cgpa) = student.semesters.iter().rev().find_map(|s| s.cgpa) {
+            let courses: Vec<&Course> = student
+                .semesters
+                .iter()
+                .flat_map(|semester| &semester.courses)
+                .collect();
+            self.summary("Y", &courses, cgpa)?;
+        }
+        for credential in &student.credentials {
+            let date = credential.conferred.as_deref().and_then(award_date);
+            let qualifier = if date.is_some() { "D8" } else { "" };
+            self.segment(
+                "DEG",
+                &[
+                    "",
+                    qualifier,
+                    date.as_deref().unwrap_or_default(),
+                    &credential.program,
+                ],
+            )?;
+        }
+        for semester in &student.semesters {
+            self.session(semester)?;
+        }
+        self.transfers(student)?;
+        for note in &student.notes {
+            self.segment("NTE", &["ADD", &note.text])?;
+        }
+        let count = (self.segments + 1).to_string();
+        self.segment("SE", &[&count, &control])
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.start_interchange()?;
+        let control = CONTROL_NUMBER.to_string();
+        self.segment("GE", &[&self.transactions.to_string(), &control])?;
+        self.segment("IEA", &["1", &format!("{CONTROL_NUMBER:09}")])?;
+        self.writer.flush()
+    }
+}
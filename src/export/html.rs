@@ -0,0 +1,314 @@
+use std::io::{self, Write};
+
+use crate::export::{
+    describe_course, describe_credentials, describe_designations, describe_grade, describe_plans,
+    describe_term, Exporter,
+};
+use crate::model::{Semester, StudentInfo};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 60rem; color: #222; }
+h1 { border-bottom: 2px solid #a6192e; padding-bottom: 0.25rem; }
+section + section { margin-top: 3rem; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1rem; }
+th, td { border-bottom: 1px solid #ddd; padding: 0.3rem 0.5rem; text-align: left; }
+th { background: #f4f4f4; cursor: pointer; user-select: none; }
+th[data-order=asc]::after { content: ' \\25B2'; }
+th[data-order=desc]::after { content: ' \\25BC'; }
+td.number { text-align: right; }
+.summary { color: #555; }
+.warning { color: #a6192e; }
+svg { width: 100%; height: auto; }
+svg .term { stroke: #999; fill: none; stroke-dasharray: 4 3; }
+svg .cumulative { stroke: #a6192e; fill: none; stroke-width: 2; }
+svg text { font-size: 10px; fill: #555; }
+";
+
+// Clicking a column header sorts the rows of its table by that column,
+// numerically where both cells are numbers and by their text otherwise.
+// Cells can override the value they sort by with `data-sort`.
+const SCRIPT: &str = "
+document.querySelectorAll('table.sortable th').forEach((th) => th.addEventListener('click', () => {
+  const table = th.closest('table');
+  const ascending = th.dataset.order !== 'asc';
+  table.querySelectorAll('th').forEach((other) => delete other.dataset.order);
+  th.dataset.order = ascending ? 'asc' : 'desc';
+  const key = (row) => {
+    const cell = row.cells[th.cellIndex];
+    return cell.dataset.sort ?? cell.textContent;
+  };
+  const rows = [...table.tBodies[0].rows].sort((a, b) => {
+    const [x, y] = [key(a), key(b)];
+    const order = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+    return ascending ? order : -order;
+  });
+  table.tBodies[0].append(...rows);
+}));
+";
+
+/// The highest GPA on SFU's scale, which bounds the charts.
+const MAX_GPA: f64 = 4.33;
+
+/// Writes a standalone HTML page with a styled report per student: the plans
+/// and credentials, a chart of the term and cumulative GPAs, and sortable
+/// tables of the transfer credits and of the courses in every term. Batches
+/// of several students get an index linking to each report. The page needs
+/// no network access to display.
+pub struct HtmlExporter<W: Write> {
+    writer: W,
+    // The reports are kept until the end, as the index precedes them.
+    reports: Vec<u8>,
+    index: Vec<(String, String)>,
+}
+
+impl<W: Write> HtmlExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            reports: Vec::new(),
+            index: Vec::new(),
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn decimal(value: Option<f64>) -> String {
+    value.map_or_else(String::new, |v| format!("{v:.2}"))
+}
+
+// Values missing from numeric columns sort before every number.
+fn sort_key(value: Option<f64>) -> String {
+    value.map_or_else(|| "-1".to_string(), |v| v.to_string())
+}
+
+fn anchor(new_id: &str) -> String {
+    format!("student-{}", escape(new_id))
+}
+
+// A line chart of the term and cumulative GPAs, with a labelled point per
+// term. Terms without a GPA, such as co-op terms, have no point on the term
+// GPA line.
+fn write_chart<W: Write>(writer: &mut W, semesters: &[Semester]) -> io::Result<()> {
+    if semesters
+        .iter()
+        .all(|s| s.gpa.is_none() && s.cgpa.is_none())
+    {
+        return Ok(());
+    }
+    let (left, top, height, step) = (30.0, 10.0, 160.0, 60.0);
+    let x = |i: u32| left + step * (f64::from(i) + 0.5);
+    let y = |gpa: f64| top + height * (1.0 - gpa.clamp(0.0, MAX_GPA) / MAX_GPA);
+    let terms = u32::try_from(semesters.len()).unwrap_or(u32::MAX);
+    let width = x(terms) + step / 2.0;
+    writeln!(
+        writer,
+        "<svg viewBox=\"0 0 {width:.0} {:.0}\" role=\"img\" aria-label=\"GPA by term\">",
+        top + height + 50.0
+    )?;
+    for gpa in 0..=4 {
+        let y = y(f64::from(gpa));
+        writeln!(
+            writer,
+            "<line x1=\"{left}\" x2=\"{width:.0}\" y1=\"{y:.1}\" y2=\"{y:.1}\" stroke=\"#eee\"/>\
+             <text x=\"{:.0}\" y=\"{:.1}\">{gpa}.0</text>",
+            left - 22.0,
+            y + 3.0
+        )?;
+    }
+    let points = |gpa: fn(&Semester) -> Option<f64>| {
+        (0..)
+            .zip(semesters)
+            .filter_map(|(i, semester)| Some((i, gpa(semester)?)))
+            .collect::<Vec<_>>()
+    };
+    for (class, points) in [
+        ("term", points(|s| s.gpa)),
+        ("cumulative", points(|s| s.cgpa)),
+    ] {
+        let line: Vec<String> = points
+            .iter()
+            .map(|&(i, gpa)| format!("{:.1},{:.1}", x(i), y(gpa)))
+            .collect();
+        writeln!(
+            writer,
+            "<polyline class=\"{class}\" points=\"{}\"/>",
+            line.join(" ")
+        )?;
+        for &(i, gpa) in &points {
+            writeln!(
+                writer,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" class=\"{class}\"><title>{gpa:.2}</title></circle>",
+                x(i),
+                y(gpa)
+            )?;
+        }
+    }
+    for (i, semester) in (0..).zip(semesters) {
+        writeln!(
+            writer,
+            "<text x=\"{:.1}\" y=\"{:.0}\" text-anchor=\"middle\">{} {}</text>",
+            x(i),
+            top + height + 20.0,
+            escape(&semester.term),
+            escape(&semester.year)
+        )?;
+    }
+    writeln!(writer, "</svg>")?;
+    writeln!(
+        writer,
+        "<p class=\"summary\">Solid: cumulative GPA. Dashed: term GPA.</p>"
+    )
+}
+
+fn write_head<W: Write>(writer: &mut W, headings: &[&str], numeric: &[bool]) -> io::Result<()> {
+    writeln!(writer, "<table class=\"sortable\"><thead><tr>")?;
+    for (heading, &numeric) in headings.iter().zip(numeric) {
+        let class = if numeric { " class=\"number\"" } else { "" };
+        write!(writer, "<th{class}>{heading}</th>")?;
+    }
+    writeln!(writer, "</tr></thead><tbody>")
+}
+
+fn write_transfers<W: Write>(writer: &mut W, student: &StudentInfo) -> io::Result<()> {
+    writeln!(writer, "<h2>Transfer credits</h2>")?;
+    write_head(
+        writer,
+        &["Institution", "Course", "Title", "Grade", "Units"],
+        &[false, false, false, false, true],
+    )?;
+    for transfer in &student.transfers {
+        let course = &transfer.course;
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td data-sort=\"{}\">{}</td><td class=\"number\">{}</td></tr>",
+            escape(transfer.school.as_deref().unwrap_or("")),
+            escape(&describe_course(course)),
+            escape(&course.title),
+            sort_key(course.grade_points),
+            escape(describe_grade(course)),
+            decimal(transfer.units),
+        )?;
+    }
+    writeln!(writer, "</tbody></table>")
+}
+
+fn write_semester<W: Write>(writer: &mut W, semester: &Semester) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<h2>{} {}</h2>\n<p class=\"summary\">{}</p>",
+        escape(&semester.year),
+        escape(&semester.term),
+        escape(&describe_term(semester))
+    )?;
+    write_head(
+        writer,
+        &["Course", "Title", "Grade", "Attempted", "Earned", "WQB"],
+        &[false, false, false, true, true, false],
+    )?;
+    for course in &semester.courses {
+        // Grades sort by their grade points, with notations that carry none
+        // first.
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td>{}</td><td data-sort=\"{}\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+            escape(&describe_course(course)),
+            escape(&course.title),
+            sort_key(course.grade_points),
+            escape(describe_grade(course)),
+            decimal(course.units_attempted),
+            decimal(course.units_earned),
+            describe_designations(&course.designations),
+        )?;
+    }
+    writeln!(writer, "</tbody></table>")
+}
+
+fn write_report<W: Write>(writer: &mut W, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<section id=\"{}\">\n<h1>Student {}</h1>\n<ul>",
+        anchor(new_id),
+        escape(new_id)
+    )?;
+    if !student.plans.is_empty() {
+        let plans = describe_plans(&student.plans);
+        writeln!(writer, "<li><b>Plan:</b> {}</li>", escape(&plans))?;
+    }
+    if !student.credentials.is_empty() {
+        let credentials = describe_credentials(&student.credentials);
+        writeln!(
+            writer,
+            "<li><b>Credentials:</b> {}</li>",
+            escape(&credentials)
+        )?;
+    }
+    if let Some(cgpa) = student.semesters.iter().rev().find_map(|s| s.cgpa) {
+        writeln!(writer, "<li><b>Cumulative GPA:</b> {cgpa:.2}</li>")?;
+    }
+    writeln!(writer, "</ul>")?;
+    write_chart(writer, &student.semesters)?;
+    if !student.transfers.is_empty() {
+        write_transfers(writer, student)?;
+    }
+    for semester in &student.semesters {
+        write_semester(writer, semester)?;
+    }
+    // Reports of leniently parsed transcripts may be incomplete, which
+    // readers need to know.
+    if !student.warnings.is_empty() {
+        writeln!(writer, "<h2>Warnings</h2>\n<ul class=\"warning\">")?;
+        for warning in &student.warnings {
+            writeln!(writer, "<li>{}</li>", escape(warning))?;
+        }
+        writeln!(writer, "</ul>")?;
+    }
+    writeln!(writer, "</section>")
+}
+
+impl<W: Write> Exporter for HtmlExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        write_report(&mut self.reports, new_id, student)?;
+        let summary = match student.semesters.iter().rev().find_map(|s| s.cgpa) {
+            Some(cgpa) => format!(
+                "{} terms, cumulative GPA {cgpa:.2}",
+                student.semesters.len()
+            ),
+            None => format!("{} terms", student.semesters.len()),
+        };
+        self.index.push((new_id.to_string(), summary));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let title = match self.index.as_slice() {
+            [(new_id, _)] => format!("Student {}", escape(new_id)),
+            students => format!("{} students", students.len()),
+        };
+        writeln!(
+            self.writer,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>"
+        )?;
+        if self.index.len() > 1 {
+            writeln!(self.writer, "<nav>\n<h1>{title}</h1>\n<ul>")?;
+            for (new_id, summary) in &self.index {
+                writeln!(
+                    self.writer,
+                    "<li><a href=\"#{}\">Student {}</a>: {summary}</li>",
+                    anchor(new_id),
+                    escape(new_id)
+                )?;
+            }
+            writeln!(self.writer, "</ul>\n</nav>")?;
+        }
+        self.writer.write_all(&self.reports)?;
+        writeln!(self.writer, "<script>{SCRIPT}</script>\n</body>\n</html>")?;
+        self.writer.flush()
+    }
+}
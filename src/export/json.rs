@@ -0,0 +1,87 @@
+use std::io::{self, Write};
+
+use crate::export::{AnonymizedStudent, Exporter};
+use crate::model::StudentInfo;
+
+/// Writes all students as a single JSON array that preserves the nested
+/// structure of plans, transfers, semesters, and courses.
+pub struct JsonExporter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> JsonExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, written: 0 }
+    }
+}
+
+impl<W: Write> Exporter for JsonExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        // The array is streamed element by element so that students do not
+        // need to be collected before writing.
+        let separator = if self.written == 0 { "[\n" } else { ",\n" };
+        self.writer.write_all(separator.as_bytes())?;
+        serde_json::to_writer_pretty(&mut self.writer, &AnonymizedStudent::new(new_id, student))?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let closing = if self.written == 0 { "[]\n" } else { "\n]\n" };
+        self.writer.write_all(closing.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Writes each student as a single line of JSON as soon as it is parsed, so
+/// long batch runs can be consumed incrementally.
+pub struct NdjsonExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Exporter for NdjsonExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &AnonymizedStudent::new(new_id, student))?;
+        self.writer.write_all(b"\n")?;
+        // Flushing per student lets downstream readers see each record as
+        // soon as its transcript finishes.
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes each student as a `MessagePack` map with the same fields as the JSON
+/// output, one after another with nothing between them, for piping large
+/// batches between processes without the size and parsing cost of JSON.
+pub struct MessagePackExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> MessagePackExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Exporter for MessagePackExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        rmp_serde::encode::write_named(&mut self.writer, &AnonymizedStudent::new(new_id, student))
+            .map_err(io::Error::other)?;
+        // As with NDJSON, each record is available as soon as it is written.
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
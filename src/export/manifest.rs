@@ -0,0 +1,111 @@
+//! The manifest of a batch run, recording what happened to every input file
+//! so that runs can be audited and reproduced.
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::export::SCHEMA_VERSION;
+
+/// Whether an input file was extracted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Passed,
+    Failed,
+}
+
+impl EntryStatus {
+    /// The name of the status as written in the manifest.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// The outcome of processing one input file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    /// The SHA-256 digest of the file contents as lowercase hex, or `None`
+    /// if the file could not be read.
+    pub sha256: Option<String>,
+    /// The anonymized ID assigned to the student, when the file passed.
+    pub anonymized_id: Option<String>,
+    /// The number of rows the file contributed to the long layout: one per
+    /// course and transfer credit, or one per requirement of a degree
+    /// progress report.
+    pub rows: usize,
+    pub warnings: Vec<String>,
+    pub status: EntryStatus,
+    /// Why the file failed, when it did.
+    pub error: Option<String>,
+}
+
+/// Every input file of a batch, in the order they were processed (and so
+/// the order sequential IDs were assigned in).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Manifest {
+    pub schema_version: u32,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Records an entry for a processed file.
+    pub fn push(&mut self, entry: ManifestEntry) {
+        self.files.push(entry);
+    }
+
+    /// Writes the manifest as a single JSON object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    pub fn write_json<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)
+    }
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::new(), |mut digest, byte| {
+            let _ = write!(digest, "{byte:02x}");
+            digest
+        })
+}
+
+/// Computes the SHA-256 digest of `bytes` as lowercase hex.
+#[must_use]
+pub fn sha256_bytes(bytes: &[u8]) -> String {
+    hex_digest(Sha256::new_with_prefix(bytes))
+}
+
+/// Computes the SHA-256 digest of the file at `path` as lowercase hex.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex_digest(hasher))
+}
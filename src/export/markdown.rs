@@ -0,0 +1,115 @@
+use std::io::{self, Write};
+
+use crate::export::{
+    describe_course, describe_credentials, describe_designations, describe_grade, describe_plans,
+    describe_term, Exporter,
+};
+use crate::model::{Semester, StudentInfo};
+
+/// Writes a human-readable Markdown report per student, with the plans, the
+/// transfer credits, and a table of courses for every term, for pasting
+/// into advising notes.
+pub struct MarkdownExporter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> MarkdownExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, written: 0 }
+    }
+}
+
+// Pipes would end a table cell early, and line breaks the whole row.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn decimal(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"))
+}
+
+fn write_plans<W: Write>(writer: &mut W, student: &StudentInfo) -> io::Result<()> {
+    if !student.plans.is_empty() {
+        writeln!(writer, "- **Plan:** {}", describe_plans(&student.plans))?;
+    }
+    if !student.credentials.is_empty() {
+        let credentials = describe_credentials(&student.credentials);
+        writeln!(writer, "- **Credentials:** {credentials}")?;
+    }
+    if let Some(cgpa) = student.semesters.iter().rev().find_map(|s| s.cgpa) {
+        writeln!(writer, "- **Cumulative GPA:** {cgpa:.2}")?;
+    }
+    Ok(())
+}
+
+fn write_transfers<W: Write>(writer: &mut W, student: &StudentInfo) -> io::Result<()> {
+    writeln!(writer, "\n## Transfer credits\n")?;
+    writeln!(writer, "| Institution | Course | Title | Grade | Units |")?;
+    writeln!(writer, "|---|---|---|---|---:|")?;
+    for transfer in &student.transfers {
+        writeln!(
+            writer,
+            "| {} | {} | {} | {} | {} |",
+            cell(transfer.school.as_deref().unwrap_or("-")),
+            cell(&describe_course(&transfer.course)),
+            cell(&transfer.course.title),
+            cell(describe_grade(&transfer.course)),
+            decimal(transfer.units),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_semester<W: Write>(writer: &mut W, semester: &Semester) -> io::Result<()> {
+    writeln!(writer, "\n## {} {}\n", semester.year, semester.term)?;
+    writeln!(writer, "{}\n", describe_term(semester))?;
+    writeln!(
+        writer,
+        "| Course | Title | Grade | Attempted | Earned | WQB |"
+    )?;
+    writeln!(writer, "|---|---|---|---:|---:|---|")?;
+    for course in &semester.courses {
+        writeln!(
+            writer,
+            "| {} | {} | {} | {} | {} | {} |",
+            cell(&describe_course(course)),
+            cell(&course.title),
+            cell(describe_grade(course)),
+            decimal(course.units_attempted),
+            decimal(course.units_earned),
+            describe_designations(&course.designations),
+        )?;
+    }
+    Ok(())
+}
+
+impl<W: Write> Exporter for MarkdownExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        if self.written > 0 {
+            writeln!(self.writer, "\n---\n")?;
+        }
+        writeln!(self.writer, "# Student {}\n", cell(new_id))?;
+        write_plans(&mut self.writer, student)?;
+        if !student.transfers.is_empty() {
+            write_transfers(&mut self.writer, student)?;
+        }
+        for semester in &student.semesters {
+            write_semester(&mut self.writer, semester)?;
+        }
+        // Reports of leniently parsed transcripts may be incomplete, which
+        // readers need to know.
+        if !student.warnings.is_empty() {
+            writeln!(self.writer, "\n## Warnings\n")?;
+            for warning in &student.warnings {
+                writeln!(self.writer, "- {warning}")?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef;
+use parquet::arrow::ArrowWriter;
+
+use crate::export::arrow::{long_record_batch, long_schema};
+use crate::export::Exporter;
+use crate::model::StudentInfo;
+
+/// Writes the long layout rows to an Apache Parquet file.
+pub struct ParquetExporter {
+    schema: SchemaRef,
+    writer: Option<ArrowWriter<File>>,
+}
+
+impl ParquetExporter {
+    /// Creates the Parquet file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let schema = long_schema();
+        let file = File::create(path)?;
+        let writer =
+            ArrowWriter::try_new(file, Arc::clone(&schema), None).map_err(io::Error::other)?;
+        Ok(Self {
+            schema,
+            writer: Some(writer),
+        })
+    }
+}
+
+impl Exporter for ParquetExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let batch = long_record_batch(&self.schema, new_id, student)?;
+        match self.writer.as_mut() {
+            Some(writer) => writer.write(&batch).map_err(io::Error::other),
+            None => Err(io::Error::other("Parquet writer already finished")),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.writer.take() {
+            Some(writer) => writer.close().map(|_| ()).map_err(io::Error::other),
+            None => Ok(()),
+        }
+    }
+}
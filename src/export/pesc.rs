@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use crate::export::{utc_timestamp, Exporter, SCHEMA_VERSION};
+use crate::model::{Course, Semester, StudentInfo};
+
+/// The institution that issued the transcripts, and the source of every
+/// document.
+const INSTITUTION: &str = "Simon Fraser University";
+
+// The namespaces of the College Transcript message and the schemas it uses.
+const NAMESPACES: &str = "xmlns:ColTrn=\"urn:org:pesc:message:CollegeTranscript:v1.6.0\" \
+     xmlns:AcRec=\"urn:org:pesc:sector:AcademicRecord:v1.9.0\" \
+     xmlns:core=\"urn:org:pesc:core:CoreMain:v1.14.0\"";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// The PESC session designator of a term, the year and month it starts in.
+fn session_designator(semester: &Semester) -> Option<String> {
+    let month = match semester.term.as_str() {
+        "Spring" => "01",
+        "Summer" => "05",
+        "Fall" => "09",
+        _ => return None,
+    };
+    Some(format!("{}-{month}", semester.year))
+}
+
+// A conferral date, if it is the ISO date the schema requires.
+fn award_date(conferred: &str) -> Option<&str> {
+    let bytes = conferred.as_bytes();
+    let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    (bytes.len() == 10
+        && digits(0..4)
+        && bytes[4] == b'-'
+        && digits(5..7)
+        && bytes[7] == b'-'
+        && digits(8..10))
+    .then_some(conferred)
+}
+
+fn element<W: Write>(writer: &mut W, indent: usize, name: &str, value: &str) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:indent$}<{name}>{}</{name}>",
+        "",
+        escape(value),
+        indent = indent
+    )
+}
+
+fn write_course<W: Write>(writer: &mut W, indent: usize, course: &Course) -> io::Result<()> {
+    writeln!(writer, "{:indent$}<Course>", "")?;
+    let inner = indent + 2;
+    element(writer, inner, "CourseCreditBasis", "Regular")?;
+    element(writer, inner, "CourseCreditUnits", "Semester")?;
+    if let Some(units) = course.units_attempted {
+        element(writer, inner, "CourseCreditValue", &units.to_string())?;
+    }
+    if let Some(units) = course.units_earned {
+        element(writer, inner, "CourseCreditEarned", &units.to_string())?;
+    }
+    if let (Some(points), Some(units)) = (course.grade_points, course.units_attempted) {
+        let quality = format!("{:.2}", points * units);
+        element(writer, inner, "CourseQualityPointsEarned", &quality)?;
+    }
+    if !course.grade.is_empty() {
+        element(writer, inner, "CourseAcademicGrade", &course.grade)?;
+    }
+    element(writer, inner, "CourseSubjectAbbreviation", &course.subject)?;
+    element(writer, inner, "CourseNumber", &course.id)?;
+    element(writer, inner, "CourseTitle", &course.title)?;
+    writeln!(writer, "{:indent$}</Course>", "")
+}
+
+fn write_gpa<W: Write>(
+    writer: &mut W,
+    indent: usize,
+    courses: &[&Course],
+    gpa: f64,
+) -> io::Result<()> {
+    let attempted: f64 = courses.iter().filter_map(|c| c.units_attempted).sum();
+    let earned: f64 = courses.iter().filter_map(|c| c.units_earned).sum();
+    writeln!(writer, "{:indent$}<AcademicSummary>", "")?;
+    let inner = indent + 2;
+    element(writer, inner, "AcademicSummaryType", "SenderOnly")?;
+    writeln!(writer, "{:inner$}<GPA>", "")?;
+    element(
+        writer,
+        inner + 2,
+        "CreditHoursAttempted",
+        &attempted.to_string(),
+    )?;
+    element(writer, inner + 2, "CreditHoursEarned", &earned.to_string())?;
+    element(writer, inner + 2, "GradePointAverage", &format!("{gpa:.2}"))?;
+    writeln!(writer, "{:inner$}</GPA>", "")?;
+    writeln!(writer, "{:indent$}</AcademicSummary>", "")
+}
+
+fn write_session<W: Write>(writer: &mut W, semester: &Semester) -> io::Result<()> {
+    writeln!(writer, "      <AcademicSession>")?;
+    writeln!(writer, "        <AcademicSessionDetail>")?;
+    if let Some(designator) = session_designator(semester) {
+        element(writer, 10, "SessionDesignator", &designator)?;
+    }
+    let name = format!("{} {}", semester.year, semester.term);
+    element(writer, 10, "SessionName", &name)?;
+    element(writer, 10, "SessionType", "Semester")?;
+    writeln!(writer, "        </AcademicSessionDetail>")?;
+    if let Some(host) = &semester.host_institution {
+        writeln!(writer, "        <School>")?;
+        element(writer, 10, "OrganizationName", host)?;
+        writeln!(writer, "        </School>")?;
+    }
+    if let Some(gpa) = semester.gpa {
+        let courses: Vec<&Course> = semester.courses.iter().collect();
+        write_gpa(writer, 8, &courses, gpa)?;
+    }
+    for course in &semester.courses {
+        write_course(writer, 8, course)?;
+    }
+    writeln!(writer, "      </AcademicSession>")
+}
+
+// The record of the credit transferred from each institution.
+fn write_transfers<W: Write>(writer: &mut W, student: &StudentInfo) -> io::Result<()> {
+    let mut schools: BTreeMap<&str, Vec<Course>> = BTreeMap::new();
+    for transfer in &student.transfers {
+        let school = transfer.school.as_deref().unwrap_or("Unknown Institution");
+        schools
+            .entry(school)
+            .or_default()
+            .push(transfer.credited_course());
+    }
+    for (school, courses) in schools {
+        writeln!(writer, "    <AcademicRecord>")?;
+        writeln!(writer, "      <School>")?;
+        element(writer, 8, "OrganizationName", school)?;
+        writeln!(writer, "      </School>")?;
+        for course in &courses {
+            write_course(writer, 6, course)?;
+        }
+        writeln!(writer, "    </AcademicRecord>")?;
+    }
+    Ok(())
+}
+
+fn write_record<W: Write>(writer: &mut W, student: &StudentInfo) -> io::Result<()> {
+    writeln!(writer, "    <AcademicRecord>")?;
+    writeln!(writer, "      <School>")?;
+    element(writer, 8, "OrganizationName", INSTITUTION)?;
+    writeln!(writer, "      </School>")?;
+    for credential in &student.credentials {
+        writeln!(writer, "      <AcademicAward>")?;
+        element(writer, 8, "AcademicAwardTitle", &credential.program)?;
+        element(writer, 8, "AcademicCompletionIndicator", "true")?;
+        if let Some(date) = credential.conferred.as_deref().and_then(award_date) {
+            element(writer, 8, "AcademicCompletionDate", date)?;
+        }
+        writeln!(writer, "      </AcademicAward>")?;
+    }
+    if let Some(cgpa) = student.semesters.iter().rev().find_map(|s| s.cgpa) {
+        let courses: Vec<&Course> = student
+            .semesters
+            .iter()
+            .flat_map(|semester| &semester.courses)
+            .collect();
+        write_gpa(writer, 6, &courses, cgpa)?;
+    }
+    for plan in &student.plans {
+        writeln!(writer, "      <AcademicProgram>")?;
+        element(writer, 8, "AcademicProgramType", "Major")?;
+        element(writer, 8, "AcademicProgramName", &plan.name)?;
+        writeln!(writer, "      </AcademicProgram>")?;
+    }
+    for semester in &student.semesters {
+        write_session(writer, semester)?;
+    }
+    for note in &student.notes {
+        element(writer, 6, "NoteMessage", &note.text)?;
+    }
+    writeln!(writer, "    </AcademicRecord>")
+}
+
+/// Writes each student as a PESC College Transcript XML document, with SFU
+/// terms as academic sessions, their courses and credits, the plans as
+/// academic programs, and credentials as academic awards. Transfer credits
+/// are listed in a record of their own for each institution.
+///
+/// The student is identified by their anonymized ID alone. The standard
+/// requires a name, which is always given as "Withheld". A batch is written
+/// as a stream of documents, each starting with its own XML declaration.
+pub struct PescExporter<W: Write> {
+    writer: W,
+    created: String,
+}
+
+impl<W: Write> PescExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            created: utc_timestamp(SystemTime::now()),
+        }
+    }
+}
+
+impl<W: Write> Exporter for PescExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let writer = &mut self.writer;
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<ColTrn:CollegeTranscript {NAMESPACES}>")?;
+        writeln!(writer, "  <TransmissionData>")?;
+        element(
+            writer,
+            4,
+            "DocumentID",
+            &format!("{new_id}-v{SCHEMA_VERSION}"),
+        )?;
+        element(writer, 4, "CreatedDateTime", &self.created)?;
+        element(writer, 4, "DocumentTypeCode", "StudentRequest")?;
+        element(writer, 4, "TransmissionType", "Original")?;
+        for party in ["Source", "Destination"] {
+            writeln!(writer, "    <{party}>")?;
+            writeln!(writer, "      <Organization>")?;
+            element(writer, 8, "OrganizationName", INSTITUTION)?;
+            writeln!(writer, "      </Organization>")?;
+            writeln!(writer, "    </{party}>")?;
+        }
+        writeln!(writer, "  </TransmissionData>")?;
+        writeln!(writer, "  <Student>")?;
+        writeln!(writer, "    <Person>")?;
+        element(writer, 6, "SchoolAssignedPersonID", new_id)?;
+        writeln!(writer, "      <Name>")?;
+        element(writer, 8, "LastName", "Withheld")?;
+        writeln!(writer, "      </Name>")?;
+        writeln!(writer, "    </Person>")?;
+        write_record(writer, student)?;
+        write_transfers(writer, student)?;
+        writeln!(writer, "  </Student>")?;
+        writeln!(writer, "</ColTrn:CollegeTranscript>")
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,295 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::error::Error as _;
+use std::io;
+use std::rc::Rc;
+
+use postgres::types::ToSql;
+use postgres::{Client, NoTls};
+
+use crate::export::{describe_credentials, long_records, Exporter, SCHEMA_VERSION};
+use crate::leak::LeakCheck;
+use crate::model::{HonourRoll, StudentInfo, TermType};
+
+// The columns of the long layout, named and typed as in the Arrow schema,
+// followed by the occurrence of the row among those sharing its key.
+const COLUMNS: [(&str, &str); 33] = [
+    ("student_id", "TEXT NOT NULL"),
+    ("program", "TEXT NOT NULL"),
+    ("year", "INTEGER"),
+    ("term", "TEXT"),
+    ("subject", "TEXT NOT NULL"),
+    ("course_id", "TEXT NOT NULL"),
+    ("grade", "TEXT NOT NULL"),
+    ("transfer_institution", "TEXT"),
+    ("is_transfer", "BOOLEAN NOT NULL"),
+    ("term_gpa", "DOUBLE PRECISION"),
+    ("cumulative_gpa", "DOUBLE PRECISION"),
+    ("units_attempted", "DOUBLE PRECISION"),
+    ("units_earned", "DOUBLE PRECISION"),
+    ("title", "TEXT NOT NULL"),
+    ("standing", "TEXT"),
+    ("credentials", "TEXT"),
+    ("transfer_units", "DOUBLE PRECISION"),
+    ("designations", "TEXT[] NOT NULL"),
+    ("outcome", "TEXT NOT NULL"),
+    ("attempt", "BIGINT NOT NULL"),
+    ("is_latest_attempt", "BOOLEAN NOT NULL"),
+    ("honour_roll", "TEXT"),
+    ("term_type", "TEXT"),
+    ("grade_points", "DOUBLE PRECISION"),
+    ("grade_valid", "BOOLEAN NOT NULL"),
+    ("printed_term_gpa", "DOUBLE PRECISION"),
+    ("printed_cumulative_gpa", "DOUBLE PRECISION"),
+    ("career", "TEXT"),
+    ("academic_group", "TEXT"),
+    ("equivalent_subject", "TEXT"),
+    ("equivalent_number", "TEXT"),
+    ("schema_version", "BIGINT NOT NULL"),
+    ("occurrence", "BIGINT NOT NULL"),
+];
+
+// Rows are keyed by the student, term, institution, and course. Transfer
+// credits have no term, which the key treats as a term of its own, so their
+// institution tells apart the same course, such as `ENGL 1XX`, from
+// different schools. Repeats of a course from the same school are told apart
+// by their occurrence, as when merging snapshots.
+const KEY: &str = "student_id, (COALESCE(year, 0)), (COALESCE(term, '')), \
+                   (COALESCE(transfer_institution, '')), subject, course_id, occurrence";
+
+// The columns added to the layout since tables were first loaded, which
+// tables loaded before them lack. They are all nullable.
+const ADDED_COLUMNS: [&str; 6] = [
+    "printed_term_gpa",
+    "printed_cumulative_gpa",
+    "career",
+    "academic_group",
+    "equivalent_subject",
+    "equivalent_number",
+];
+
+// The suffixes of the names of indexes on earlier keys, which would reject
+// rows that differ only by institution or occurrence.
+const SUPERSEDED_INDEXES: [&str; 2] = ["key", "row_key"];
+
+// Quotes a table name, which may be qualified by its schema.
+fn quote_table(table: &str) -> String {
+    table
+        .split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// Scans the text of a row, each value on a line of its own so that text is
+// not found across two of them.
+fn scan_row<'a>(leaks: &RefCell<LeakCheck>, texts: impl Iterator<Item = &'a str>) {
+    let mut leaks = leaks.borrow_mut();
+    for text in texts {
+        leaks.scan(text.as_bytes());
+        leaks.scan(b"\n");
+    }
+}
+
+// Errors from the server describe themselves only in their source.
+fn to_io(err: postgres::Error) -> io::Error {
+    match err.source() {
+        Some(source) => io::Error::other(format!("{err}: {source}")),
+        None => io::Error::other(err),
+    }
+}
+
+/// Loads the long layout rows into a Postgres table, creating it if
+/// needed. Rows are upserted on the anonymized ID, term, institution, and
+/// course, so loading a later transcript of a student updates the rows
+/// already there and adds the new ones. Each student is written within its
+/// own transaction.
+pub struct PostgresExporter {
+    client: Client,
+    insert: String,
+    leaks: Option<Rc<RefCell<LeakCheck>>>,
+}
+
+impl PostgresExporter {
+    /// Connects to the database at `url`, such as
+    /// `postgres://user@localhost/ingest`, and ensures `table` exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails or the table cannot be
+    /// created.
+    pub fn connect(url: &str, table: &str) -> io::Result<Self> {
+        let mut client = Client::connect(url, NoTls).map_err(to_io)?;
+        let table = quote_table(table);
+        let definitions: Vec<String> = COLUMNS
+            .iter()
+            .map(|(name, kind)| format!("{name} {kind}"))
+            .collect();
+        // The index is named after the table, less its quotes and schema,
+        // and lives in the schema of the table.
+        let (schema, name) = match table.rsplit_once('.') {
+            Some((schema, name)) => (format!("{schema}."), name),
+            None => (String::new(), table.as_str()),
+        };
+        let index = name.trim_matches('"');
+        // Tables loaded before the key held the institution and occurrence
+        // lack the occurrence of their rows, all of which were the first,
+        // and have an index on an earlier key.
+        let drops: Vec<String> = SUPERSEDED_INDEXES
+            .iter()
+            .map(|suffix| format!("DROP INDEX IF EXISTS {schema}\"{index}_{suffix}\";"))
+            .collect();
+        let additions: Vec<String> = COLUMNS
+            .iter()
+            .filter(|(name, _)| ADDED_COLUMNS.contains(name))
+            .map(|(name, kind)| {
+                format!("ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {name} {kind};")
+            })
+            .collect();
+        client
+            .batch_execute(&format!(
+                "CREATE TABLE IF NOT EXISTS {table} ({});
+                 ALTER TABLE {table} ADD COLUMN IF NOT EXISTS occurrence BIGINT NOT NULL DEFAULT 1;
+                 {}
+                 {}
+                 CREATE UNIQUE INDEX IF NOT EXISTS \"{index}_occurrence_key\" ON {table} ({KEY});",
+                definitions.join(", "),
+                additions.join(" "),
+                drops.join(" ")
+            ))
+            .map_err(to_io)?;
+
+        let names: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+        let placeholders: Vec<String> = (1..=COLUMNS.len()).map(|i| format!("${i}")).collect();
+        let updates: Vec<String> = names
+            .iter()
+            .map(|name| format!("{name} = EXCLUDED.{name}"))
+            .collect();
+        let insert = format!(
+            "INSERT INTO {table} ({}) VALUES ({}) ON CONFLICT ({KEY}) DO UPDATE SET {}",
+            names.join(", "),
+            placeholders.join(", "),
+            updates.join(", ")
+        );
+        Ok(Self {
+            client,
+            insert,
+            leaks: None,
+        })
+    }
+
+    /// Scans the text of every row with `leaks` before it is committed, and
+    /// rolls back the rows of any student in which identifying text turns
+    /// up, since rows once loaded may already be read by others.
+    #[must_use]
+    pub fn with_leak_check(mut self, leaks: Rc<RefCell<LeakCheck>>) -> Self {
+        self.leaks = Some(leaks);
+        self
+    }
+}
+
+impl Exporter for PostgresExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        let described = describe_credentials(&student.credentials);
+        let described = Some(described.as_str()).filter(|s| !s.is_empty());
+        let version = i64::from(SCHEMA_VERSION);
+        let mut tx = self.client.transaction().map_err(to_io)?;
+        let insert = tx.prepare(&self.insert).map_err(to_io)?;
+        let mut occurrences = BTreeMap::new();
+        for record in long_records(new_id, student) {
+            let key = (
+                record.year,
+                record.term,
+                record.school,
+                record.course.subject.as_str(),
+                record.course.id.as_str(),
+            );
+            let count: &mut i64 = occurrences.entry(key).or_default();
+            *count += 1;
+            let occurrence = *count;
+            let year: Option<i32> = record.year.and_then(|y| y.parse().ok());
+            let designations: Vec<&str> = record
+                .course
+                .designations
+                .iter()
+                .map(|designation| designation.tag())
+                .collect();
+            let attempt = i64::from(record.course.attempt);
+            let honour_roll = record.honour_roll.map(HonourRoll::as_str);
+            let term_type = record.term_type.map(TermType::as_str);
+            let equivalent_subject = record.equivalent.map(|e| e.subject.as_str());
+            let equivalent_number = record.equivalent.map(|e| e.id.as_str());
+            let values: [&(dyn ToSql + Sync); COLUMNS.len()] = [
+                &record.id,
+                &record.plan,
+                &year,
+                &record.term,
+                &record.course.subject,
+                &record.course.id,
+                &record.course.grade,
+                &record.school,
+                &record.is_transfer,
+                &record.term_gpa,
+                &record.cumulative_gpa,
+                &record.course.units_attempted,
+                &record.course.units_earned,
+                &record.course.title,
+                &record.standing,
+                &described,
+                &record.transfer_units,
+                &designations,
+                &record.course.outcome.as_str(),
+                &attempt,
+                &record.course.is_latest_attempt,
+                &honour_roll,
+                &term_type,
+                &record.course.grade_points,
+                &record.course.grade_valid,
+                &record.printed_term_gpa,
+                &record.printed_cumulative_gpa,
+                &record.career,
+                &record.academic_group,
+                &equivalent_subject,
+                &equivalent_number,
+                &version,
+                &occurrence,
+            ];
+            if let Some(leaks) = &self.leaks {
+                let texts = [
+                    Some(record.id),
+                    Some(record.plan),
+                    record.term,
+                    Some(record.course.subject.as_str()),
+                    Some(record.course.id.as_str()),
+                    Some(record.course.grade.as_str()),
+                    record.school,
+                    Some(record.course.title.as_str()),
+                    record.standing,
+                    described,
+                    record.career,
+                    record.academic_group,
+                    equivalent_subject,
+                    equivalent_number,
+                ];
+                let texts = texts.into_iter().flatten();
+                scan_row(leaks, texts.chain(designations.iter().copied()));
+            }
+            tx.execute(&insert, &values).map_err(to_io)?;
+        }
+        let found = self
+            .leaks
+            .as_ref()
+            .map(|leaks| leaks.borrow().found().join(", "));
+        if let Some(found) = found.filter(|found| !found.is_empty()) {
+            tx.rollback().map_err(to_io)?;
+            return Err(io::Error::other(format!(
+                "The rows contain identifying text: {found}"
+            )));
+        }
+        tx.commit().map_err(to_io)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
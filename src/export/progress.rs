@@ -0,0 +1,167 @@
+//! Writers for degree progress reports. Reports have their own data model,
+//! so they are written through [`ProgressExporter`] rather than
+//! [`Exporter`](crate::export::Exporter), in the text formats only.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::export::csv::CsvOptions;
+use crate::export::SCHEMA_VERSION;
+use crate::model::{DegreeProgress, Requirement};
+
+pub trait ProgressExporter {
+    /// Writes the requirements of one report under the anonymized `new_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    fn write_report(&mut self, new_id: &str, report: &DegreeProgress) -> io::Result<()>;
+
+    /// Completes the output once all reports have been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying writer fails.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// The serializable view of a report with the real student ID replaced by
+/// the anonymized one.
+#[derive(Clone, Debug, Serialize)]
+pub struct AnonymizedProgress<'a> {
+    pub schema_version: u32,
+    pub id: &'a str,
+    pub program: Option<&'a str>,
+    pub requirements: &'a [Requirement],
+    pub warnings: &'a [String],
+}
+
+impl<'a> AnonymizedProgress<'a> {
+    #[must_use]
+    pub fn new(new_id: &'a str, report: &'a DegreeProgress) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: new_id,
+            program: report.program.as_deref(),
+            requirements: &report.requirements,
+            warnings: &report.warnings,
+        }
+    }
+}
+
+const PROGRESS_HEADERS: [&str; 8] = [
+    "Student ID",
+    "Program",
+    "Requirement",
+    "Status",
+    "Units Required",
+    "Units Used",
+    "Units Needed",
+    "Schema Version",
+];
+
+fn format_units(value: Option<f64>) -> String {
+    value.map_or_else(String::new, |v| format!("{v:.2}"))
+}
+
+/// Writes one CSV row per requirement block.
+pub struct ProgressCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+    header_pending: bool,
+}
+
+impl<W: Write> ProgressCsvExporter<W> {
+    pub fn new(writer: W, options: CsvOptions) -> Self {
+        Self {
+            writer: csv::WriterBuilder::new()
+                .delimiter(options.delimiter)
+                .from_writer(writer),
+            header_pending: options.header,
+        }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.header_pending) {
+            self.writer.write_record(PROGRESS_HEADERS)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ProgressExporter for ProgressCsvExporter<W> {
+    fn write_report(&mut self, new_id: &str, report: &DegreeProgress) -> io::Result<()> {
+        self.write_header()?;
+        let program = report.program.as_deref().unwrap_or_default();
+        let version = SCHEMA_VERSION.to_string();
+        for requirement in &report.requirements {
+            self.writer.write_record([
+                new_id,
+                program,
+                &requirement.name,
+                requirement.status.as_str(),
+                &format_units(requirement.units_required),
+                &format_units(requirement.units_used),
+                &format_units(requirement.units_needed),
+                &version,
+            ])?;
+        }
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+}
+
+/// Writes all reports as a single JSON array.
+pub struct ProgressJsonExporter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> ProgressJsonExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, written: 0 }
+    }
+}
+
+impl<W: Write> ProgressExporter for ProgressJsonExporter<W> {
+    fn write_report(&mut self, new_id: &str, report: &DegreeProgress) -> io::Result<()> {
+        let separator = if self.written == 0 { "[\n" } else { ",\n" };
+        self.writer.write_all(separator.as_bytes())?;
+        serde_json::to_writer_pretty(&mut self.writer, &AnonymizedProgress::new(new_id, report))?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let closing = if self.written == 0 { "[]\n" } else { "\n]\n" };
+        self.writer.write_all(closing.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Writes each report as a single line of JSON as soon as it is parsed.
+pub struct ProgressNdjsonExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ProgressNdjsonExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> ProgressExporter for ProgressNdjsonExporter<W> {
+    fn write_report(&mut self, new_id: &str, report: &DegreeProgress) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &AnonymizedProgress::new(new_id, report))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,387 @@
+//! Protobuf messages of the parsed model, matching `proto/transcript.proto`
+//! field for field, so that consumers generating code from the schema can
+//! read the output of `--format proto`.
+
+use std::io::{self, Write};
+
+use prost::Message;
+
+use crate::export::{Exporter, SCHEMA_VERSION};
+use crate::model;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StudentInfo {
+    #[prost(uint32, tag = "1")]
+    pub schema_version: u32,
+    #[prost(string, tag = "2")]
+    pub id: String,
+    #[prost(message, repeated, tag = "3")]
+    pub plans: Vec<Plan>,
+    #[prost(message, repeated, tag = "4")]
+    pub transfers: Vec<Transfer>,
+    #[prost(message, repeated, tag = "5")]
+    pub semesters: Vec<Semester>,
+    #[prost(message, repeated, tag = "6")]
+    pub credentials: Vec<Credential>,
+    #[prost(message, repeated, tag = "7")]
+    pub milestones: Vec<Milestone>,
+    #[prost(message, repeated, tag = "8")]
+    pub notes: Vec<Note>,
+    #[prost(message, repeated, tag = "9")]
+    pub unit_totals: Vec<GroupUnits>,
+    #[prost(string, repeated, tag = "10")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Plan {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, optional, tag = "2")]
+    pub effective_year: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub effective_term: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Course {
+    #[prost(string, tag = "1")]
+    pub subject: String,
+    #[prost(string, tag = "2")]
+    pub id: String,
+    #[prost(string, tag = "3")]
+    pub title: String,
+    #[prost(string, tag = "4")]
+    pub grade: String,
+    #[prost(double, optional, tag = "5")]
+    pub grade_points: Option<f64>,
+    #[prost(enumeration = "Outcome", tag = "6")]
+    pub outcome: i32,
+    #[prost(double, optional, tag = "7")]
+    pub units_attempted: Option<f64>,
+    #[prost(double, optional, tag = "8")]
+    pub units_earned: Option<f64>,
+    #[prost(enumeration = "Designation", repeated, tag = "9")]
+    pub designations: Vec<i32>,
+    #[prost(uint32, tag = "10")]
+    pub attempt: u32,
+    #[prost(bool, tag = "11")]
+    pub is_latest_attempt: bool,
+    #[prost(bool, tag = "12")]
+    pub grade_valid: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Transfer {
+    #[prost(message, optional, tag = "1")]
+    pub course: Option<Course>,
+    #[prost(string, optional, tag = "2")]
+    pub school: Option<String>,
+    #[prost(double, optional, tag = "3")]
+    pub units: Option<f64>,
+    #[prost(message, optional, tag = "4")]
+    pub equivalent: Option<Equivalent>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Equivalent {
+    #[prost(string, tag = "1")]
+    pub subject: String,
+    #[prost(string, tag = "2")]
+    pub id: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Semester {
+    #[prost(string, tag = "1")]
+    pub year: String,
+    #[prost(string, tag = "2")]
+    pub term: String,
+    #[prost(enumeration = "TermType", tag = "3")]
+    pub term_type: i32,
+    #[prost(string, optional, tag = "4")]
+    pub host_institution: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub career: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub academic_group: Option<String>,
+    #[prost(bool, tag = "7")]
+    pub is_good_standing: bool,
+    #[prost(string, optional, tag = "8")]
+    pub standing: Option<String>,
+    #[prost(enumeration = "HonourRoll", optional, tag = "9")]
+    pub honour_roll: Option<i32>,
+    #[prost(message, repeated, tag = "10")]
+    pub courses: Vec<Course>,
+    #[prost(double, optional, tag = "11")]
+    pub gpa: Option<f64>,
+    #[prost(double, optional, tag = "12")]
+    pub cgpa: Option<f64>,
+    #[prost(double, optional, tag = "13")]
+    pub printed_gpa: Option<f64>,
+    #[prost(double, optional, tag = "14")]
+    pub printed_cgpa: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Credential {
+    #[prost(string, tag = "1")]
+    pub program: String,
+    #[prost(string, optional, tag = "2")]
+    pub conferred: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Milestone {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub year: String,
+    #[prost(string, tag = "3")]
+    pub term: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Note {
+    #[prost(string, optional, tag = "1")]
+    pub year: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub term: Option<String>,
+    #[prost(string, tag = "3")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct GroupUnits {
+    #[prost(string, tag = "1")]
+    pub group: String,
+    #[prost(double, tag = "2")]
+    pub units: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Designation {
+    Unspecified = 0,
+    Writing = 1,
+    Quantitative = 2,
+    BreadthScience = 3,
+    BreadthHumanities = 4,
+    BreadthSocialSciences = 5,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Outcome {
+    Unspecified = 0,
+    Completed = 1,
+    Failed = 2,
+    Withdrawn = 3,
+    InProgress = 4,
+    Pending = 5,
+    Audit = 6,
+    Unknown = 7,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum TermType {
+    Unspecified = 0,
+    Academic = 1,
+    CoOp = 2,
+    Exchange = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum HonourRoll {
+    Unspecified = 0,
+    Deans = 1,
+    Presidents = 2,
+}
+
+impl From<model::Designation> for Designation {
+    fn from(designation: model::Designation) -> Self {
+        match designation {
+            model::Designation::Writing => Self::Writing,
+            model::Designation::Quantitative => Self::Quantitative,
+            model::Designation::BreadthScience => Self::BreadthScience,
+            model::Designation::BreadthHumanities => Self::BreadthHumanities,
+            model::Designation::BreadthSocialSciences => Self::BreadthSocialSciences,
+        }
+    }
+}
+
+impl From<model::Outcome> for Outcome {
+    fn from(outcome: model::Outcome) -> Self {
+        match outcome {
+            model::Outcome::Completed => Self::Completed,
+            model::Outcome::Failed => Self::Failed,
+            model::Outcome::Withdrawn => Self::Withdrawn,
+            model::Outcome::InProgress => Self::InProgress,
+            model::Outcome::Pending => Self::Pending,
+            model::Outcome::Audit => Self::Audit,
+            model::Outcome::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl From<model::TermType> for TermType {
+    fn from(term_type: model::TermType) -> Self {
+        match term_type {
+            model::TermType::Academic => Self::Academic,
+            model::TermType::CoOp => Self::CoOp,
+            model::TermType::Exchange => Self::Exchange,
+        }
+    }
+}
+
+impl From<model::HonourRoll> for HonourRoll {
+    fn from(honour_roll: model::HonourRoll) -> Self {
+        match honour_roll {
+            model::HonourRoll::DeansHonourRoll => Self::Deans,
+            model::HonourRoll::PresidentsHonourRoll => Self::Presidents,
+        }
+    }
+}
+
+impl From<&model::Course> for Course {
+    fn from(course: &model::Course) -> Self {
+        Self {
+            subject: course.subject.clone(),
+            id: course.id.clone(),
+            title: course.title.clone(),
+            grade: course.grade.clone(),
+            grade_points: course.grade_points,
+            outcome: Outcome::from(course.outcome).into(),
+            units_attempted: course.units_attempted,
+            units_earned: course.units_earned,
+            designations: course
+                .designations
+                .iter()
+                .map(|&designation| Designation::from(designation).into())
+                .collect(),
+            attempt: course.attempt,
+            is_latest_attempt: course.is_latest_attempt,
+            grade_valid: course.grade_valid,
+        }
+    }
+}
+
+impl From<&model::Semester> for Semester {
+    fn from(semester: &model::Semester) -> Self {
+        Self {
+            year: semester.year.clone(),
+            term: semester.term.clone(),
+            term_type: TermType::from(semester.term_type).into(),
+            host_institution: semester.host_institution.clone(),
+            career: semester.career.clone(),
+            academic_group: semester.academic_group.clone(),
+            is_good_standing: semester.is_good_standing,
+            standing: semester.standing.clone(),
+            honour_roll: semester
+                .honour_roll
+                .map(|honour_roll| HonourRoll::from(honour_roll).into()),
+            courses: semester.courses.iter().map(Course::from).collect(),
+            gpa: semester.gpa,
+            cgpa: semester.cgpa,
+            printed_gpa: semester.printed_gpa,
+            printed_cgpa: semester.printed_cgpa,
+        }
+    }
+}
+
+impl StudentInfo {
+    /// The message of a student under their anonymized ID. The real ID and
+    /// the identity of the student are left out.
+    #[must_use]
+    pub fn new(new_id: &str, student: &model::StudentInfo) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: new_id.to_string(),
+            plans: student
+                .plans
+                .iter()
+                .map(|plan| Plan {
+                    name: plan.name.clone(),
+                    effective_year: plan.effective_year.clone(),
+                    effective_term: plan.effective_term.clone(),
+                })
+                .collect(),
+            transfers: student
+                .transfers
+                .iter()
+                .map(|transfer| Transfer {
+                    course: Some(Course::from(&transfer.course)),
+                    school: transfer.school.clone(),
+                    units: transfer.units,
+                    equivalent: transfer.equivalent.as_ref().map(|equivalent| Equivalent {
+                        subject: equivalent.subject.clone(),
+                        id: equivalent.id.clone(),
+                    }),
+                })
+                .collect(),
+            semesters: student.semesters.iter().map(Semester::from).collect(),
+            credentials: student
+                .credentials
+                .iter()
+                .map(|credential| Credential {
+                    program: credential.program.clone(),
+                    conferred: credential.conferred.clone(),
+                })
+                .collect(),
+            milestones: student
+                .milestones
+                .iter()
+                .map(|milestone| Milestone {
+                    name: milestone.name.clone(),
+                    year: milestone.year.clone(),
+                    term: milestone.term.clone(),
+                })
+                .collect(),
+            notes: student
+                .notes
+                .iter()
+                .map(|note| Note {
+                    year: note.year.clone(),
+                    term: note.term.clone(),
+                    text: note.text.clone(),
+                })
+                .collect(),
+            unit_totals: student
+                .unit_totals
+                .iter()
+                .map(|totals| GroupUnits {
+                    group: totals.group.clone(),
+                    units: totals.units,
+                })
+                .collect(),
+            warnings: student.warnings.clone(),
+        }
+    }
+}
+
+/// Writes each student as a `StudentInfo` message preceded by its length
+/// as a varint, the framing read by `parseDelimitedFrom` in Java and
+/// `protodelim` in Go.
+pub struct ProtoExporter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ProtoExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Exporter for ProtoExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &model::StudentInfo) -> io::Result<()> {
+        let message = StudentInfo::new(new_id, student);
+        self.writer
+            .write_all(&message.encode_length_delimited_to_vec())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
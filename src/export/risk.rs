@@ -0,0 +1,67 @@
+//! Writers for the at-risk report produced alongside a batch export.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::export::csv::CsvOptions;
+use crate::export::SCHEMA_VERSION;
+use crate::risk::StudentRisk;
+
+/// The columns of the at-risk report CSV, with one row per student.
+pub const RISK_COLUMNS: [&str; 4] = ["ID", "At Risk", "Flags", "Schema Version"];
+
+/// Writes the report as a CSV, listing the rules that flagged each student
+/// with how often their condition occurred, such as `Low term GPA (2)`,
+/// separated by semicolons.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_risk_csv<W: Write>(
+    writer: W,
+    students: &[StudentRisk],
+    options: CsvOptions,
+) -> io::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    if options.header {
+        writer.write_record(RISK_COLUMNS)?;
+    }
+    let version = SCHEMA_VERSION.to_string();
+    for student in students {
+        let flags: Vec<String> = student
+            .flags
+            .iter()
+            .map(|flag| format!("{} ({})", flag.rule, flag.count))
+            .collect();
+        writer.write_record([
+            student.id.as_str(),
+            if student.at_risk { "true" } else { "false" },
+            &flags.join("; "),
+            &version,
+        ])?;
+    }
+    writer.flush()
+}
+
+#[derive(Serialize)]
+struct VersionedReport<'a> {
+    schema_version: u32,
+    students: &'a [StudentRisk],
+}
+
+/// Writes the report as a single JSON object.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_risk_json<W: Write>(mut writer: W, students: &[StudentRisk]) -> io::Result<()> {
+    let versioned = VersionedReport {
+        schema_version: SCHEMA_VERSION,
+        students,
+    };
+    serde_json::to_writer_pretty(&mut writer, &versioned)?;
+    writeln!(writer)
+}
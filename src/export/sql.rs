@@ -0,0 +1,173 @@
+use std::io::{self, Write};
+
+use crate::export::{describe_credentials, long_records, Exporter, SCHEMA_VERSION};
+use crate::model::{HonourRoll, StudentInfo, TermType};
+
+/// The table the statements create and fill.
+pub const TABLE: &str = "transcript_rows";
+
+// The columns of the long layout, named as in the Arrow schema. The types
+// keep to those every major database accepts, so booleans are 0 or 1 and
+// designations are their tags separated by spaces.
+const COLUMNS: [(&str, &str); 32] = [
+    ("student_id", "VARCHAR(64) NOT NULL"),
+    ("program", "VARCHAR(255) NOT NULL"),
+    ("year", "INTEGER"),
+    ("term", "VARCHAR(32)"),
+    ("subject", "VARCHAR(32) NOT NULL"),
+    ("course_id", "VARCHAR(32) NOT NULL"),
+    ("grade", "VARCHAR(32) NOT NULL"),
+    ("transfer_institution", "VARCHAR(255)"),
+    ("is_transfer", "SMALLINT NOT NULL"),
+    ("term_gpa", "DOUBLE PRECISION"),
+    ("cumulative_gpa", "DOUBLE PRECISION"),
+    ("units_attempted", "DOUBLE PRECISION"),
+    ("units_earned", "DOUBLE PRECISION"),
+    ("title", "VARCHAR(255) NOT NULL"),
+    ("standing", "VARCHAR(255)"),
+    ("credentials", "VARCHAR(1000)"),
+    ("transfer_units", "DOUBLE PRECISION"),
+    ("designations", "VARCHAR(64) NOT NULL"),
+    ("outcome", "VARCHAR(32) NOT NULL"),
+    ("attempt", "INTEGER NOT NULL"),
+    ("is_latest_attempt", "SMALLINT NOT NULL"),
+    ("honour_roll", "VARCHAR(32)"),
+    ("term_type", "VARCHAR(32)"),
+    ("grade_points", "DOUBLE PRECISION"),
+    ("grade_valid", "SMALLINT NOT NULL"),
+    ("printed_term_gpa", "DOUBLE PRECISION"),
+    ("printed_cumulative_gpa", "DOUBLE PRECISION"),
+    ("career", "VARCHAR(64)"),
+    ("academic_group", "VARCHAR(255)"),
+    ("equivalent_subject", "VARCHAR(32)"),
+    ("equivalent_number", "VARCHAR(32)"),
+    ("schema_version", "INTEGER NOT NULL"),
+];
+
+// A string literal, with its quotes doubled.
+fn text(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn optional_text(value: Option<&str>) -> String {
+    value.map_or_else(|| "NULL".to_string(), text)
+}
+
+fn number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "NULL".to_string(),
+    }
+}
+
+fn flag(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+/// Writes the long layout rows as a portable SQL script: a `CREATE TABLE`
+/// statement followed by one `INSERT` per row, which loads into any
+/// relational database without other tools.
+pub struct SqlExporter<W: Write> {
+    writer: W,
+    created: bool,
+}
+
+impl<W: Write> SqlExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            created: false,
+        }
+    }
+
+    fn create_table(&mut self) -> io::Result<()> {
+        let definitions: Vec<String> = COLUMNS
+            .iter()
+            .map(|(name, kind)| format!("  {name} {kind}"))
+            .collect();
+        writeln!(
+            self.writer,
+            "CREATE TABLE {TABLE} (\n{}\n);",
+            definitions.join(",\n")
+        )?;
+        self.created = true;
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for SqlExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        if !self.created {
+            self.create_table()?;
+        }
+        let described = describe_credentials(&student.credentials);
+        let described = Some(described.as_str()).filter(|s| !s.is_empty());
+        let names: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+        let names = names.join(", ");
+        for record in long_records(new_id, student) {
+            let designations: Vec<&str> = record
+                .course
+                .designations
+                .iter()
+                .map(|designation| designation.tag())
+                .collect();
+            let year = record
+                .year
+                .and_then(|y| y.parse::<i32>().ok())
+                .map_or_else(|| "NULL".to_string(), |y| y.to_string());
+            let values = [
+                text(record.id),
+                text(record.plan),
+                year,
+                optional_text(record.term),
+                text(&record.course.subject),
+                text(&record.course.id),
+                text(&record.course.grade),
+                optional_text(record.school),
+                flag(record.is_transfer).to_string(),
+                number(record.term_gpa),
+                number(record.cumulative_gpa),
+                number(record.course.units_attempted),
+                number(record.course.units_earned),
+                text(&record.course.title),
+                optional_text(record.standing),
+                optional_text(described),
+                number(record.transfer_units),
+                text(&designations.join(" ")),
+                text(record.course.outcome.as_str()),
+                record.course.attempt.to_string(),
+                flag(record.course.is_latest_attempt).to_string(),
+                optional_text(record.honour_roll.map(HonourRoll::as_str)),
+                optional_text(record.term_type.map(TermType::as_str)),
+                number(record.course.grade_points),
+                flag(record.course.grade_valid).to_string(),
+                number(record.printed_term_gpa),
+                number(record.printed_cumulative_gpa),
+                optional_text(record.career),
+                optional_text(record.academic_group),
+                optional_text(record.equivalent.map(|e| e.subject.as_str())),
+                optional_text(record.equivalent.map(|e| e.id.as_str())),
+                SCHEMA_VERSION.to_string(),
+            ];
+            writeln!(
+                self.writer,
+                "INSERT INTO {TABLE} ({names}) VALUES ({});",
+                values.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // An empty batch still creates the table, so loading it never fails
+        // on a missing one.
+        if !self.created {
+            self.create_table()?;
+        }
+        self.writer.flush()
+    }
+}
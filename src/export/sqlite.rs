@@ -0,0 +1,442 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, ToSql, Transaction};
+
+use crate::export::{describe_designations, Exporter, RowKey, SCHEMA_VERSION};
+use crate::model::{HonourRoll, Semester, StudentInfo, Transfer};
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS plans (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS students (
+        id TEXT PRIMARY KEY,
+        plan_id INTEGER NOT NULL REFERENCES plans(id)
+    );
+    CREATE TABLE IF NOT EXISTS student_plans (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        plan_id INTEGER NOT NULL REFERENCES plans(id),
+        effective_year INTEGER,
+        effective_term TEXT
+    );
+    CREATE TABLE IF NOT EXISTS transfers (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        subject TEXT NOT NULL,
+        number TEXT NOT NULL,
+        title TEXT NOT NULL,
+        grade TEXT NOT NULL,
+        grade_points REAL,
+        units_attempted REAL,
+        units_earned REAL,
+        school TEXT,
+        units REAL,
+        equivalent_subject TEXT,
+        equivalent_number TEXT,
+        designations TEXT NOT NULL,
+        outcome TEXT NOT NULL,
+        attempt INTEGER NOT NULL,
+        is_latest_attempt INTEGER NOT NULL,
+        grade_valid INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS credentials (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        program TEXT NOT NULL,
+        conferred TEXT
+    );
+    CREATE TABLE IF NOT EXISTS milestones (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        name TEXT NOT NULL,
+        year INTEGER NOT NULL,
+        term TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS notes (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        year INTEGER,
+        term TEXT,
+        text TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS unit_totals (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        academic_group TEXT NOT NULL,
+        units REAL NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS semesters (
+        id INTEGER PRIMARY KEY,
+        student_id TEXT NOT NULL REFERENCES students(id),
+        year INTEGER NOT NULL,
+        term TEXT NOT NULL,
+        term_type TEXT NOT NULL,
+        host_institution TEXT,
+        career TEXT,
+        academic_group TEXT,
+        is_good_standing INTEGER NOT NULL,
+        standing TEXT,
+        honour_roll TEXT,
+        gpa REAL,
+        cgpa REAL,
+        printed_gpa REAL,
+        printed_cgpa REAL
+    );
+    CREATE TABLE IF NOT EXISTS courses (
+        id INTEGER PRIMARY KEY,
+        semester_id INTEGER NOT NULL REFERENCES semesters(id),
+        subject TEXT NOT NULL,
+        number TEXT NOT NULL,
+        title TEXT NOT NULL,
+        grade TEXT NOT NULL,
+        grade_points REAL,
+        units_attempted REAL,
+        units_earned REAL,
+        designations TEXT NOT NULL,
+        outcome TEXT NOT NULL,
+        attempt INTEGER NOT NULL,
+        is_latest_attempt INTEGER NOT NULL,
+        grade_valid INTEGER NOT NULL
+    );
+";
+
+fn insert_transfers(
+    tx: &Transaction,
+    new_id: &str,
+    transfers: &[Transfer],
+) -> rusqlite::Result<()> {
+    for transfer in transfers {
+        let equivalent = transfer.equivalent.as_ref();
+        tx.execute(
+            "INSERT INTO transfers
+                 (student_id, subject, number, title, grade, grade_points, units_attempted,
+                  units_earned, school, units, designations, outcome, attempt, is_latest_attempt,
+                  grade_valid, equivalent_subject, equivalent_number)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![
+                new_id,
+                transfer.course.subject,
+                transfer.course.id,
+                transfer.course.title,
+                transfer.course.grade,
+                transfer.course.grade_points,
+                transfer.course.units_attempted,
+                transfer.course.units_earned,
+                transfer.school,
+                transfer.units,
+                describe_designations(&transfer.course.designations),
+                transfer.course.outcome.as_str(),
+                transfer.course.attempt,
+                transfer.course.is_latest_attempt,
+                transfer.course.grade_valid,
+                equivalent.map(|equivalent| &equivalent.subject),
+                equivalent.map(|equivalent| &equivalent.id),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+// Inserts a row into `table`, unless appending and an equal row is already
+// there.
+fn insert_row(
+    tx: &Transaction,
+    append: bool,
+    table: &str,
+    columns: &[&str],
+    values: &[&dyn ToSql],
+) -> rusqlite::Result<()> {
+    let names = columns.join(", ");
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{i}")).collect();
+    let placeholders = placeholders.join(", ");
+    let sql = if append {
+        let matches: Vec<String> = (1..)
+            .zip(columns)
+            .map(|(i, column)| format!("{column} IS ?{i}"))
+            .collect();
+        format!(
+            "INSERT INTO {table} ({names}) SELECT {placeholders}
+             WHERE NOT EXISTS (SELECT 1 FROM {table} WHERE {})",
+            matches.join(" AND ")
+        )
+    } else {
+        format!("INSERT INTO {table} ({names}) VALUES ({placeholders})")
+    };
+    tx.execute(&sql, values)?;
+    Ok(())
+}
+
+// Writes a semester, returning its row. When appending, a semester the
+// student already has is updated in place, keeping its courses.
+fn write_semester(
+    tx: &Transaction,
+    append: bool,
+    new_id: &str,
+    semester: &Semester,
+) -> rusqlite::Result<i64> {
+    let existing: Option<i64> = if append {
+        tx.query_row(
+            "SELECT id FROM semesters WHERE student_id = ?1 AND year = ?2 AND term = ?3",
+            params![new_id, semester.year, semester.term],
+            |row| row.get(0),
+        )
+        .optional()?
+    } else {
+        None
+    };
+    let sql = match existing {
+        Some(_) => {
+            "UPDATE semesters
+             SET term_type = ?4, host_institution = ?5, is_good_standing = ?6, standing = ?7,
+                 honour_roll = ?8, gpa = ?9, cgpa = ?10, printed_gpa = ?11, printed_cgpa = ?12,
+                 career = ?13, academic_group = ?14
+             WHERE id = ?15 AND student_id = ?1 AND year = ?2 AND term = ?3"
+        }
+        None => {
+            "INSERT INTO semesters
+                 (student_id, year, term, term_type, host_institution, is_good_standing,
+                  standing, honour_roll, gpa, cgpa, printed_gpa, printed_cgpa, career,
+                  academic_group)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"
+        }
+    };
+    let term_type = semester.term_type.as_str();
+    let honour_roll = semester.honour_roll.map(HonourRoll::as_str);
+    let mut values = params![
+        new_id,
+        semester.year,
+        semester.term,
+        term_type,
+        semester.host_institution,
+        semester.is_good_standing,
+        semester.standing,
+        honour_roll,
+        semester.gpa,
+        semester.cgpa,
+        semester.printed_gpa,
+        semester.printed_cgpa,
+        semester.career,
+        semester.academic_group,
+    ]
+    .to_vec();
+    if let Some(id) = &existing {
+        values.push(id);
+    }
+    tx.execute(sql, values.as_slice())?;
+    Ok(existing.unwrap_or_else(|| tx.last_insert_rowid()))
+}
+
+/// Writes students into normalized tables of an `SQLite` database. Each
+/// student is written within its own transaction.
+pub struct SqliteExporter {
+    connection: Connection,
+    append: bool,
+}
+
+fn to_io(err: rusqlite::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+impl SqliteExporter {
+    /// Opens (or creates) the database at `path` and ensures the schema
+    /// exists. The layout version is stored as the database's
+    /// `user_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or initialized, or
+    /// if it was written with a different schema version.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let connection = Connection::open(path).map_err(to_io)?;
+        let version: u32 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(to_io)?;
+        // Databases written before the version was recorded report 0.
+        if version != 0 && version != SCHEMA_VERSION {
+            return Err(io::Error::other(format!(
+                "database has schema version {version}, expected {SCHEMA_VERSION}"
+            )));
+        }
+        connection.execute_batch(SCHEMA).map_err(to_io)?;
+        connection
+            .pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(to_io)?;
+        Ok(Self {
+            connection,
+            append: false,
+        })
+    }
+
+    /// Adds to the students already in the database instead of rejecting
+    /// them. Their current plan, the terms they share with the database,
+    /// and their unit totals are updated, and their other records are
+    /// added where the database lacks them. Transfer credits and courses
+    /// are always added, so the ones already present are left to be removed
+    /// beforehand, with [`read_sqlite_row_keys`].
+    #[must_use]
+    pub fn appending(mut self) -> Self {
+        self.append = true;
+        self
+    }
+
+    fn insert_student(&mut self, new_id: &str, student: &StudentInfo) -> rusqlite::Result<()> {
+        let tx = self.connection.transaction()?;
+
+        let mut plan_ids = Vec::with_capacity(student.plans.len());
+        for plan in &student.plans {
+            tx.execute(
+                "INSERT OR IGNORE INTO plans (name) VALUES (?1)",
+                [&plan.name],
+            )?;
+            let plan_id: i64 = tx.query_row(
+                "SELECT id FROM plans WHERE name = ?1",
+                [&plan.name],
+                |row| row.get(0),
+            )?;
+            plan_ids.push(plan_id);
+        }
+
+        // Students are keyed to their current plan, with the full history in
+        // student_plans.
+        let upsert = if self.append {
+            " ON CONFLICT (id) DO UPDATE SET plan_id = excluded.plan_id"
+        } else {
+            ""
+        };
+        tx.execute(
+            &format!("INSERT INTO students (id, plan_id) VALUES (?1, ?2){upsert}"),
+            params![new_id, plan_ids.last()],
+        )?;
+        for (plan, plan_id) in student.plans.iter().zip(&plan_ids) {
+            insert_row(
+                &tx,
+                self.append,
+                "student_plans",
+                &["student_id", "plan_id", "effective_year", "effective_term"],
+                params![new_id, plan_id, plan.effective_year, plan.effective_term],
+            )?;
+        }
+
+        insert_transfers(&tx, new_id, &student.transfers)?;
+
+        for credential in &student.credentials {
+            insert_row(
+                &tx,
+                self.append,
+                "credentials",
+                &["student_id", "program", "conferred"],
+                params![new_id, credential.program, credential.conferred],
+            )?;
+        }
+
+        for milestone in &student.milestones {
+            insert_row(
+                &tx,
+                self.append,
+                "milestones",
+                &["student_id", "name", "year", "term"],
+                params![new_id, milestone.name, milestone.year, milestone.term],
+            )?;
+        }
+
+        for note in &student.notes {
+            insert_row(
+                &tx,
+                self.append,
+                "notes",
+                &["student_id", "year", "term", "text"],
+                params![new_id, note.year, note.term, note.text],
+            )?;
+        }
+
+        // The unit totals of the latest transcript replace earlier ones.
+        if self.append && !student.unit_totals.is_empty() {
+            tx.execute("DELETE FROM unit_totals WHERE student_id = ?1", [new_id])?;
+        }
+        for total in &student.unit_totals {
+            tx.execute(
+                "INSERT INTO unit_totals (student_id, academic_group, units) VALUES (?1, ?2, ?3)",
+                params![new_id, total.group, total.units],
+            )?;
+        }
+
+        for semester in &student.semesters {
+            let semester_id = write_semester(&tx, self.append, new_id, semester)?;
+            for course in &semester.courses {
+                tx.execute(
+                    "INSERT INTO courses
+                         (semester_id, subject, number, title, grade, grade_points, units_attempted,
+                          units_earned, designations, outcome, attempt, is_latest_attempt,
+                          grade_valid)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        semester_id,
+                        course.subject,
+                        course.id,
+                        course.title,
+                        course.grade,
+                        course.grade_points,
+                        course.units_attempted,
+                        course.units_earned,
+                        describe_designations(&course.designations),
+                        course.outcome.as_str(),
+                        course.attempt,
+                        course.is_latest_attempt,
+                        course.grade_valid,
+                    ],
+                )?;
+            }
+        }
+
+        tx.commit()
+    }
+}
+
+impl Exporter for SqliteExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.insert_student(new_id, student).map_err(to_io)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the keys of the transfer credits and courses already in the
+/// database at `path`, for appending to it without repeating them.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be read.
+pub fn read_sqlite_row_keys<P: AsRef<Path>>(path: P) -> io::Result<BTreeSet<RowKey>> {
+    let connection = Connection::open(path).map_err(to_io)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT semesters.student_id, CAST(semesters.year AS TEXT), semesters.term,
+                    semesters.host_institution, courses.subject, courses.number
+             FROM courses JOIN semesters ON courses.semester_id = semesters.id
+             UNION ALL
+             SELECT student_id, NULL, NULL, school, subject, number FROM transfers",
+        )
+        .map_err(to_io)?;
+    let keys = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })
+        .map_err(to_io)?
+        .collect::<rusqlite::Result<_>>()
+        .map_err(to_io)?;
+    Ok(keys)
+}
@@ -0,0 +1,129 @@
+use std::io::{self, Write};
+
+use crate::export::{
+    describe_course, describe_designations, describe_grade, describe_plans, Exporter,
+};
+use crate::model::StudentInfo;
+
+/// Writes rows under a header with every column padded to its widest cell,
+/// for reading in a terminal. The first `left_aligned` columns are left
+/// aligned and the rest are right aligned.
+///
+/// # Errors
+///
+/// Returns an error if the underlying writer fails.
+pub fn write_table<W: Write>(
+    out: &mut W,
+    header: &[String],
+    rows: &[Vec<String>],
+    left_aligned: usize,
+) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, &width))| {
+                if i < left_aligned {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+const COLUMNS: [&str; 7] = [
+    "Term",
+    "Course",
+    "Title",
+    "Grade",
+    "WQB",
+    "Attempted",
+    "Earned",
+];
+
+fn decimal(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"))
+}
+
+/// Writes the courses of each student as an aligned table for a quick look
+/// in a terminal, with transfer credits first and the term GPAs after the
+/// courses of each term.
+pub struct TableExporter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> TableExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, written: 0 }
+    }
+}
+
+impl<W: Write> Exporter for TableExporter<W> {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        if self.written > 0 {
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "Student {new_id}")?;
+        if !student.plans.is_empty() {
+            writeln!(self.writer, "Plan: {}", describe_plans(&student.plans))?;
+        }
+        writeln!(self.writer)?;
+
+        let transfers = student.transfers.iter().map(|transfer| {
+            let course = &transfer.course;
+            vec![
+                "Transfer".to_string(),
+                describe_course(course),
+                course.title.clone(),
+                describe_grade(course).to_string(),
+                describe_designations(&course.designations),
+                String::new(),
+                decimal(transfer.units),
+            ]
+        });
+        let terms = student.semesters.iter().flat_map(|semester| {
+            let term = format!("{} {}", semester.year, semester.term);
+            let courses = semester.courses.iter().map(move |course| {
+                vec![
+                    term.clone(),
+                    describe_course(course),
+                    course.title.clone(),
+                    describe_grade(course).to_string(),
+                    describe_designations(&course.designations),
+                    decimal(course.units_attempted),
+                    decimal(course.units_earned),
+                ]
+            });
+            let gpas = format!(
+                "Term GPA {}, cumulative {}",
+                decimal(semester.gpa),
+                decimal(semester.cgpa)
+            );
+            courses.chain(std::iter::once(vec![String::new(), String::new(), gpas]))
+        });
+        let rows: Vec<Vec<String>> = transfers.chain(terms).collect();
+        let header = COLUMNS.map(str::to_string);
+        write_table(&mut self.writer, &header, &rows, 5)?;
+        for warning in &student.warnings {
+            writeln!(self.writer, "Warning: {warning}")?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,301 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rust_xlsxwriter::{DocProperties, Format, Workbook, Worksheet, XlsxError};
+
+use crate::export::{describe_designations, semester_plans, Exporter, SCHEMA_VERSION};
+use crate::model::{HonourRoll, StudentInfo, Transfer};
+
+const COURSE_HEADERS: [&str; 26] = [
+    "Student ID",
+    "Program",
+    "Year",
+    "Term",
+    "Term Type",
+    "Host Institution",
+    "Career",
+    "Academic Group",
+    "Subject",
+    "Course ID",
+    "Title",
+    "Grade",
+    "Grade Points",
+    "Grade Valid",
+    "Outcome",
+    "Attempt",
+    "Latest Attempt",
+    "Units Attempted",
+    "Units Earned",
+    "Term GPA",
+    "Cumulative GPA",
+    "Printed Term GPA",
+    "Printed Cumulative GPA",
+    "Academic Standing",
+    "Honour Roll",
+    "Designations",
+];
+
+const TRANSFER_HEADERS: [&str; 18] = [
+    "Student ID",
+    "Program",
+    "Subject",
+    "Course ID",
+    "Title",
+    "Grade",
+    "Grade Points",
+    "Grade Valid",
+    "Outcome",
+    "Attempt",
+    "Latest Attempt",
+    "Units Attempted",
+    "Units Earned",
+    "Transfer Institution",
+    "Transfer Units",
+    "Equivalent Subject",
+    "Equivalent Course ID",
+    "Designations",
+];
+
+const CREDENTIAL_HEADERS: [&str; 4] = ["Student ID", "Program", "Credential", "Conferred"];
+
+const MILESTONE_HEADERS: [&str; 5] = ["Student ID", "Program", "Milestone", "Year", "Term"];
+
+const NOTE_HEADERS: [&str; 5] = ["Student ID", "Program", "Year", "Term", "Note"];
+
+const UNIT_TOTAL_HEADERS: [&str; 4] = ["Student ID", "Program", "Academic Group", "Units"];
+
+const SUMMARY_HEADERS: [&str; 7] = [
+    "Student ID",
+    "Program",
+    "Terms",
+    "Courses",
+    "Transfers",
+    "Cumulative GPA",
+    "Schema Version",
+];
+
+fn to_io(err: XlsxError) -> io::Error {
+    io::Error::other(err)
+}
+
+/// A worksheet along with the next row to be written.
+struct Sheet {
+    worksheet: Worksheet,
+    row: u32,
+}
+
+impl Sheet {
+    fn new(name: &str, headers: &[&str]) -> Result<Self, XlsxError> {
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(name)?;
+        let bold = Format::new().set_bold();
+        for (col, header) in (0..).zip(headers) {
+            worksheet.write_string_with_format(0, col, *header, &bold)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        Ok(Self { worksheet, row: 1 })
+    }
+
+    fn write_row(&mut self, cells: &[Cell]) -> Result<(), XlsxError> {
+        for (col, cell) in (0..).zip(cells) {
+            match cell {
+                Cell::Text(text) => {
+                    self.worksheet.write_string(self.row, col, *text)?;
+                }
+                Cell::Number(Some(number)) => {
+                    self.worksheet.write_number(self.row, col, *number)?;
+                }
+                Cell::Number(None) => {}
+                Cell::Boolean(value) => {
+                    self.worksheet.write_boolean(self.row, col, *value)?;
+                }
+            }
+        }
+        self.row += 1;
+        Ok(())
+    }
+}
+
+/// A typed cell value. Identifiers such as course numbers are always text so
+/// spreadsheet tools do not strip leading zeros or reinterpret them.
+enum Cell<'a> {
+    Text(&'a str),
+    Number(Option<f64>),
+    Boolean(bool),
+}
+
+/// Writes an Excel workbook with one sheet each for SFU courses, transfer
+/// credits, awarded credentials, milestones, notes, the units passed by
+/// academic group, and a per-student summary. The workbook is saved by `finish`.
+pub struct XlsxExporter {
+    path: PathBuf,
+    courses: Sheet,
+    transfers: Sheet,
+    credentials: Sheet,
+    milestones: Sheet,
+    notes: Sheet,
+    unit_totals: Sheet,
+    summary: Sheet,
+}
+
+impl XlsxExporter {
+    /// Prepares a workbook to be saved at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the worksheets cannot be initialized.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            courses: Sheet::new("Courses", &COURSE_HEADERS).map_err(to_io)?,
+            transfers: Sheet::new("Transfers", &TRANSFER_HEADERS).map_err(to_io)?,
+            credentials: Sheet::new("Credentials", &CREDENTIAL_HEADERS).map_err(to_io)?,
+            milestones: Sheet::new("Milestones", &MILESTONE_HEADERS).map_err(to_io)?,
+            notes: Sheet::new("Notes", &NOTE_HEADERS).map_err(to_io)?,
+            unit_totals: Sheet::new("Unit Totals", &UNIT_TOTAL_HEADERS).map_err(to_io)?,
+            summary: Sheet::new("Summary", &SUMMARY_HEADERS).map_err(to_io)?,
+        })
+    }
+
+    // Transfer credits are listed under the plan the student was admitted to.
+    fn add_transfers(
+        &mut self,
+        new_id: &str,
+        plan: &str,
+        transfers: &[Transfer],
+    ) -> Result<(), XlsxError> {
+        for transfer in transfers {
+            let course = &transfer.course;
+            let equivalent = transfer.equivalent.as_ref();
+            self.transfers.write_row(&[
+                Cell::Text(new_id),
+                Cell::Text(plan),
+                Cell::Text(&course.subject),
+                Cell::Text(&course.id),
+                Cell::Text(&course.title),
+                Cell::Text(&course.grade),
+                Cell::Number(course.grade_points),
+                Cell::Boolean(course.grade_valid),
+                Cell::Text(course.outcome.as_str()),
+                Cell::Number(Some(f64::from(course.attempt))),
+                Cell::Boolean(course.is_latest_attempt),
+                Cell::Number(course.units_attempted),
+                Cell::Number(course.units_earned),
+                Cell::Text(transfer.school.as_deref().unwrap_or_default()),
+                Cell::Number(transfer.units),
+                Cell::Text(equivalent.map_or("", |equivalent| &equivalent.subject)),
+                Cell::Text(equivalent.map_or("", |equivalent| &equivalent.id)),
+                Cell::Text(&describe_designations(&course.designations)),
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn add_student(&mut self, new_id: &str, student: &StudentInfo) -> Result<(), XlsxError> {
+        let first_plan = student.first_plan().map_or("", |plan| plan.name.as_str());
+        self.add_transfers(new_id, first_plan, &student.transfers)?;
+        for (semester, plan) in semester_plans(student) {
+            for course in &semester.courses {
+                self.courses.write_row(&[
+                    Cell::Text(new_id),
+                    Cell::Text(plan),
+                    Cell::Number(semester.year.parse().ok()),
+                    Cell::Text(&semester.term),
+                    Cell::Text(semester.term_type.as_str()),
+                    Cell::Text(semester.host_institution.as_deref().unwrap_or_default()),
+                    Cell::Text(semester.career.as_deref().unwrap_or_default()),
+                    Cell::Text(semester.academic_group.as_deref().unwrap_or_default()),
+                    Cell::Text(&course.subject),
+                    Cell::Text(&course.id),
+                    Cell::Text(&course.title),
+                    Cell::Text(&course.grade),
+                    Cell::Number(course.grade_points),
+                    Cell::Boolean(course.grade_valid),
+                    Cell::Text(course.outcome.as_str()),
+                    Cell::Number(Some(f64::from(course.attempt))),
+                    Cell::Boolean(course.is_latest_attempt),
+                    Cell::Number(course.units_attempted),
+                    Cell::Number(course.units_earned),
+                    Cell::Number(semester.gpa),
+                    Cell::Number(semester.cgpa),
+                    Cell::Number(semester.printed_gpa),
+                    Cell::Number(semester.printed_cgpa),
+                    Cell::Text(semester.standing.as_deref().unwrap_or_default()),
+                    Cell::Text(semester.honour_roll.map_or("", HonourRoll::as_str)),
+                    Cell::Text(&describe_designations(&course.designations)),
+                ])?;
+            }
+        }
+        let plan = student.current_plan().map_or("", |plan| plan.name.as_str());
+        for credential in &student.credentials {
+            self.credentials.write_row(&[
+                Cell::Text(new_id),
+                Cell::Text(plan),
+                Cell::Text(&credential.program),
+                Cell::Text(credential.conferred.as_deref().unwrap_or_default()),
+            ])?;
+        }
+        for milestone in &student.milestones {
+            self.milestones.write_row(&[
+                Cell::Text(new_id),
+                Cell::Text(plan),
+                Cell::Text(&milestone.name),
+                Cell::Number(milestone.year.parse().ok()),
+                Cell::Text(&milestone.term),
+            ])?;
+        }
+        for note in &student.notes {
+            self.notes.write_row(&[
+                Cell::Text(new_id),
+                Cell::Text(plan),
+                Cell::Number(note.year.as_deref().and_then(|year| year.parse().ok())),
+                Cell::Text(note.term.as_deref().unwrap_or_default()),
+                Cell::Text(&note.text),
+            ])?;
+        }
+        for total in &student.unit_totals {
+            self.unit_totals.write_row(&[
+                Cell::Text(new_id),
+                Cell::Text(plan),
+                Cell::Text(&total.group),
+                Cell::Number(Some(total.units)),
+            ])?;
+        }
+        let count = |n: usize| Cell::Number(u32::try_from(n).ok().map(f64::from));
+        self.summary.write_row(&[
+            Cell::Text(new_id),
+            Cell::Text(plan),
+            count(student.semesters.len()),
+            count(student.semesters.iter().map(|s| s.courses.len()).sum()),
+            count(student.transfers.len()),
+            Cell::Number(student.semesters.last().and_then(|s| s.cgpa)),
+            Cell::Number(Some(f64::from(SCHEMA_VERSION))),
+        ])
+    }
+}
+
+impl Exporter for XlsxExporter {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> io::Result<()> {
+        self.add_student(new_id, student).map_err(to_io)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let mut workbook = Workbook::new();
+        let properties =
+            DocProperties::new().set_custom_property("schema_version", f64::from(SCHEMA_VERSION));
+        workbook.set_properties(&properties);
+        for sheet in [
+            &mut self.courses,
+            &mut self.transfers,
+            &mut self.credentials,
+            &mut self.milestones,
+            &mut self.notes,
+            &mut self.unit_totals,
+            &mut self.summary,
+        ] {
+            sheet.worksheet.autofit();
+            workbook.push_worksheet(std::mem::take(&mut sheet.worksheet));
+        }
+        workbook.save(&self.path).map_err(to_io)
+    }
+}
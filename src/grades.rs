@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Course, Outcome};
+
+/// The grade points of SFU's 4.33 scale. Grades not listed carry no grade
+/// points.
+pub const SFU_GRADE_POINTS: [(&str, f64); 13] = [
+    ("A+", 4.33),
+    ("A", 4.0),
+    ("A-", 3.67),
+    ("B+", 3.33),
+    ("B", 3.0),
+    ("B-", 2.67),
+    ("C+", 2.33),
+    ("C", 2.0),
+    ("C-", 1.67),
+    ("D", 1.0),
+    ("F", 0.0),
+    ("FD", 0.0),
+    ("N", 0.0),
+];
+
+/// Returns the grade points SFU assigns to a letter grade, or `None` if the
+/// grade does not count toward the GPA (e.g. `P`, `WD`, `AU`, or `CR`).
+#[must_use]
+pub fn grade_points(grade: &str) -> Option<f64> {
+    SFU_GRADE_POINTS
+        .iter()
+        .find(|(letter, _)| *letter == grade)
+        .map(|&(_, points)| points)
+}
+
+/// A mapping from grades to grade points, used for the grade points reported
+/// alongside each course. Grades without an entry, such as the notations
+/// `AU`, `WD`, and `CR`, have no grade points rather than zero.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GradeScale(BTreeMap<String, Option<f64>>);
+
+impl Default for GradeScale {
+    /// SFU's 4.33 scale.
+    fn default() -> Self {
+        Self(
+            SFU_GRADE_POINTS
+                .iter()
+                .map(|&(grade, points)| (grade.to_string(), Some(points)))
+                .collect(),
+        )
+    }
+}
+
+impl GradeScale {
+    /// Reads a scale from a JSON object mapping grades to grade points, such
+    /// as `{"A": 4.0, "B": 3.0, "AU": null}`. A `null` entry is the same as
+    /// leaving the grade out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader does not hold such an object.
+    pub fn from_json<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Returns the grade points of `grade` on this scale.
+    #[must_use]
+    pub fn points(&self, grade: &str) -> Option<f64> {
+        self.0.get(grade).copied().flatten()
+    }
+}
+
+/// Classifies how a course attempt ended from its grade. Courses that are
+/// still underway have an empty grade.
+#[must_use]
+pub fn outcome(grade: &str) -> Outcome {
+    match grade {
+        // Letter grades and passes, as well as aegrotat standing, course
+        // challenges, credit, and transfer credit
+        "A+" | "A" | "A-" | "B+" | "B" | "B-" | "C+" | "C" | "C-" | "D" | "P" | "AE" | "CC"
+        | "CR" | "TR" => Outcome::Completed,
+        "F" | "FD" | "N" | "FX" | "CF" | "NC" => Outcome::Failed,
+        "WD" | "WE" | "CN" => Outcome::Withdrawn,
+        "IP" | "" => Outcome::InProgress,
+        "DE" | "GN" => Outcome::Pending,
+        "AU" => Outcome::Audit,
+        _ => Outcome::Unknown,
+    }
+}
+
+/// Computes the unit-weighted GPA of the given courses from their grade
+/// points. Courses without grade points or whose units are unknown are
+/// excluded, and `None` is returned when no graded units remain.
+pub fn gpa<'a>(courses: impl IntoIterator<Item = &'a Course>) -> Option<f64> {
+    let (points, units) = courses
+        .into_iter()
+        .filter_map(|c| Some((c.grade_points?, c.units_attempted?)))
+        .fold((0.0, 0.0), |(points, units), (grade, attempted)| {
+            (points + grade * attempted, units + attempted)
+        });
+    (units > 0.0).then(|| points / units)
+}
@@ -0,0 +1,138 @@
+//! Checking output for the identifying text of the students written to it,
+//! as a safeguard against anonymization regressions.
+
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+use crate::model::{Identity, StudentInfo};
+
+// Shorter text, such as a two letter name, is too likely to turn up by
+// chance to be worth looking for.
+const MIN_LENGTH: usize = 4;
+
+/// Identifying text to look for in output, along with whatever of it has
+/// been found. Findings are described by whose and what text was found,
+/// such as "the student ID from a.pdf", so that reporting them does not
+/// repeat the text itself.
+#[derive(Debug, Default)]
+pub struct LeakCheck {
+    targets: Vec<(String, Vec<u8>)>,
+    // The end of the output scanned so far, to find text split between
+    // two writes.
+    tail: Vec<u8>,
+    found: Vec<String>,
+}
+
+impl LeakCheck {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks for the real ID, name, and birth date of `student` from here
+    /// on, describing findings as coming from `source`.
+    pub fn add_student(&mut self, source: &str, student: &StudentInfo) {
+        self.add_identity(source, &student.id, &student.identity);
+    }
+
+    /// Looks for a real student ID, along with the name and birth date of
+    /// `identity`, from here on, such as those of a transcript that could
+    /// not be parsed in full.
+    pub fn add_identity(&mut self, source: &str, id: &str, identity: &Identity) {
+        let targets = [
+            ("student ID", Some(id)),
+            ("name", identity.name.as_deref()),
+            ("birth date", identity.birth_date.as_deref()),
+        ];
+        for (kind, text) in targets {
+            let Some(text) = text.map(str::trim) else {
+                continue;
+            };
+            if text.len() >= MIN_LENGTH {
+                let description = format!("the {kind} from {source}");
+                self.targets.push((description, text.as_bytes().to_vec()));
+            }
+        }
+    }
+
+    /// Scans the next bytes of the output.
+    pub fn scan(&mut self, bytes: &[u8]) {
+        let mut window = std::mem::take(&mut self.tail);
+        window.extend_from_slice(bytes);
+        for (description, text) in &self.targets {
+            if !self.found.contains(description)
+                && window
+                    .windows(text.len())
+                    .any(|part| part == text.as_slice())
+            {
+                self.found.push(description.clone());
+            }
+        }
+        let longest = self.targets.iter().map(|(_, text)| text.len()).max();
+        let keep = longest.unwrap_or(1).saturating_sub(1);
+        self.tail = window.split_off(window.len().saturating_sub(keep));
+    }
+
+    /// Scans the whole of another output, such as a file written by an
+    /// exporter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output cannot be read.
+    pub fn scan_reader<R: Read>(&mut self, mut reader: R) -> io::Result<()> {
+        self.tail.clear();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            match reader.read(&mut buffer)? {
+                0 => break,
+                read => self.scan(&buffer[..read]),
+            }
+        }
+        self.tail.clear();
+        Ok(())
+    }
+
+    /// Descriptions of the identifying text found so far.
+    #[must_use]
+    pub fn found(&self) -> &[String] {
+        &self.found
+    }
+}
+
+/// A writer that scans everything written through it with a shared
+/// [`LeakCheck`], failing any write in which identifying text is found
+/// instead of passing it on.
+pub struct LeakScanner<W: Write> {
+    inner: W,
+    check: Rc<RefCell<LeakCheck>>,
+}
+
+impl<W: Write> LeakScanner<W> {
+    pub fn new(inner: W, check: Rc<RefCell<LeakCheck>>) -> Self {
+        Self { inner, check }
+    }
+}
+
+impl<W: Write> Write for LeakScanner<W> {
+    // Output is scanned before it is passed on, so that identifying text
+    // found in it never reaches the inner writer, which may be a pipe read
+    // as it is written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut check = self.check.borrow_mut();
+        check.scan(buf);
+        if !check.found().is_empty() {
+            return Err(io::Error::other(format!(
+                "The output contains identifying text: {}",
+                check.found().join(", ")
+            )));
+        }
+        drop(check);
+        self.inner.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,382 @@
+//! Extraction of structured information from SFU SIMS transcript PDFs.
+//!
+//! The simplest entry point is [`Transcript::from_path`], which loads a PDF
+//! and returns the parsed [`StudentInfo`]. The individual stages of the
+//! pipeline (raw `Chunk` extraction, footer removal, and section parsing)
+//! are also exposed for tools that need finer control. Both the official
+//! transcript and the unofficial copy printed by advisors are supported, and
+//! the layout is detected automatically. Other institutions can be added by
+//! implementing [`TranscriptParser`].
+
+#![warn(clippy::all, clippy::pedantic)]
+
+#[cfg(feature = "export")]
+pub mod anonymize;
+pub mod audit;
+#[cfg(feature = "export")]
+pub mod cache;
+pub mod chunk;
+pub mod cmap;
+pub mod cohort;
+pub mod diff;
+#[cfg(feature = "export")]
+pub mod equivalency;
+pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod grades;
+#[cfg(feature = "export")]
+pub mod leak;
+pub mod merge;
+pub mod model;
+pub mod parse;
+pub mod parser;
+pub mod pdf;
+pub mod pesc;
+pub mod prerequisites;
+pub mod privacy;
+pub mod progress;
+pub mod redact;
+pub mod risk;
+pub mod stats;
+pub mod synthetic;
+#[cfg(feature = "vault")]
+pub mod vault;
+pub mod vocabulary;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use std::path::Path;
+
+use lopdf::Document;
+
+pub use chunk::Chunk;
+pub use error::{Diagnostics, ErrorCategory, TranscriptError};
+pub use model::{
+    Course, Credential, DegreeProgress, Designation, Equivalent, HonourRoll, Outcome, Plan,
+    Requirement, RequirementStatus, Semester, StudentInfo, TermLoad, TermType, Transfer,
+};
+pub use parser::{SfuParser, TranscriptParser};
+pub use pdf::{PageOrder, TranscriptLayout};
+pub use vocabulary::Vocabulary;
+
+/// Options controlling how transcripts are parsed.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// Record anomalies such as unknown grades, malformed rows, or missing
+    /// footers as warnings on the result instead of failing.
+    pub lenient: bool,
+    /// Treat grades outside of the vocabulary as anomalies, failing unless
+    /// lenient, instead of keeping their courses with the grade marked
+    /// invalid.
+    pub strict: bool,
+    /// How the pages are put into reading order before parsing.
+    pub page_order: PageOrder,
+    /// The grades and WQB tags rows may contain.
+    pub vocabulary: Vocabulary,
+    /// Compare the computed term and cumulative GPAs against those printed
+    /// on the transcript, treating differences beyond this tolerance as
+    /// anomalies. See [`StudentInfo::gpa_mismatches`].
+    pub gpa_tolerance: Option<f64>,
+}
+
+/// Entry points for parsing academic advisement (degree progress) reports.
+pub struct ProgressReport;
+
+impl ProgressReport {
+    /// Loads the degree progress report PDF at `path` and extracts its
+    /// requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded as a PDF or if it does
+    /// not list any requirements.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<DegreeProgress, TranscriptError> {
+        Self::from_path_with(path, &ParseOptions::default())
+    }
+
+    /// Loads the degree progress report PDF at `path` and extracts its
+    /// requirements using the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded as a PDF or if it does
+    /// not list any requirements.
+    pub fn from_path_with<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<DegreeProgress, TranscriptError> {
+        Self::from_document_with(&pdf::load_document(path.as_ref())?, options)
+    }
+
+    /// Extracts the requirements from an already loaded degree progress
+    /// report using the given options. Page headers and footers are kept, as
+    /// they do not contain requirement blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document does not list any requirements.
+    pub fn from_document_with(
+        document: &Document,
+        options: &ParseOptions,
+    ) -> Result<DegreeProgress, TranscriptError> {
+        let mut diagnostics = Diagnostics::new(options.lenient);
+        let chunks: Vec<Chunk> = Transcript::page_chunks_with(document, &options.page_order)?
+            .into_iter()
+            .flatten()
+            .collect();
+        progress::process_progress_chunks(&chunks, &mut diagnostics)
+    }
+}
+
+/// Entry points for parsing complete transcripts.
+pub struct Transcript;
+
+impl Transcript {
+    /// Loads the transcript PDF at `path` and extracts its student information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded as a PDF or if it does
+    /// not follow the SFU SIMS transcript layout.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<StudentInfo, TranscriptError> {
+        Self::from_path_with(path, &ParseOptions::default())
+    }
+
+    /// Loads the transcript PDF at `path` and extracts its student information
+    /// using the given options. Files with an `.xml` extension are read as
+    /// PESC College Transcripts instead, as described in [`pesc`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded as a PDF or if it does
+    /// not follow the SFU SIMS transcript layout.
+    pub fn from_path_with<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"))
+        {
+            return pesc::from_path(path, options).and_then(|s| Self::check_gpas(s, options));
+        }
+        Self::from_document_with(&pdf::load_document(path)?, options)
+    }
+
+    /// Extracts the student information from an in-memory transcript PDF.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes cannot be loaded as a PDF or if they do
+    /// not follow the SFU SIMS transcript layout.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StudentInfo, TranscriptError> {
+        Self::from_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /// Extracts the student information from an in-memory transcript PDF
+    /// using the given options. Bytes that start like an XML document are
+    /// read as a PESC College Transcript instead, as described in [`pesc`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes cannot be loaded as a PDF or if they do
+    /// not follow the SFU SIMS transcript layout.
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        if pesc::is_xml(bytes) {
+            return pesc::from_bytes(bytes, options).and_then(|s| Self::check_gpas(s, options));
+        }
+        Self::from_document_with(&pdf::load_document_mem(bytes)?, options)
+    }
+
+    /// Extracts the student information from an already loaded PDF document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document does not follow the SFU SIMS
+    /// transcript layout.
+    pub fn from_document(document: &Document) -> Result<StudentInfo, TranscriptError> {
+        Self::from_document_with(document, &ParseOptions::default())
+    }
+
+    /// Extracts the student information from an already loaded PDF document
+    /// using the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document does not follow the SFU SIMS
+    /// transcript layout.
+    pub fn from_document_with(
+        document: &Document,
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        let pages = Self::page_chunks_with(document, &options.page_order)?;
+        let parser = parser::detect_with(&pages, &options.vocabulary);
+        Self::parse_pages(parser.as_ref(), pages, options)
+    }
+
+    /// Extracts the student information from an already loaded PDF document
+    /// with a specific parser instead of the detected one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document does not follow the parser's layout.
+    pub fn from_document_using(
+        document: &Document,
+        parser: &dyn TranscriptParser,
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        let pages = Self::page_chunks_with(document, &options.page_order)?;
+        Self::parse_pages(parser, pages, options)
+    }
+
+    fn parse_pages(
+        parser: &dyn TranscriptParser,
+        pages: Vec<Vec<Chunk>>,
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        let mut diagnostics = Diagnostics::new(options.lenient).with_strict_grades(options.strict);
+        let combined = parser.combine(pages, &mut diagnostics)?;
+        let student = parser.parse(&combined, &mut diagnostics)?;
+        Self::check_gpas(student, options)
+    }
+
+    // Reports the GPA mismatches of a parsed transcript like any other
+    // anomaly, when the options ask for them to be checked.
+    fn check_gpas(
+        mut student: StudentInfo,
+        options: &ParseOptions,
+    ) -> Result<StudentInfo, TranscriptError> {
+        let Some(tolerance) = options.gpa_tolerance else {
+            return Ok(student);
+        };
+        let mut diagnostics = Diagnostics::new(options.lenient);
+        for mismatch in student.gpa_mismatches(tolerance) {
+            diagnostics.report(mismatch)?;
+        }
+        student.warnings.extend(diagnostics.into_warnings());
+        Ok(student)
+    }
+
+    /// Checks the structural invariants of a transcript (footer positions,
+    /// section markers, grade vocabulary, and row widths) without producing
+    /// any data. Returns a description of every problem found, so an empty
+    /// result means the transcript can be extracted as is.
+    #[must_use]
+    pub fn validate(document: &Document) -> Vec<String> {
+        Self::validate_with(document, &ParseOptions::default())
+    }
+
+    /// Checks the structural invariants of a transcript like [`validate`],
+    /// putting the pages in order and checking the grades and GPAs as
+    /// `options` direct. Leniency does not apply, as every problem is
+    /// reported.
+    ///
+    /// [`validate`]: Self::validate
+    #[must_use]
+    pub fn validate_with(document: &Document, options: &ParseOptions) -> Vec<String> {
+        let mut diagnostics = Diagnostics::new(true);
+        let mut problems = Vec::new();
+        let combined = Self::page_chunks_with(document, &options.page_order).and_then(|pages| {
+            let parser = parser::detect_with(&pages, &options.vocabulary);
+            let chunks = parser.combine(pages, &mut diagnostics)?;
+            Ok((parser, chunks))
+        });
+        match combined {
+            Ok((parser, chunks)) => {
+                let missing = parser.missing_sections(&chunks);
+                if missing.is_empty() {
+                    match parser.parse(&chunks, &mut diagnostics) {
+                        Ok(student) => {
+                            let mismatches = options
+                                .gpa_tolerance
+                                .map(|tolerance| student.gpa_mismatches(tolerance))
+                                .unwrap_or_default();
+                            problems.extend(mismatches.iter().map(ToString::to_string));
+                        }
+                        Err(err) => problems.push(err.to_string()),
+                    }
+                } else {
+                    problems.extend(
+                        missing
+                            .into_iter()
+                            .map(|section| TranscriptError::MissingSection(section).to_string()),
+                    );
+                }
+            }
+            Err(err) => problems.push(err.to_string()),
+        }
+        let mut warnings = diagnostics.into_warnings();
+        warnings.append(&mut problems);
+        warnings
+    }
+
+    /// Detects whether a document is an official or unofficial transcript.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page contents cannot be decoded.
+    pub fn layout(document: &Document) -> Result<TranscriptLayout, TranscriptError> {
+        Ok(TranscriptLayout::detect(&Self::page_chunks(document)?))
+    }
+
+    /// Extracts the simplified `Chunk`s of all pages with the page headers
+    /// and footers removed by the detected parser. For SFU transcripts, this
+    /// is the input expected by [`parse::process_chunks`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page contents cannot be decoded or if the page
+    /// footers are not where they are expected.
+    pub fn chunks(document: &Document) -> Result<Vec<Chunk>, TranscriptError> {
+        let pages = Self::page_chunks(document)?;
+        parser::detect(&pages).combine(pages, &mut Diagnostics::default())
+    }
+
+    /// Reconstructs the table rows of every page from the positions of the
+    /// text, with one cell per column of the page. See
+    /// [`pdf::reconstruct_rows`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page contents cannot be decoded.
+    pub fn page_rows(document: &Document) -> Result<Vec<Vec<Vec<String>>>, TranscriptError> {
+        Ok(pdf::extract_page_runs(document)?
+            .iter()
+            .map(|runs| pdf::reconstruct_rows(runs, pdf::COLUMN_TOLERANCE))
+            .collect())
+    }
+
+    /// Extracts the simplified `Chunk`s of every page separately, before the
+    /// page footers are removed. The pages are put into reading order
+    /// automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page contents cannot be decoded.
+    pub fn page_chunks(document: &Document) -> Result<Vec<Vec<Chunk>>, TranscriptError> {
+        Self::page_chunks_with(document, &PageOrder::Auto)
+    }
+
+    /// Extracts the simplified `Chunk`s of every page separately like
+    /// [`Transcript::page_chunks`], with the pages in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the page contents cannot be decoded or if the
+    /// order cannot be resolved.
+    pub fn page_chunks_with(
+        document: &Document,
+        order: &PageOrder,
+    ) -> Result<Vec<Vec<Chunk>>, TranscriptError> {
+        let chunks = pdf::extract_ordered_page_chunks(document, order)?;
+        Ok(chunks
+            .into_iter()
+            .map(|page| page.into_iter().map(Chunk::simplify).collect())
+            .collect())
+    }
+}
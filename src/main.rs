@@ -1,505 +1,2737 @@
 #![warn(clippy::all, clippy::pedantic)]
 
-use std::collections::BTreeMap;
-use std::io::{Error, ErrorKind};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
 
-use clap::{Args, Parser};
-use lopdf::content::{Content, Operation};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use lopdf::Document;
-use lopdf::Error as LopdfError;
-use lopdf::Object;
-use lopdf::Result as LopdfResult;
 use rand::prelude::SliceRandom;
 use rand::thread_rng;
+use scrape_sfu_transcript::anonymize::{key_fingerprint, Anonymizer, IdMap};
+use scrape_sfu_transcript::audit::{AuditedRequirement, DegreeRequirements};
+use scrape_sfu_transcript::cache::ParseCache;
+use scrape_sfu_transcript::cohort::{CohortBuilder, CohortReport};
+use scrape_sfu_transcript::equivalency::EquivalencyTable;
+#[cfg(feature = "arrow")]
+use scrape_sfu_transcript::export::arrow::ArrowIpcExporter;
+#[cfg(feature = "avro")]
+use scrape_sfu_transcript::export::avro::AvroExporter;
+use scrape_sfu_transcript::export::cohort::{write_cohort_csv, write_cohort_json};
+use scrape_sfu_transcript::export::csv::{
+    default_long_columns, long_column, read_long_row_keys, CourseCsvExporter, CsvOptions,
+    GpaCsvExporter, LongCsvExporter, RowOrder, TermLoadCsvExporter, WideCsvExporter, LONG_COLUMNS,
+};
+#[cfg(feature = "duckdb")]
+use scrape_sfu_transcript::export::duckdb::DuckDbExporter;
+use scrape_sfu_transcript::export::edi::EdiExporter;
+use scrape_sfu_transcript::export::html::HtmlExporter;
+use scrape_sfu_transcript::export::json::{JsonExporter, MessagePackExporter, NdjsonExporter};
+use scrape_sfu_transcript::export::manifest::{
+    sha256_bytes, sha256_file, EntryStatus, Manifest, ManifestEntry,
+};
+use scrape_sfu_transcript::export::markdown::MarkdownExporter;
+#[cfg(feature = "parquet")]
+use scrape_sfu_transcript::export::parquet::ParquetExporter;
+use scrape_sfu_transcript::export::pesc::PescExporter;
+#[cfg(feature = "postgres")]
+use scrape_sfu_transcript::export::postgres::PostgresExporter;
+use scrape_sfu_transcript::export::progress::{
+    ProgressCsvExporter, ProgressExporter, ProgressJsonExporter, ProgressNdjsonExporter,
+};
+#[cfg(feature = "proto")]
+use scrape_sfu_transcript::export::proto::ProtoExporter;
+use scrape_sfu_transcript::export::risk::{write_risk_csv, write_risk_json};
+use scrape_sfu_transcript::export::sql::SqlExporter;
+use scrape_sfu_transcript::export::sqlite::{read_sqlite_row_keys, SqliteExporter};
+use scrape_sfu_transcript::export::table::{write_table, TableExporter};
+use scrape_sfu_transcript::export::xlsx::XlsxExporter;
+use scrape_sfu_transcript::export::{long_records, remove_present_rows, Exporter, RowKey};
+use scrape_sfu_transcript::grades::GradeScale;
+use scrape_sfu_transcript::leak::{LeakCheck, LeakScanner};
+use scrape_sfu_transcript::merge::merge;
+use scrape_sfu_transcript::model::{term_key, Identity, TERMS};
+use scrape_sfu_transcript::parse::header_identity;
+use scrape_sfu_transcript::pdf::{load_document, map_file};
+use scrape_sfu_transcript::prerequisites::{PrerequisiteRules, UnmetPrerequisites};
+use scrape_sfu_transcript::privacy::{coarsen_terms, suppress_rare, TermGranularity};
+use scrape_sfu_transcript::redact::{identifying_text, keeps_records};
+use scrape_sfu_transcript::risk::{RiskRules, StudentRisk};
+use scrape_sfu_transcript::stats::{self, GradeDistribution, DISTRIBUTION_GRADES};
+use scrape_sfu_transcript::synthetic::{self, StudentConfig};
+#[cfg(feature = "vault")]
+use scrape_sfu_transcript::vault::{read_identity, Recipient, Vault};
+use scrape_sfu_transcript::{
+    Chunk, Course, ErrorCategory, PageOrder, ParseOptions, ProgressReport, RequirementStatus,
+    StudentInfo, Transcript, TranscriptError, Vocabulary,
+};
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-enum Chunk {
-    Chunks(Vec<Chunk>),
-    String(String),
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+// The flags are independent command line switches rather than states.
+#[allow(clippy::struct_excessive_bools)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    source: InputSource,
+
+    #[command(flatten)]
+    walk: WalkArgs,
+
+    /// Skip input files whose paths match this glob pattern (repeatable)
+    #[arg(long, value_parser = parse_pattern)]
+    exclude: Vec<glob::Pattern>,
+
+    /// Anonymized (first) student ID to use during export
+    #[arg(short, long)]
+    newid: Option<usize>,
+
+    /// How anonymized student IDs are assigned
+    #[arg(long, value_enum, default_value_t = Anonymization::Sequential)]
+    anonymize: Anonymization,
+
+    /// File containing the secret key for HMAC anonymization, if the key
+    /// itself is not in the environment
+    #[arg(long, env = KEY_FILE_VAR)]
+    key_file: Option<PathBuf>,
+
+    /// CSV file persisting the real to anonymized ID mapping across runs
+    #[arg(long, conflicts_with = "key_file")]
+    id_map: Option<PathBuf>,
+
+    /// CSV file collecting the real student IDs, encrypted to
+    /// --vault-recipient, for re-identification by the holder of its key
+    #[cfg(feature = "vault")]
+    #[arg(long, requires = "vault_recipient")]
+    vault: Option<PathBuf>,
+
+    /// Public age key of the data steward allowed to re-identify students
+    #[cfg(feature = "vault")]
+    #[arg(long, requires = "vault", value_parser = parse_recipient)]
+    vault_recipient: Option<Recipient>,
+
+    /// Output format for the extracted data
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// Kind of document being extracted
+    #[arg(long, value_enum, default_value_t = DocType::Transcript)]
+    doc_type: DocType,
+
+    /// Record anomalies as warnings and keep the partial data instead of
+    /// failing on unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+
+    /// Fail on grades outside of the grade vocabulary instead of keeping
+    /// their courses with the grade marked invalid
+    #[arg(long)]
+    strict: bool,
+
+    /// Reading order of the pages: auto, tree, labels, printed, or an
+    /// explicit list of pages such as 2,1,3
+    #[arg(long, default_value = "auto")]
+    page_order: PageOrder,
+
+    /// JSON profile of grades and WQB tags to recognize in addition to
+    /// SFU's, for notations newer than this release
+    #[arg(long, value_parser = parse_vocabulary)]
+    vocabulary: Option<Vocabulary>,
+
+    /// Compare the computed term and cumulative GPAs against those printed
+    /// on the transcript, treating differences beyond this tolerance (such
+    /// as 0.01) as anomalies
+    #[arg(long)]
+    gpa_tolerance: Option<f64>,
+
+    /// Directory caching parsed transcripts by the SHA-256 of their PDFs, so
+    /// that re-runs only parse new or changed files
+    #[arg(long)]
+    cache: Option<PathBuf>,
+
+    /// Merge transcripts of the same student (such as snapshots pulled each
+    /// term) into one record, with the latest grades winning. All
+    /// transcripts are parsed before any are written
+    #[arg(long)]
+    merge_snapshots: bool,
+
+    /// Suppress plans, credentials, transfer institutions, and admission
+    /// terms shared by fewer than this many students in the batch. All
+    /// transcripts are parsed before any are written
+    #[arg(long, value_parser = clap::value_parser!(u32).range(2..))]
+    k_anonymity: Option<u32>,
+
+    /// Give only the academic or calendar year of every term and the year
+    /// of every conferral, rather than the exact term and date
+    #[arg(long, value_enum)]
+    coarsen_term: Option<Coarsening>,
+
+    /// Also write a cohort-level summary of the batch (students per plan,
+    /// GPA and earned units per entry cohort) to this path, as JSON if it
+    /// ends in .json and as CSV otherwise
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Also write the course load of every term (courses, units attempted
+    /// and earned, and withdrawals) to this path, as CSV with one row per
+    /// student and term
+    #[arg(long)]
+    term_table: Option<PathBuf>,
+
+    /// TOML or JSON file of rules flagging students at risk, such as a term
+    /// GPA below 2.0 in two terms, read as TOML when its extension is .toml
+    #[arg(long, requires = "risk_report")]
+    risk_rules: Option<PathBuf>,
+
+    /// Also write a report of the students flagged by --risk-rules to this
+    /// path, as JSON if it ends in .json and as CSV otherwise
+    #[arg(long, requires = "risk_rules")]
+    risk_report: Option<PathBuf>,
+
+    /// Also write a JSON manifest of the batch to this path, listing each
+    /// input file with its SHA-256, anonymized ID, rows, warnings, and status
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+
+    /// JSON file mapping grades to grade points, replacing SFU's 4.33 scale
+    /// in the grade points column
+    #[arg(long)]
+    grade_scale: Option<PathBuf>,
+
+    /// CSV file mapping the courses of other institutions to SFU courses,
+    /// with the columns institution, subject, number, sfu subject, and sfu
+    /// number, recording the SFU equivalent of each transfer credit
+    #[arg(long)]
+    equivalencies: Option<PathBuf>,
+
+    /// Only write the courses and transfer credits of these subjects, such
+    /// as CMPT,MACM. GPAs still cover every course
+    #[arg(long, value_delimiter = ',')]
+    subject: Vec<String>,
+
+    /// Only write the terms from this one on, such as "2019 Fall"
+    #[arg(long, value_parser = parse_term)]
+    from_term: Option<(u32, usize)>,
+
+    /// Only write the terms up to and including this one, such as
+    /// "2023 Summer"
+    #[arg(long, value_parser = parse_term)]
+    to_term: Option<(u32, usize)>,
+
+    /// Leave out transfer credits, which have no term, such as when only a
+    /// window of terms is wanted
+    #[arg(long)]
+    no_transfers: bool,
+
+    /// Field separator for CSV output, such as ';' or '\t'
+    #[arg(long, value_parser = parse_delimiter)]
+    delimiter: Option<u8>,
+
+    /// Omit the header row from CSV output
+    #[arg(long)]
+    no_header: bool,
+
+    /// Columns of the long CSV layout to write, in order, such as
+    /// id,year,term,subject,number,grade. The keys are listed by
+    /// --emit-schema
+    #[arg(long, value_delimiter = ',', value_parser = parse_column)]
+    columns: Vec<usize>,
+
+    /// Add the term and cumulative GPAs printed on the transcript to the long
+    /// CSV layout, beside the ones computed from the grades
+    #[arg(long, conflicts_with = "columns")]
+    printed_gpas: bool,
+
+    /// Order of the rows across the whole batch of the long CSV layout,
+    /// rather than student by student in the order of each transcript
+    #[arg(long, value_enum)]
+    sort: Option<SortOrder>,
+
+    /// Print the names and types of the long CSV columns as JSON and exit
+    #[arg(long, exclusive = true)]
+    emit_schema: bool,
+
+    /// Row layout for CSV output
+    #[arg(short, long, value_enum, default_value_t = Layout::Long)]
+    layout: Layout,
+
+    /// Path to write the output to instead of stdout, or a postgres:// URL
+    /// to load the long layout into (requires the postgres feature)
+    #[arg(short, long, required_if_eq_any([
+        ("format", "sqlite"),
+        ("format", "xlsx"),
+        ("format", "arrow"),
+        ("format", "parquet"),
+        ("format", "duckdb"),
+        ("format", "avro"),
+    ]))]
+    output: Option<PathBuf>,
+
+    /// Add to the existing output instead of replacing it, leaving out the
+    /// courses and transfer credits it already has for each student. Only
+    /// for the long CSV and TSV layout and sqlite databases
+    #[arg(long, requires = "output")]
+    append: bool,
+
+    /// Table to load rows into when the output is a postgres:// URL,
+    /// optionally qualified by its schema. It is created if missing
+    #[arg(long, default_value = "transcript_rows")]
+    pg_table: String,
+
+    /// Extract and validate every input, reporting the rows, terms, and
+    /// warnings each would contribute, without writing any output,
+    /// manifest, report, ID map, or cache entry
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the simplified chunk tree of a transcript with indices, for
+    /// diagnosing layouts that break extraction
+    Inspect(InspectArgs),
+    /// Check that transcripts satisfy every structural invariant the parser
+    /// relies on, without extracting any data
+    Validate(ValidateArgs),
+    /// Print grade distribution tables per course and per term
+    Stats(StatsArgs),
+    /// Compare two transcripts of the same student, printing new terms and
+    /// the courses that were added, removed, or had their grades revised
+    Diff(DiffArgs),
+    /// Report the completed courses of each transcript that were taken
+    /// before their prerequisites were met
+    Prereqs(PrereqsArgs),
+    /// Audit transcripts against degree requirements, reporting which are
+    /// satisfied and what is missing
+    Audit(AuditArgs),
+    /// Write a synthetic transcript PDF of a made up student, for testing
+    /// without real student records
+    Gen(GenArgs),
+    /// Write a copy of a transcript PDF with the student ID and other
+    /// identifying text replaced by an anonymized ID, for sharing with
+    /// auditors
+    Redact(RedactArgs),
+    /// Print the real student IDs behind anonymized IDs from a vault, for
+    /// the data steward holding its secret key
+    #[cfg(feature = "vault")]
+    Reveal(RevealArgs),
+    /// Serve extraction over HTTP: POST a transcript PDF to /extract to
+    /// receive the extracted student information as JSON
+    #[cfg(feature = "serve")]
+    Serve(ServeArgs),
+}
+
+#[cfg(feature = "serve")]
+#[derive(Args, Debug)]
+struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// Record anomalies as warnings instead of rejecting unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+
+    /// Largest accepted request body, in bytes
+    #[arg(long, default_value_t = 16 * 1024 * 1024)]
+    max_body: u64,
+}
+
+#[cfg(feature = "serve")]
+fn json_response(
+    status: u16,
+    body: &serde_json::Value,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let header = tiny_http::Header::from_bytes("Content-Type", "application/json")
+        .expect("static header is valid");
+    tiny_http::Response::from_data(body.to_string())
+        .with_status_code(status)
+        .with_header(header)
+}
+
+#[cfg(feature = "serve")]
+fn handle_extract(
+    request: &mut tiny_http::Request,
+    args: &ServeArgs,
+    options: &ParseOptions,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    use std::io::Read;
+
+    let error =
+        |status, message: String| json_response(status, &serde_json::json!({ "error": message }));
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > args.max_body)
+    {
+        return error(413, "request body is too large".to_string());
+    }
+    let mut bytes = Vec::new();
+    let read = request
+        .as_reader()
+        .take(args.max_body + 1)
+        .read_to_end(&mut bytes);
+    if let Err(err) = read {
+        return error(400, err.to_string());
+    }
+    if bytes.len() as u64 > args.max_body {
+        return error(413, "request body is too large".to_string());
+    }
+    match Transcript::from_bytes_with(&bytes, options) {
+        Ok(student) => match serde_json::to_value(&student) {
+            Ok(value) => json_response(200, &value),
+            Err(err) => error(500, err.to_string()),
+        },
+        Err(err) => error(422, err.to_string()),
+    }
+}
+
+// Requests are handled one at a time, as parsing a transcript takes only a
+// few milliseconds.
+#[cfg(feature = "serve")]
+fn serve(args: &ServeArgs) -> Result<(), Error> {
+    let server = tiny_http::Server::http(&args.addr).map_err(Error::other)?;
+    eprintln!("Listening on http://{}", args.addr);
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    for mut request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (tiny_http::Method::Post, "/extract") => handle_extract(&mut request, args, &options),
+            (_, "/extract") => json_response(405, &serde_json::json!({ "error": "use POST" })),
+            _ => json_response(404, &serde_json::json!({ "error": "not found" })),
+        };
+        if let Err(err) = request.respond(response) {
+            eprintln!("Error: {err}");
+        }
+    }
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Path to a transcript PDF, a directory of transcripts, or a glob
+    /// pattern
+    input: PathBuf,
+
+    #[command(flatten)]
+    walk: WalkArgs,
+
+    /// JSON profile of grades and WQB tags to recognize in addition to
+    /// SFU's, for notations newer than this release
+    #[arg(long, value_parser = parse_vocabulary)]
+    vocabulary: Option<Vocabulary>,
+
+    /// Also report terms whose computed GPAs differ from the printed ones
+    /// by more than this tolerance, such as 0.01
+    #[arg(long)]
+    gpa_tolerance: Option<f64>,
+}
+
+fn validate(args: &ValidateArgs) -> Result<ExitStatus, Error> {
+    let sources = collect_sources(&args.input, args.input.is_dir(), &args.walk, PDF)?;
+    let options = ParseOptions {
+        vocabulary: args.vocabulary.clone().unwrap_or_default(),
+        gpa_tolerance: args.gpa_tolerance,
+        ..ParseOptions::default()
+    };
+
+    let mut failures = 0;
+    let mut status = ExitStatus::Success;
+    for source in &sources {
+        let (problems, failure) = match load_document(source) {
+            Ok(document) => (
+                Transcript::validate_with(&document, &options),
+                ExitStatus::Validation,
+            ),
+            Err(err) => (vec![err.to_string()], err.category().into()),
+        };
+        if problems.is_empty() {
+            println!("{}: OK", source.display());
+        } else {
+            failures += 1;
+            status = status.and(failure);
+            println!("{}: FAILED", source.display());
+            for problem in problems {
+                println!("  {problem}");
+            }
+        }
+    }
+
+    println!(
+        "{} of {} transcripts passed",
+        sources.len() - failures,
+        sources.len()
+    );
+    Ok(status)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum StatsGrouping {
+    /// One table row per subject and course ID
+    Course,
+    /// One table row per term
+    Term,
+    /// Both tables
+    Both,
+}
+
+#[derive(Args, Debug)]
+struct StatsArgs {
+    /// Path to a transcript PDF, a directory of transcripts, or a glob
+    /// pattern
+    input: PathBuf,
+
+    #[command(flatten)]
+    walk: WalkArgs,
+
+    /// Which tables to print
+    #[arg(long, value_enum, default_value_t = StatsGrouping::Both)]
+    by: StatsGrouping,
+
+    /// Record anomalies as warnings instead of skipping unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+}
+
+fn format_optional(value: Option<f64>, format: impl Fn(f64) -> String) -> String {
+    value.map_or_else(|| "-".to_string(), format)
+}
+
+fn distribution_table<W: Write>(
+    out: &mut W,
+    label: &str,
+    groups: impl Iterator<Item = (String, GradeDistribution)>,
+) -> Result<(), Error> {
+    let header: Vec<String> = std::iter::once(label)
+        .chain(["N"])
+        .chain(DISTRIBUTION_GRADES)
+        .chain(["Other", "Mean", "DFW"])
+        .map(str::to_string)
+        .collect();
+    let rows: Vec<Vec<String>> = groups
+        .map(|(name, distribution)| {
+            std::iter::once(name)
+                .chain([distribution.enrollments.to_string()])
+                .chain(
+                    DISTRIBUTION_GRADES
+                        .iter()
+                        .map(|grade| distribution.count(grade).to_string()),
+                )
+                .chain([
+                    distribution.other.to_string(),
+                    format_optional(distribution.mean_grade_points(), |m| format!("{m:.2}")),
+                    format_optional(distribution.dfw_rate(), |r| format!("{:.1}%", r * 100.0)),
+                ])
+                .collect()
+        })
+        .collect();
+    write_table(out, &header, &rows, 1)
+}
+
+fn stats(args: &StatsArgs) -> Result<(), Error> {
+    let sources = collect_sources(&args.input, args.input.is_dir(), &args.walk, TRANSCRIPTS)?;
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    let students: Vec<StudentInfo> = sources
+        .iter()
+        .filter_map(
+            |source| match Transcript::from_path_with(source, &options) {
+                Ok(student) => Some(student),
+                Err(err) => {
+                    eprintln!("Error: {}: {err}", source.display());
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let mut out = std::io::stdout().lock();
+    writeln!(out, "{} transcripts", students.len())?;
+    if args.by != StatsGrouping::Term {
+        writeln!(out)?;
+        let groups = stats::by_course(&students)
+            .into_iter()
+            .map(|((subject, id), distribution)| (format!("{subject} {id}"), distribution));
+        distribution_table(&mut out, "Course", groups)?;
+    }
+    if args.by != StatsGrouping::Course {
+        writeln!(out)?;
+        let groups = stats::by_term(&students)
+            .into_iter()
+            .map(|((_, year, term), distribution)| (format!("{year} {term}"), distribution));
+        distribution_table(&mut out, "Term", groups)?;
+    }
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// The earlier transcript PDF
+    before: PathBuf,
+
+    /// The later transcript PDF
+    after: PathBuf,
+
+    /// Print the differences as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Record anomalies as warnings instead of rejecting unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+}
+
+#[derive(Args, Debug)]
+struct GenArgs {
+    /// Where to write the transcript PDF
+    output: PathBuf,
+
+    /// Student ID printed on the transcript
+    #[arg(long, default_value = "301000001")]
+    id: String,
+
+    /// Number of consecutive terms, starting in Fall 2017
+    #[arg(long, default_value_t = 8)]
+    terms: usize,
+
+    /// Number of courses in each regular term
+    #[arg(long, default_value_t = 4)]
+    courses: usize,
+
+    /// Number of transfer credits
+    #[arg(long, default_value_t = 2)]
+    transfers: usize,
+
+    /// Include retaken courses, withdrawals, co-op and exchange terms, a
+    /// plan change, and the other records the parser treats specially
+    #[arg(long)]
+    edge_cases: bool,
+
+    /// Number of lines on each page before its footer
+    #[arg(long, default_value_t = 40)]
+    lines_per_page: usize,
+
+    /// Split the second transfer credit over a page break
+    #[arg(long)]
+    split_transfer: bool,
+}
+
+fn generate(args: &GenArgs) -> Result<(), Error> {
+    let config = StudentConfig {
+        id: args.id.clone(),
+        terms: args.terms,
+        courses_per_term: args.courses,
+        transfers: args.transfers,
+        edge_cases: args.edge_cases,
+    };
+    let layout = synthetic::Layout {
+        lines_per_page: args.lines_per_page,
+        split_transfer: args.split_transfer,
+    };
+    let mut document = synthetic::render(&synthetic::student(&config), &layout);
+    document
+        .save(&args.output)
+        .map_err(|err| output_error(Error::other(format!("{}: {err}", args.output.display()))))?;
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+struct RedactArgs {
+    /// Transcript PDF to redact
+    input: PathBuf,
+
+    /// Where to write the redacted PDF
+    output: PathBuf,
+
+    #[command(flatten)]
+    anonymized: AnonymizedId,
+
+    /// Other identifying text to replace beyond the ID, name, and birth
+    /// date, as printed on the transcript (repeatable)
+    #[arg(long)]
+    text: Vec<String>,
+
+    /// Record anomalies as warnings instead of rejecting unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+}
+
+#[cfg(feature = "vault")]
+#[derive(Args, Debug)]
+struct RevealArgs {
+    /// Vault written with --vault
+    vault: PathBuf,
+
+    /// Anonymized IDs to re-identify, or every ID in the vault if none are
+    /// given
+    ids: Vec<String>,
+
+    /// age identity file holding the secret key the vault is encrypted to
+    #[arg(long)]
+    identity: PathBuf,
+}
+
+// Every ID is decrypted before any is printed, so a vault that cannot be
+// read in full prints nothing.
+#[cfg(feature = "vault")]
+fn reveal(args: &RevealArgs) -> Result<(), Error> {
+    let vault = Vault::load(&args.vault)?;
+    let identity = read_identity(&args.identity)?;
+    let ids: Vec<&str> = if args.ids.is_empty() {
+        vault.ids().collect()
+    } else {
+        args.ids.iter().map(String::as_str).collect()
+    };
+    let mut rows = Vec::with_capacity(ids.len());
+    for id in ids {
+        let real_id = vault.reveal(&identity, id)?.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("The vault has no entry for {id}"),
+            )
+        })?;
+        rows.push([id.to_string(), real_id]);
+    }
+    let mut writer = csv::Writer::from_writer(std::io::stdout().lock());
+    writer.write_record(["anonymized_id", "real_id"])?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()
+}
+
+/// How the anonymized ID printed on a redacted transcript is chosen. Without
+/// `--newid` or `--id-map`, it is derived with HMAC from a key file or the
+/// key in the environment, as when extracting.
+#[derive(Args, Debug)]
+struct AnonymizedId {
+    /// Anonymized student ID to print
+    #[arg(short, long)]
+    newid: Option<usize>,
+
+    /// File containing the secret key for deriving the ID with HMAC-SHA256,
+    /// if the key itself is not in the environment
+    #[arg(long, env = KEY_FILE_VAR)]
+    key_file: Option<PathBuf>,
+
+    /// CSV file persisting the real to anonymized ID mapping across runs
+    #[arg(long, conflicts_with = "newid")]
+    id_map: Option<PathBuf>,
+}
+
+fn redact(args: &RedactArgs) -> Result<(), Error> {
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    let failure = |err: &TranscriptError| transcript_failure(&args.input, err);
+    let mut document = load_document(&args.input).map_err(|err| failure(&err))?;
+    let student =
+        Transcript::from_document_with(&document, &options).map_err(|err| failure(&err))?;
+
+    let ids = &args.anonymized;
+    // A key file may come from the environment, so it only applies when
+    // neither of the other flags is given.
+    let mut anonymizer = match (ids.newid, &ids.key_file, &ids.id_map) {
+        (_, _, Some(path)) => Anonymizer::Mapped(IdMap::load(path, 1)?),
+        (Some(first), ..) => Anonymizer::Sequential { first },
+        (None, None, None) if std::env::var_os(KEY_VAR).is_none() => {
+            exit_usage(&Cli::command().error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                format!(
+                    "one of --newid, --key-file, or --id-map is required unless {KEY_VAR} is set"
+                ),
+            ))
+        }
+        (None, path, None) => Anonymizer::Hmac {
+            key: hmac_key(path.as_deref())?,
+        },
+    };
+    let new_id = anonymizer.anonymize(0, &student.id);
+
+    let mut targets = identifying_text(&student);
+    targets.extend(args.text.iter().map(String::as_str));
+    scrape_sfu_transcript::redact::redact(&mut document, &targets, &new_id)
+        .map_err(|err| failure(&err.into()))?;
+
+    // The records must survive redaction unchanged apart from the ID, which
+    // also confirms that the real ID is gone from where the parser finds it.
+    let redacted = Transcript::from_document_with(&document, &options).ok();
+    if !redacted.is_some_and(|redacted| keeps_records(&student, &redacted, &new_id)) {
+        let message = format!(
+            "{}: redacting changed the extracted records; check the --text values",
+            args.input.display()
+        );
+        return Err(with_status(ExitStatus::Validation, Error::other(message)));
+    }
+    document
+        .save(&args.output)
+        .map_err(|err| output_error(Error::other(format!("{}: {err}", args.output.display()))))?;
+    anonymizer.finish().map_err(output_error)
+}
+
+fn diff(args: &DiffArgs) -> Result<(), Error> {
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    let load = |path: &Path| {
+        Transcript::from_path_with(path, &options).map_err(|err| transcript_failure(path, &err))
+    };
+    let before = load(&args.before)?;
+    let after = load(&args.after)?;
+    if before.id != after.id {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The transcripts belong to different students",
+        ));
+    }
+    let changes = scrape_sfu_transcript::diff::diff(&before, &after);
+
+    let mut out = std::io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &changes)?;
+        return writeln!(out);
+    }
+    if changes.is_empty() {
+        return writeln!(out, "No changes");
+    }
+    for term in &changes.new_terms {
+        writeln!(out, "New term: {term}")?;
+    }
+    for term in &changes.removed_terms {
+        writeln!(out, "Removed term: {term}")?;
+    }
+    if !changes.courses.is_empty() {
+        writeln!(out)?;
+        let header: Vec<String> = ["Change", "Term", "Course", "Before", "After"]
+            .map(str::to_string)
+            .to_vec();
+        let grade = |course: Option<&Course>| course.map_or("-", |c| c.grade.as_str()).to_string();
+        let rows: Vec<Vec<String>> = changes
+            .courses
+            .iter()
+            .map(|change| {
+                let term = match (&change.year, &change.term) {
+                    (Some(year), Some(term)) => format!("{year} {term}"),
+                    _ => "Transfer".to_string(),
+                };
+                vec![
+                    change.kind.as_str().to_string(),
+                    term,
+                    format!("{} {}", change.subject, change.id),
+                    grade(change.before.as_ref()),
+                    grade(change.after.as_ref()),
+                ]
+            })
+            .collect();
+        write_table(&mut out, &header, &rows, 1)?;
+    }
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+struct PrereqsArgs {
+    /// Path to a transcript PDF, a directory of transcripts, or a glob
+    /// pattern
+    input: PathBuf,
+
+    #[command(flatten)]
+    walk: WalkArgs,
+
+    /// JSON file listing the prerequisites of each course, and optionally
+    /// the minimum grade they must be passed with
+    #[arg(long)]
+    rules: PathBuf,
+
+    /// Print the courses as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Record anomalies as warnings instead of skipping unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+}
+
+// Transcripts are labelled by their file rather than the student ID, as
+// with the other reports.
+fn prereqs(args: &PrereqsArgs) -> Result<(), Error> {
+    let rules = PrerequisiteRules::from_json(File::open(&args.rules)?).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {err}", args.rules.display()),
+        )
+    })?;
+    let sources = collect_sources(&args.input, args.input.is_dir(), &args.walk, TRANSCRIPTS)?;
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    let reports: Vec<_> = sources
+        .iter()
+        .filter_map(
+            |source| match Transcript::from_path_with(source, &options) {
+                Ok(student) => Some((source, rules.check(&student))),
+                Err(err) => {
+                    eprintln!("Error: {}: {err}", source.display());
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let mut out = std::io::stdout().lock();
+    if args.json {
+        #[derive(serde::Serialize)]
+        struct Report<'a> {
+            file: &'a Path,
+            unmet: &'a [UnmetPrerequisites],
+        }
+        let reports: Vec<Report> = reports
+            .iter()
+            .map(|(file, unmet)| Report { file, unmet })
+            .collect();
+        serde_json::to_writer_pretty(&mut out, &reports)?;
+        return writeln!(out);
+    }
+    let mut flagged = 0;
+    for (source, unmet) in &reports {
+        if unmet.is_empty() {
+            writeln!(out, "{}: OK", source.display())?;
+            continue;
+        }
+        flagged += 1;
+        let noun = if unmet.len() == 1 {
+            "course"
+        } else {
+            "courses"
+        };
+        writeln!(out, "{}: {} {noun}", source.display(), unmet.len())?;
+        for course in unmet {
+            let missing: Vec<String> = course
+                .missing
+                .iter()
+                .map(|group| group.join(" or "))
+                .collect();
+            writeln!(
+                out,
+                "  {} {} {} {}: missing {}",
+                course.year,
+                course.term,
+                course.subject,
+                course.id,
+                missing.join(", ")
+            )?;
+        }
+    }
+    writeln!(
+        out,
+        "{flagged} of {} transcripts had courses taken without their prerequisites",
+        reports.len()
+    )
+}
+
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// Path to a transcript PDF, a directory of transcripts, or a glob
+    /// pattern
+    input: PathBuf,
+
+    #[command(flatten)]
+    walk: WalkArgs,
+
+    /// TOML or JSON file defining the requirements of the degree, read as
+    /// TOML when its extension is .toml
+    #[arg(long)]
+    requirements: PathBuf,
+
+    /// CSV file of the SFU equivalents of transfer credits, which then count
+    /// toward requirements as those courses
+    #[arg(long)]
+    equivalencies: Option<PathBuf>,
+
+    /// Print the audits as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Record anomalies as warnings instead of skipping unusual transcripts
+    #[arg(long)]
+    lenient: bool,
+}
+
+// Reads a definition, such as degree requirements or risk rules, as TOML
+// when the file's extension is .toml and as JSON otherwise.
+fn load_definition<T>(
+    path: &Path,
+    from_toml: impl FnOnce(&str) -> Result<T, toml::de::Error>,
+    from_json: impl FnOnce(File) -> serde_json::Result<T>,
+) -> Result<T, Error> {
+    let definition = if has_extension(path, &["toml"]) {
+        from_toml(&std::fs::read_to_string(path)?).map_err(|err| err.to_string())
+    } else {
+        from_json(File::open(path)?).map_err(|err| err.to_string())
+    };
+    definition
+        .map_err(|err| Error::new(ErrorKind::InvalidData, format!("{}: {err}", path.display())))
+}
+
+fn audit(args: &AuditArgs) -> Result<(), Error> {
+    let requirements = load_definition(
+        &args.requirements,
+        DegreeRequirements::from_toml,
+        DegreeRequirements::from_json,
+    )?;
+    let equivalencies = match &args.equivalencies {
+        Some(path) => Some(EquivalencyTable::from_csv(File::open(path)?).map_err(Error::other)?),
+        None => None,
+    };
+    let sources = collect_sources(&args.input, args.input.is_dir(), &args.walk, TRANSCRIPTS)?;
+    let options = ParseOptions {
+        lenient: args.lenient,
+        ..ParseOptions::default()
+    };
+    let audits: Vec<_> = sources
+        .iter()
+        .filter_map(
+            |source| match Transcript::from_path_with(source, &options) {
+                Ok(mut student) => {
+                    if let Some(table) = &equivalencies {
+                        table.apply(&mut student);
+                    }
+                    Some((source, requirements.audit(&student)))
+                }
+                Err(err) => {
+                    eprintln!("Error: {}: {err}", source.display());
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let mut out = std::io::stdout().lock();
+    if args.json {
+        #[derive(serde::Serialize)]
+        struct Audit<'a> {
+            file: &'a Path,
+            requirements: &'a [AuditedRequirement],
+        }
+        let audits: Vec<Audit> = audits
+            .iter()
+            .map(|(file, requirements)| Audit { file, requirements })
+            .collect();
+        serde_json::to_writer_pretty(&mut out, &audits)?;
+        return writeln!(out);
+    }
+    let header: Vec<String> = ["Requirement", "Status", "Units", "Missing"]
+        .map(str::to_string)
+        .to_vec();
+    let mut complete = 0;
+    for (source, audited) in &audits {
+        let satisfied = audited
+            .iter()
+            .filter(|a| a.requirement.status == RequirementStatus::Satisfied)
+            .count();
+        complete += usize::from(satisfied == audited.len());
+        writeln!(
+            out,
+            "{}: {satisfied} of {} requirements satisfied",
+            source.display(),
+            audited.len()
+        )?;
+        let rows: Vec<Vec<String>> = audited
+            .iter()
+            .map(|a| {
+                let requirement = &a.requirement;
+                let units = match (requirement.units_used, requirement.units_required) {
+                    (Some(used), Some(required)) => format!("{used} of {required}"),
+                    _ => String::new(),
+                };
+                vec![
+                    requirement.name.clone(),
+                    requirement.status.as_str().to_string(),
+                    units,
+                    a.missing.join(", "),
+                ]
+            })
+            .collect();
+        write_table(&mut out, &header, &rows, header.len())?;
+        writeln!(out)?;
+    }
+    writeln!(
+        out,
+        "{complete} of {} transcripts satisfied every requirement",
+        audits.len()
+    )
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// Path to the transcript PDF
+    pdf: PathBuf,
+
+    /// Print the chunks after footer removal, indexed as the parser sees them
+    #[arg(long)]
+    combined: bool,
+
+    /// Print the rows of each page reconstructed from the text positions,
+    /// with one cell per column
+    #[arg(long, conflicts_with = "combined")]
+    columns: bool,
+}
+
+fn inspect(args: &InspectArgs) -> Result<(), Error> {
+    let document = load_document(&args.pdf).map_err(Error::other)?;
+    let mut out = std::io::stdout().lock();
+    let layout = Transcript::layout(&document).map_err(Error::other)?;
+    writeln!(out, "Layout: {layout:?}")?;
+    if args.columns {
+        let pages = Transcript::page_rows(&document).map_err(Error::other)?;
+        for (page_index, rows) in pages.iter().enumerate() {
+            writeln!(out, "Page {}", page_index + 1)?;
+            for row in rows {
+                writeln!(out, "{}", row.join(" | "))?;
+            }
+        }
+    } else if args.combined {
+        let chunks = Transcript::chunks(&document).map_err(Error::other)?;
+        for (index, chunk) in chunks.iter().enumerate() {
+            chunk.write_tree(&mut out, &index.to_string())?;
+        }
+    } else {
+        let pages = Transcript::page_chunks(&document).map_err(Error::other)?;
+        for (page_index, page) in pages.iter().enumerate() {
+            writeln!(out, "Page {}", page_index + 1)?;
+            for (index, chunk) in page.iter().enumerate() {
+                chunk.write_tree(&mut out, &index.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// One row per course or transfer credit
+    Csv,
+    /// CSV with tab-separated fields
+    Tsv,
+    /// The full nested structure of every student
+    Json,
+    /// One JSON object per line, written as each transcript finishes
+    Ndjson,
+    /// The same objects as JSON in compact binary msgpack, one after
+    /// another
+    Msgpack,
+    /// A readable report per student with a table of courses for every term
+    Markdown,
+    /// A standalone web page with a styled report per student, including
+    /// GPA charts and sortable tables
+    Html,
+    /// Aligned tables of every student's courses, for a quick look in a
+    /// terminal
+    Table,
+    /// A PESC College Transcript XML document per student, for exchange
+    /// with other registrars
+    Pesc,
+    /// An X12 interchange of TS130 transcript transactions, one per student,
+    /// for exchange through SPEEDE
+    Ts130,
+    /// Length-delimited protobuf messages of every student, as described by
+    /// proto/transcript.proto
+    #[cfg(feature = "proto")]
+    Proto,
+    /// CREATE TABLE and INSERT statements of the long layout, portable
+    /// across relational databases
+    Sql,
+    /// Normalized tables in a sqlite database file (requires --output)
+    Sqlite,
+    /// An Excel workbook with courses, transfers, and summary sheets (requires --output)
+    Xlsx,
+    /// Typed long-format records in an Arrow IPC (Feather) file (requires --output)
+    #[cfg(feature = "arrow")]
+    Arrow,
+    /// Typed long-format records in an Apache Parquet file (requires --output)
+    #[cfg(feature = "parquet")]
+    Parquet,
+    /// Typed long-format records in a duckdb database file (requires --output)
+    #[cfg(feature = "duckdb")]
+    Duckdb,
+    /// Typed long-format records in an Avro container file that carries its
+    /// schema (requires --output)
+    #[cfg(feature = "avro")]
+    Avro,
+    /// The text of every page, one text block (a row of the transcript) per
+    /// line prefixed with its page and block indices, for debugging layouts
+    /// or grepping
+    Text,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum DocType {
+    /// SIMS transcripts of courses and grades
+    Transcript,
+    /// Academic advisement (degree progress) reports of requirements
+    Progress,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Anonymization {
+    /// Number students consecutively starting from --newid
+    Sequential,
+    /// Derive stable IDs from the real student IDs with HMAC-SHA256
+    Hmac,
+}
+
+fn create_anonymizer(args: &Cli) -> Result<Anonymizer, Error> {
+    let anonymizer = match (&args.id_map, args.anonymize) {
+        (Some(path), _) => Anonymizer::Mapped(IdMap::load(path, args.newid.unwrap_or(1))?),
+        (None, Anonymization::Sequential) => Anonymizer::Sequential {
+            first: args.newid.unwrap_or_default(),
+        },
+        (None, Anonymization::Hmac) => Anonymizer::Hmac {
+            key: hmac_key(args.key_file.as_deref())?,
+        },
+    };
+    #[cfg(feature = "vault")]
+    if let (Some(path), Some(recipient)) = (&args.vault, &args.vault_recipient) {
+        return Ok(Anonymizer::Vaulted {
+            inner: Box::new(anonymizer),
+            vault: Vault::load(path)?,
+            recipient: recipient.clone(),
+        });
+    }
+    Ok(anonymizer)
+}
+
+#[cfg(feature = "vault")]
+fn parse_recipient(s: &str) -> Result<Recipient, String> {
+    s.parse().map_err(|err: &str| err.to_string())
+}
+
+/// The environment variable holding the secret HMAC key itself.
+const KEY_VAR: &str = "SFU_TRANSCRIPT_KEY";
+
+/// The environment variable holding the path of the HMAC key file.
+const KEY_FILE_VAR: &str = "SFU_TRANSCRIPT_KEY_FILE";
+
+// Reads the HMAC key from its file, or from the environment if no file is
+// given. Its fingerprint is reported so that research assistants anonymizing
+// on separate machines can confirm that they derive the same IDs.
+fn hmac_key(path: Option<&Path>) -> Result<Vec<u8>, Error> {
+    let key = match path {
+        Some(path) => read_hmac_key(path)?,
+        None => match std::env::var_os(KEY_VAR) {
+            Some(key) if !key.is_empty() => key.into_encoded_bytes(),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("HMAC anonymization needs a key from --key-file or {KEY_VAR}"),
+                ))
+            }
+        },
+    };
+    eprintln!(
+        "Using the HMAC key with fingerprint {}",
+        key_fingerprint(&key)
+    );
+    Ok(key)
+}
+
+fn read_hmac_key(path: &Path) -> Result<Vec<u8>, Error> {
+    let key = std::fs::read(path)?;
+    if key.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The HMAC key file is empty",
+        ));
+    }
+    Ok(key)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Layout {
+    /// One row per course or transfer credit
+    Long,
+    /// One row per student with a grade column per course
+    Wide,
+    /// One row per course across the batch, with its enrollments, grade
+    /// histogram, and withdrawal rate
+    Course,
+    /// One row per student and term with the term and cumulative GPA, for
+    /// plotting GPA trajectories
+    Gpa,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SortOrder {
+    /// Chronologically, with transfer credits first
+    Term,
+    /// By subject and course number
+    Course,
+    /// From the highest grade to the lowest
+    Grade,
+}
+
+impl From<SortOrder> for RowOrder {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Term => RowOrder::Term,
+            SortOrder::Course => RowOrder::Course,
+            SortOrder::Grade => RowOrder::Grade,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Coarsening {
+    /// The academic year starting in the Fall
+    AcademicYear,
+    /// The calendar year
+    Year,
+}
+
+impl From<Coarsening> for TermGranularity {
+    fn from(coarsening: Coarsening) -> Self {
+        match coarsening {
+            Coarsening::AcademicYear => TermGranularity::AcademicYear,
+            Coarsening::Year => TermGranularity::Year,
+        }
+    }
+}
+
+fn text_output(path: Option<&Path>) -> Result<Box<dyn Write>, Error> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout()),
+    })
+}
+
+#[derive(Args, Debug)]
+// The group is not marked as required so that --emit-schema works without an
+// input, so its presence is checked in main instead.
+#[group(multiple = false)]
+struct InputSource {
+    /// Path to input file
+    #[arg(short, long)]
+    pdf: Option<PathBuf>,
+
+    /// Path to a directory of input files
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+
+    /// Path to an input file, a directory of input files, or a glob pattern
+    /// such as 'transcripts/2024/**/*.pdf'
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+}
+
+/// The extensions of PDF inputs.
+const PDF: &[&str] = &["pdf"];
+
+/// The extensions of transcript inputs, which may also be PESC XML.
+const TRANSCRIPTS: &[&str] = &["pdf", "xml"];
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// Options controlling how input directories are traversed.
+#[derive(Args, Debug, Default)]
+struct WalkArgs {
+    /// Also process PDFs in the subdirectories of an input directory
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// How many levels of subdirectories to descend into when recursing
+    #[arg(long, requires = "recursive")]
+    max_depth: Option<usize>,
+
+    /// Follow symbolic links to files and directories
+    #[arg(long)]
+    follow_symlinks: bool,
+}
+
+fn collect_directory(
+    path: &Path,
+    walk: &WalkArgs,
+    extensions: &[&str],
+) -> Result<Vec<PathBuf>, Error> {
+    let mut sources = Vec::new();
+    let mut visited = HashSet::new();
+    walk_directory(path, walk, extensions, 0, &mut visited, &mut sources)?;
+    Ok(sources)
+}
+
+fn walk_directory(
+    path: &Path,
+    walk: &WalkArgs,
+    extensions: &[&str],
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    sources: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    // Followed links can lead back to a directory that was already walked.
+    if !visited.insert(path.canonicalize()?) {
+        return Ok(());
+    }
+    // Only process entries that are readable
+    for entry in std::fs::read_dir(path)?.filter_map(std::result::Result::ok) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let entry_path = entry.path();
+        let (is_file, is_dir) = if file_type.is_symlink() {
+            if !walk.follow_symlinks {
+                continue;
+            }
+            (entry_path.is_file(), entry_path.is_dir())
+        } else {
+            (file_type.is_file(), file_type.is_dir())
+        };
+        // Restrict to the kinds of input being read
+        if is_file && has_extension(&entry_path, extensions) {
+            sources.push(entry_path);
+        } else if is_dir && walk.recursive && walk.max_depth.is_none_or(|max| depth < max) {
+            // Unreadable subdirectories are skipped like unreadable files.
+            let _ = walk_directory(&entry_path, walk, extensions, depth + 1, visited, sources);
+        }
+    }
+    Ok(())
+}
+
+fn is_glob(path: &Path) -> bool {
+    !path.exists() && path.to_string_lossy().contains(['*', '?', '['])
+}
+
+// Shells on Windows do not expand globs, so patterns are expanded here.
+fn collect_glob(pattern: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, Error> {
+    let paths = glob::glob(&pattern.to_string_lossy())
+        .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+    Ok(paths
+        // Only process files that are readable
+        .filter_map(std::result::Result::ok)
+        .filter(|path| path.is_file() && has_extension(path, extensions))
+        .collect())
+}
+
+fn collect_sources(
+    path: &Path,
+    is_dir: bool,
+    walk: &WalkArgs,
+    extensions: &[&str],
+) -> Result<Vec<PathBuf>, Error> {
+    if is_dir {
+        collect_directory(path, walk, extensions)
+    } else if is_glob(path) {
+        collect_glob(path, extensions)
+    } else {
+        Ok(vec![path.to_path_buf()])
+    }
+}
+
+fn parse_pattern(value: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(value).map_err(|err| err.to_string())
+}
+
+// Profiles extend the built-in vocabulary, so that they only need to list
+// what is new.
+fn parse_vocabulary(path: &str) -> Result<Vocabulary, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let profile =
+        Vocabulary::from_json(std::io::BufReader::new(file)).map_err(|err| err.to_string())?;
+    let mut vocabulary = Vocabulary::default();
+    vocabulary.extend(profile);
+    Ok(vocabulary)
+}
+
+/// Accepts a year and term such as `2019 Fall`, as the key ordering it among
+/// other terms.
+fn parse_term(value: &str) -> Result<(u32, usize), String> {
+    let mut pieces = value.split_ascii_whitespace();
+    let (Some(year), Some(term), None) = (pieces.next(), pieces.next(), pieces.next()) else {
+        return Err("expected a year and term, such as \"2019 Fall\"".to_string());
+    };
+    let term = TERMS
+        .iter()
+        .find(|t| t.eq_ignore_ascii_case(term))
+        .ok_or_else(|| format!("the term must be one of {}", TERMS.join(", ")))?;
+    year.parse::<u32>()
+        .map_err(|_| format!("`{year}` is not a year"))?;
+    Ok(term_key(year, term))
+}
+
+fn parse_column(value: &str) -> Result<usize, String> {
+    long_column(value).ok_or_else(|| {
+        let keys: Vec<&str> = LONG_COLUMNS.iter().map(|column| column.key).collect();
+        format!("the column must be one of {}", keys.join(", "))
+    })
+}
+
+fn csv_options(args: &Cli) -> CsvOptions {
+    let default = if args.format == Format::Tsv {
+        b'\t'
+    } else {
+        b','
+    };
+    CsvOptions {
+        delimiter: args.delimiter.unwrap_or(default),
+        header: !args.no_header,
+    }
+}
+
+/// Accepts a single ASCII character, or `\t` or `tab` for a tab.
+fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ => match value.as_bytes() {
+            [byte] if byte.is_ascii() => Ok(*byte),
+            _ => Err("the delimiter must be a single ASCII character".to_string()),
+        },
+    }
+}
+
+// The database URL given as the output, if rows are loaded into PostgreSQL.
+fn database_url(args: &Cli) -> Option<&str> {
+    args.output
+        .as_deref()
+        .and_then(Path::to_str)
+        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
+}
+
+/// Writes every student to the main output and to the term load table.
+struct WithTermTable {
+    exporter: Box<dyn Exporter>,
+    loads: TermLoadCsvExporter<LeakScanner<BufWriter<File>>>,
+}
+
+impl Exporter for WithTermTable {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> std::io::Result<()> {
+        self.exporter.write_student(new_id, student)?;
+        self.loads.write_student(new_id, student)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.exporter.finish()?;
+        self.loads.finish()
+    }
+}
+
+fn with_term_table(
+    args: &Cli,
+    exporter: Box<dyn Exporter>,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<Box<dyn Exporter>, Error> {
+    let Some(path) = &args.term_table else {
+        return Ok(exporter);
+    };
+    // Renamed into place by `run` along with the output.
+    let writer = BufWriter::new(File::create(staging_path(path))?);
+    let writer = LeakScanner::new(writer, Rc::clone(leaks));
+    let loads = TermLoadCsvExporter::new(writer, csv_options(args));
+    Ok(Box::new(WithTermTable { exporter, loads }))
+}
+
+/// Writes every student to an exporter of a binary format, while scanning
+/// the records written as JSON, since compressed formats such as Excel
+/// workbooks can hide text from a scan of the file.
+struct WithRecordScan {
+    exporter: Box<dyn Exporter>,
+    scan: JsonExporter<LeakScanner<std::io::Sink>>,
+}
+
+impl Exporter for WithRecordScan {
+    fn write_student(&mut self, new_id: &str, student: &StudentInfo) -> std::io::Result<()> {
+        self.scan.write_student(new_id, student)?;
+        self.exporter.write_student(new_id, student)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.scan.finish()?;
+        self.exporter.finish()
+    }
+}
+
+fn with_record_scan(
+    args: &Cli,
+    exporter: Box<dyn Exporter>,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Box<dyn Exporter> {
+    if is_text_format(args.format) || database_url(args).is_some() {
+        return exporter;
+    }
+    let scan = JsonExporter::new(LeakScanner::new(std::io::sink(), Rc::clone(leaks)));
+    Box::new(WithRecordScan { exporter, scan })
+}
+
+// Whether the format is written through a text writer, which is scanned as
+// it is written, rather than by a library writing its own file.
+fn is_text_format(format: Format) -> bool {
+    #[cfg(feature = "proto")]
+    if format == Format::Proto {
+        return true;
+    }
+    matches!(
+        format,
+        Format::Csv
+            | Format::Tsv
+            | Format::Json
+            | Format::Ndjson
+            | Format::Msgpack
+            | Format::Markdown
+            | Format::Html
+            | Format::Table
+            | Format::Sql
+            | Format::Pesc
+            | Format::Ts130
+            | Format::Text
+    )
+}
+
+fn create_exporter(
+    args: &Cli,
+    output: Option<&Path>,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<Box<dyn Exporter>, Error> {
+    if let Some(url) = database_url(args) {
+        #[cfg(feature = "postgres")]
+        return Ok(Box::new(
+            PostgresExporter::connect(url, &args.pg_table)?.with_leak_check(Rc::clone(leaks)),
+        ));
+        #[cfg(not(feature = "postgres"))]
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("Loading into {url} needs the postgres feature"),
+        ));
+    }
+    // Text is checked as it is written, which also covers standard output.
+    let text_output = |output: Option<&Path>| -> Result<Box<dyn Write>, Error> {
+        let writer: Box<dyn Write> = match output {
+            Some(path) if args.append => Box::new(BufWriter::new(
+                OpenOptions::new().append(true).create(true).open(path)?,
+            )),
+            _ => text_output(output)?,
+        };
+        Ok(Box::new(LeakScanner::new(writer, Rc::clone(leaks))))
+    };
+    Ok(match args.format {
+        Format::Csv | Format::Tsv if args.layout == Layout::Wide => Box::new(WideCsvExporter::new(
+            text_output(output)?,
+            csv_options(args),
+        )),
+        Format::Csv | Format::Tsv if args.layout == Layout::Course => Box::new(
+            CourseCsvExporter::new(text_output(output)?, csv_options(args)),
+        ),
+        Format::Csv | Format::Tsv if args.layout == Layout::Gpa => {
+            Box::new(GpaCsvExporter::new(text_output(output)?, csv_options(args)))
+        }
+        Format::Csv | Format::Tsv => {
+            let mut options = csv_options(args);
+            // Appended rows continue under the header already written.
+            if args.append && output.is_some_and(|path| path.metadata().is_ok_and(|m| m.len() > 0))
+            {
+                options.header = false;
+            }
+            let exporter =
+                LongCsvExporter::with_columns(text_output(output)?, options, long_columns(args));
+            match args.sort {
+                Some(order) => Box::new(exporter.sorted(order.into())),
+                None => Box::new(exporter),
+            }
+        }
+        Format::Json => Box::new(JsonExporter::new(text_output(output)?)),
+        Format::Ndjson => Box::new(NdjsonExporter::new(text_output(output)?)),
+        Format::Msgpack => Box::new(MessagePackExporter::new(text_output(output)?)),
+        Format::Markdown => Box::new(MarkdownExporter::new(text_output(output)?)),
+        Format::Html => Box::new(HtmlExporter::new(text_output(output)?)),
+        Format::Table => Box::new(TableExporter::new(text_output(output)?)),
+        Format::Sql => Box::new(SqlExporter::new(text_output(output)?)),
+        Format::Pesc => Box::new(PescExporter::new(text_output(output)?)),
+        Format::Ts130 => Box::new(EdiExporter::new(text_output(output)?)),
+        #[cfg(feature = "proto")]
+        Format::Proto => Box::new(ProtoExporter::new(text_output(output)?)),
+        Format::Sqlite if args.append => {
+            Box::new(SqliteExporter::new(output.unwrap())?.appending())
+        }
+        Format::Sqlite => Box::new(SqliteExporter::new(output.unwrap())?),
+        Format::Xlsx => Box::new(XlsxExporter::new(output.unwrap())?),
+        #[cfg(feature = "arrow")]
+        Format::Arrow => Box::new(ArrowIpcExporter::new(output.unwrap())?),
+        #[cfg(feature = "parquet")]
+        Format::Parquet => Box::new(ParquetExporter::new(output.unwrap())?),
+        #[cfg(feature = "duckdb")]
+        Format::Duckdb => Box::new(DuckDbExporter::new(output.unwrap())?),
+        #[cfg(feature = "avro")]
+        Format::Avro => Box::new(AvroExporter::new(output.unwrap())?),
+        Format::Text => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Text dumps are written without parsing the transcripts",
+            ))
+        }
+    })
+}
+
+// The columns of the long layout chosen on the command line.
+fn long_columns(args: &Cli) -> Vec<usize> {
+    if args.printed_gpas {
+        (0..LONG_COLUMNS.len())
+            .filter(|&i| !LONG_COLUMNS[i].optional || LONG_COLUMNS[i].key.starts_with("printed_"))
+            .collect()
+    } else if args.columns.is_empty() {
+        default_long_columns()
+    } else {
+        args.columns.clone()
+    }
+}
+
+// The keys of the rows already in the output being appended to, if any.
+fn present_rows(args: &Cli, output: Option<&Path>) -> Result<Option<BTreeSet<RowKey>>, Error> {
+    let Some(path) = output.filter(|_| args.append) else {
+        return Ok(None);
+    };
+    let keys = match args.format {
+        Format::Sqlite => read_sqlite_row_keys(path)?,
+        _ => read_long_row_keys(File::open(path)?, csv_options(args), &long_columns(args))?,
+    };
+    Ok(Some(keys))
 }
-impl Chunk {
-    fn get_contained(&self) -> Option<&[Self]> {
-        match self {
-            Self::String(_) => None,
-            Self::Chunks(v) => Some(v.as_slice()),
+
+fn create_progress_exporter(
+    args: &Cli,
+    output: Option<&Path>,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<Box<dyn ProgressExporter>, Error> {
+    let text_output = |output: Option<&Path>| -> Result<Box<dyn Write>, Error> {
+        Ok(Box::new(LeakScanner::new(
+            text_output(output)?,
+            Rc::clone(leaks),
+        )))
+    };
+    Ok(match args.format {
+        Format::Csv | Format::Tsv if args.layout != Layout::Long => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Only the long layout is supported for degree progress reports",
+            ))
         }
+        Format::Csv | Format::Tsv => Box::new(ProgressCsvExporter::new(
+            text_output(output)?,
+            csv_options(args),
+        )),
+        Format::Json => Box::new(ProgressJsonExporter::new(text_output(output)?)),
+        Format::Ndjson => Box::new(ProgressNdjsonExporter::new(text_output(output)?)),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Degree progress reports can only be written as CSV, TSV, JSON, or NDJSON",
+            ))
+        }
+    })
+}
+
+fn parse_options(args: &Cli) -> ParseOptions {
+    ParseOptions {
+        lenient: args.lenient,
+        strict: args.strict,
+        page_order: args.page_order.clone(),
+        vocabulary: args.vocabulary.clone().unwrap_or_default(),
+        gpa_tolerance: args.gpa_tolerance,
     }
+}
+
+/// Why an input file could not be exported, and the exit status it earns.
+#[derive(Clone, Debug)]
+struct FileError {
+    status: ExitStatus,
+    message: String,
+}
 
-    fn get_string(&self) -> Option<&str> {
-        match self {
-            Self::String(s) => Some(s),
-            Self::Chunks(_) => None,
+impl From<TranscriptError> for FileError {
+    fn from(err: TranscriptError) -> Self {
+        Self {
+            status: err.category().into(),
+            message: err.to_string(),
         }
     }
+}
 
-    const fn is_chunks(&self) -> bool {
-        matches!(self, Self::Chunks(_))
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
     }
+}
 
-    // Simplification recursively transforms Chunks objects containing one
-    // element into the single element they contain for readability. Column
-    // structure is preserved because that can be useful for ensuring
-    // consistency when extracting the data later.
-    fn simplify(self) -> Self {
-        match self {
-            Self::String(s) => Self::String(s.trim().to_string()),
-            Self::Chunks(v) => {
-                let fresh: Vec<Self> = v.into_iter().map(Self::simplify).collect();
-                if fresh.len() == 1 {
-                    fresh.into_iter().next().unwrap()
-                } else {
-                    Self::Chunks(fresh)
-                }
-            }
+/// The anonymized ID, row count, and warnings of a file that passed, or the
+/// error of one that failed.
+type FileOutcome<'a> = Result<(String, usize, &'a [String]), FileError>;
+
+// Records the outcome of one input file, if a manifest is being written.
+fn record_file(manifest: Option<&mut Manifest>, source: &Path, outcome: FileOutcome) {
+    let Some(manifest) = manifest else {
+        return;
+    };
+    let entry = ManifestEntry {
+        path: source.display().to_string(),
+        sha256: sha256_file(source).ok(),
+        anonymized_id: None,
+        rows: 0,
+        warnings: Vec::new(),
+        status: EntryStatus::Failed,
+        error: None,
+    };
+    manifest.push(match outcome {
+        Ok((new_id, rows, warnings)) => ManifestEntry {
+            anonymized_id: Some(new_id),
+            rows,
+            warnings: warnings.to_vec(),
+            status: EntryStatus::Passed,
+            ..entry
+        },
+        Err(error) => ManifestEntry {
+            error: Some(error.message),
+            ..entry
+        },
+    });
+}
+
+fn write_manifest(path: Option<&Path>, manifest: Option<&Manifest>) -> Result<(), Error> {
+    match (path, manifest) {
+        (Some(path), Some(manifest)) => {
+            write_staged(path, |writer| manifest.write_json(writer)).map_err(output_error)
         }
+        _ => Ok(()),
     }
 }
 
-fn objects_to_chunk(encoding: Option<&str>, operands: &[Object]) -> Chunk {
-    let mut chunks = Vec::with_capacity(operands.len());
-    for operand in operands {
-        match operand {
-            Object::String(bytes, _) => {
-                chunks.push(Chunk::String(Document::decode_text(encoding, bytes)));
-            }
-            Object::Array(arr) => {
-                chunks.push(objects_to_chunk(encoding, arr));
-            }
-            _ => {}
-        }
-    }
-    Chunk::Chunks(chunks)
-}
-
-fn block_to_chunk(
-    operations: &[Operation],
-    encodings: &BTreeMap<Vec<u8>, &str>,
-) -> LopdfResult<Chunk> {
-    let mut current_encoding = None;
-    let mut chunks = Vec::new();
-    for operation in operations {
-        match operation.operator.as_ref() {
-            "Tf" => {
-                let current_font = operation
-                    .operands
-                    .first()
-                    .ok_or_else(|| LopdfError::Syntax("missing font operand".to_string()))?
-                    .as_name()?;
-                current_encoding = encodings.get(current_font).copied();
+fn export_progress(
+    args: &Cli,
+    sources: &[PathBuf],
+    output: Option<&Path>,
+) -> Result<ExitStatus, Error> {
+    let mut anonymizer = create_anonymizer(args)?;
+    let leaks = Rc::new(RefCell::new(LeakCheck::new()));
+    let mut exporter = create_progress_exporter(args, output, &leaks).map_err(output_error)?;
+    let options = parse_options(args);
+    let mut manifest = args.manifest.as_ref().map(|_| Manifest::default());
+    let mut status = ExitStatus::Success;
+    for (count, source) in sources.iter().enumerate() {
+        match ProgressReport::from_path_with(source, &options) {
+            Ok(report) => {
+                for warning in &report.warnings {
+                    eprintln!("Warning: {}: {warning}", source.display());
+                }
+                let new_id = anonymizer.anonymize(count, &report.id);
+                let label = source.display().to_string();
+                leaks
+                    .borrow_mut()
+                    .add_identity(&label, &report.id, &Identity::default());
+                exporter
+                    .write_report(&new_id, &report)
+                    .map_err(output_error)?;
+                let rows = report.requirements.len();
+                record_file(
+                    manifest.as_mut(),
+                    source,
+                    Ok((new_id, rows, &report.warnings)),
+                );
             }
-            "Tj" | "TJ" => {
-                chunks.push(objects_to_chunk(current_encoding, &operation.operands));
+            Err(err) => {
+                eprintln!("Error: {}: {err}", source.display());
+                let err = FileError::from(err);
+                status = status.and(err.status);
+                record_file(manifest.as_mut(), source, Err(err));
             }
-            _ => {}
         }
     }
-    Ok(Chunk::Chunks(chunks))
+    exporter.finish().map_err(output_error)?;
+    check_leaks(args, output, &mut leaks.borrow_mut())?;
+    write_manifest(args.manifest.as_deref(), manifest.as_ref())?;
+    anonymizer.finish().map_err(output_error)?;
+    Ok(status)
+}
+
+// Parses a transcript unless the cache holds the result for identical
+// bytes, counting the transcripts reused. Failing to store a new entry only
+// costs a re-parse on the next run, so it is reported as a warning.
+fn parse_cached(
+    cache: &ParseCache,
+    source: &Path,
+    options: &ParseOptions,
+    hits: &Cell<usize>,
+) -> Result<StudentInfo, FileError> {
+    let bytes = map_file(source).map_err(TranscriptError::from)?;
+    let digest = sha256_bytes(&bytes);
+    if let Some(student) = cache.get(&digest) {
+        hits.set(hits.get() + 1);
+        return Ok(student);
+    }
+    let student = Transcript::from_bytes_with(&bytes, options)?;
+    if let Err(err) = cache.insert(&digest, &student) {
+        eprintln!(
+            "Warning: {}: cannot cache the transcript: {err}",
+            source.display()
+        );
+    }
+    Ok(student)
+}
+
+/// The input files a student was extracted from, and the student or the
+/// reason extraction failed.
+type Extracted<'a> = (Vec<&'a Path>, Result<StudentInfo, FileError>);
+
+// The input files of a student as they are named in messages.
+fn files_label(files: &[&Path]) -> String {
+    files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-fn group_text_blocks(content: &Content) -> Vec<&[Operation]> {
-    content
-        .operations
-        .as_slice()
-        .split(|o| matches!(o.operator.as_ref(), "ET"))
+// Parses every source up front and merges the snapshots of each student,
+// keeping students in the order they were first seen. Files that fail to
+// parse follow on their own, so merged students keep consecutive IDs.
+fn merge_snapshots(
+    sources: &[PathBuf],
+    parse: impl Fn(&Path) -> Result<StudentInfo, FileError>,
+) -> Vec<Extracted<'_>> {
+    let mut groups: Vec<(Vec<&Path>, Vec<StudentInfo>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut failures = Vec::new();
+    for source in sources {
+        match parse(source) {
+            Ok(student) => {
+                let position = *positions.entry(student.id.clone()).or_insert_with(|| {
+                    groups.push((Vec::new(), Vec::new()));
+                    groups.len() - 1
+                });
+                groups[position].0.push(source);
+                groups[position].1.push(student);
+            }
+            Err(err) => failures.push((vec![source.as_path()], Err(err))),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(files, snapshots)| {
+            let merged = merge(snapshots).map_err(|err| FileError {
+                status: ExitStatus::Failure,
+                message: err.to_string(),
+            });
+            (files, merged)
+        })
+        .chain(failures)
         .collect()
 }
 
-fn extract_page_chunks(doc: &Document) -> LopdfResult<Vec<Vec<Chunk>>> {
-    let mut page_chunks = Vec::new();
-    for page_id in doc.get_pages().values().copied() {
-        // The first stage per page extracts general page information
-        // required to extract the text later.
-        let fonts = doc.get_page_fonts(page_id);
-        let encodings: BTreeMap<Vec<u8>, &str> = fonts
-            .into_iter()
-            .map(|(name, font)| (name, font.get_font_encoding()))
-            .collect::<BTreeMap<Vec<u8>, &str>>();
-        let content_data = doc.get_page_content(page_id)?;
-        let content = Content::decode(&content_data)?;
-
-        // After extracting general page information, we can proceed to the
-        // text extraction itself.
-        let blocks = group_text_blocks(&content);
-        let as_chunks: LopdfResult<Vec<Chunk>> = blocks
-            .iter()
-            .map(|b| block_to_chunk(b, &encodings))
-            .collect();
-        page_chunks.push(as_chunks?);
+// The students of the batch, merged and with rare values suppressed as asked.
+fn extract_students<'a>(
+    args: &Cli,
+    sources: &'a [PathBuf],
+    parse: impl Fn(&Path) -> Result<StudentInfo, FileError> + 'a,
+) -> Box<dyn Iterator<Item = Extracted<'a>> + 'a> {
+    // Without merging, each transcript is written as soon as it is parsed.
+    let students: Box<dyn Iterator<Item = Extracted>> = if args.merge_snapshots {
+        Box::new(merge_snapshots(sources, parse).into_iter())
+    } else {
+        Box::new(
+            sources
+                .iter()
+                .map(move |source| (vec![source.as_path()], parse(source))),
+        )
+    };
+    match args.k_anonymity {
+        Some(k) => Box::new(suppress_rare_values(students.collect(), k).into_iter()),
+        None => students,
     }
-    Ok(page_chunks)
 }
 
-const FOOTER_BANNER: &str = "S I M O N   F R A S E R   U N I V E R S I T Y";
+// The grade scale and equivalency table the rows are rewritten with.
+fn load_tables(args: &Cli) -> Result<(Option<GradeScale>, Option<EquivalencyTable>), Error> {
+    let scale = match &args.grade_scale {
+        Some(path) => Some(GradeScale::from_json(File::open(path)?).map_err(Error::other)?),
+        None => None,
+    };
+    let equivalencies = match &args.equivalencies {
+        Some(path) => Some(EquivalencyTable::from_csv(File::open(path)?).map_err(Error::other)?),
+        None => None,
+    };
+    Ok((scale, equivalencies))
+}
 
-fn combine_page_chunks(mut page_chunks: Vec<Vec<Chunk>>) -> Result<Vec<Chunk>, Error> {
-    let num_pages = page_chunks.len();
-    for page in &mut page_chunks[0..num_pages - 1] {
-        // The footer starts 7 indices before the end of every page
-        // except for the last page, but we leave it on the last page anyway.
-        let footer_start = page.len() - 7;
-        match &page[footer_start] {
-            Chunk::Chunks(v) if v[0] == Chunk::String(String::from(FOOTER_BANNER)) => {}
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "Footer banner not found at expected position",
-                ));
+fn export(args: &Cli, sources: &[PathBuf], output: Option<&Path>) -> Result<ExitStatus, Error> {
+    let mut anonymizer = create_anonymizer(args)?;
+    let leaks = Rc::new(RefCell::new(LeakCheck::new()));
+    let exporter = create_exporter(args, output, &leaks).map_err(output_error)?;
+    let exporter = with_record_scan(args, exporter, &leaks);
+    let mut exporter = with_term_table(args, exporter, &leaks).map_err(output_error)?;
+    let present = present_rows(args, output)?;
+    let mut skipped = 0;
+    let options = parse_options(args);
+    let (scale, equivalencies) = load_tables(args)?;
+    let (mut transfers, mut articulated) = (0, 0);
+    let mut risk = match &args.risk_rules {
+        Some(path) => Some((
+            load_definition(path, RiskRules::from_toml, RiskRules::from_json)?,
+            Vec::new(),
+        )),
+        None => None,
+    };
+    let cache = match &args.cache {
+        Some(dir) => Some(ParseCache::open(dir, &options)?),
+        None => None,
+    };
+    let hits = Cell::new(0);
+    let parse = |source: &Path| match &cache {
+        Some(cache) => parse_cached(cache, source, &options, &hits),
+        None => Transcript::from_path_with(source, &options).map_err(FileError::from),
+    };
+    let students = extract_students(args, sources, parse);
+    let mut cohort = CohortBuilder::new();
+    let mut manifest = args.manifest.as_ref().map(|_| Manifest::default());
+    let mut status = ExitStatus::Success;
+    for (count, (files, result)) in students.enumerate() {
+        let label = files_label(&files);
+        match result {
+            Ok(mut student) => {
+                if let Some(scale) = &scale {
+                    student.apply_grade_scale(scale);
+                }
+                if let Some(table) = &equivalencies {
+                    transfers += student.transfers.len();
+                    articulated += table.apply(&mut student);
+                }
+                let new_id = anonymizer.anonymize(count, &student.id);
+                add_to_cohort(args, &mut cohort, &student);
+                if let Some((rules, risks)) = &mut risk {
+                    risks.push(StudentRisk::new(new_id.clone(), rules.flags(&student)));
+                }
+                select_rows(args, &mut student);
+                if let Some(coarsening) = args.coarsen_term {
+                    coarsen_terms(&mut student, coarsening.into());
+                }
+                for warning in &student.warnings {
+                    eprintln!("Warning: {label}: {warning}");
+                }
+                if let Some(present) = &present {
+                    skipped += remove_present_rows(&mut student, &new_id, present);
+                }
+                leaks.borrow_mut().add_student(&label, &student);
+                exporter
+                    .write_student(&new_id, &student)
+                    .map_err(output_error)?;
+                let rows = long_records(&new_id, &student).count();
+                for file in files {
+                    let outcome = Ok((new_id.clone(), rows, student.warnings.as_slice()));
+                    record_file(manifest.as_mut(), file, outcome);
+                }
+            }
+            Err(err) => {
+                eprintln!("Error: {label}: {err}");
+                status = status.and(err.status);
+                for file in files {
+                    record_file(manifest.as_mut(), file, Err(err.clone()));
+                }
             }
         }
-        page.truncate(footer_start);
     }
-    Ok(page_chunks.into_iter().flatten().collect())
+    exporter.finish().map_err(output_error)?;
+    check_leaks(args, output, &mut leaks.borrow_mut())?;
+    if present.is_some() {
+        eprintln!("Skipped {skipped} rows already in the output");
+    }
+    if cache.is_some() {
+        eprintln!(
+            "Reused {} of {} transcripts from the cache",
+            hits.get(),
+            sources.len()
+        );
+    }
+    if equivalencies.is_some() {
+        eprintln!("Found SFU equivalents for {articulated} of {transfers} transfer credits");
+    }
+    write_reports(
+        args,
+        cohort,
+        risk.as_ref().map(|(_, risks)| risks.as_slice()),
+        &leaks,
+    )?;
+    write_manifest(args.manifest.as_deref(), manifest.as_ref())?;
+    anonymizer.finish().map_err(output_error)?;
+    Ok(status)
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Plan {
-    name: String,
+// The cohort report covers every term of the students, but is coarsened
+// along with the rest of the output.
+fn add_to_cohort(args: &Cli, cohort: &mut CohortBuilder, student: &StudentInfo) {
+    match args.coarsen_term {
+        Some(coarsening) => {
+            let mut student = student.clone();
+            coarsen_terms(&mut student, coarsening.into());
+            cohort.add(&student);
+        }
+        None => cohort.add(student),
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Course {
-    subject: String,
-    id: String,
-    grade: String,
+// Suppresses the rare quasi-identifiers across the students of the batch
+// that were parsed. The batch is already shuffled, so failures are moved to
+// its end.
+fn suppress_rare_values(batch: Vec<Extracted>, k: u32) -> Vec<Extracted> {
+    let mut files = Vec::new();
+    let mut students = Vec::new();
+    let mut failed = Vec::new();
+    for (sources, result) in batch {
+        match result {
+            Ok(student) => {
+                files.push(sources);
+                students.push(student);
+            }
+            Err(err) => failed.push((sources, Err(err))),
+        }
+    }
+    let suppressed = suppress_rare(&mut students, k as usize);
+    eprintln!("Suppressed {suppressed} values shared by fewer than {k} students");
+    files
+        .into_iter()
+        .zip(students.into_iter().map(Ok))
+        .chain(failed)
+        .collect()
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Transfer {
-    course: Course,
-    school: Option<String>,
+// The student ID, name, and birth date of a transcript, so that they can be
+// replaced in text dumps. Transcripts that fail to parse are dumped when they
+// are the most useful to look at, so these are then taken from where the
+// parser expects them, as long as the ID looks like one.
+fn student_identity(
+    document: &Document,
+    options: &ParseOptions,
+) -> Result<(String, Identity), TranscriptError> {
+    Transcript::from_document_with(document, options)
+        .map(|student| (student.id, student.identity))
+        .or_else(|err| {
+            let chunks = Transcript::chunks(document)?;
+            let id = chunks
+                .len()
+                .checked_sub(3)
+                .and_then(|i| chunks[i].get_string())
+                .filter(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+                .map(str::to_string)
+                .ok_or(err)?;
+            Ok((id, header_identity(&chunks)))
+        })
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Semester {
-    year: String,
-    term: String,
-    is_good_standing: bool,
-    courses: Vec<Course>,
+// Fails if the real ID, name, or birth date of any student turned up in the
+// output. Files written by the binary formats are read back in full, on top
+// of the scan of their records as they are written.
+fn check_leaks(args: &Cli, output: Option<&Path>, leaks: &mut LeakCheck) -> Result<(), Error> {
+    let scanned = is_text_format(args.format) || database_url(args).is_some();
+    if let Some(path) = output.filter(|_| !scanned) {
+        leaks.scan_reader(File::open(path)?).map_err(output_error)?;
+    }
+    if leaks.found().is_empty() {
+        return Ok(());
+    }
+    Err(output_error(Error::other(format!(
+        "The output contains identifying text: {}",
+        leaks.found().join(", ")
+    ))))
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct StudentInfo {
-    id: String,
-    plan: Plan,
-    transfers: Vec<Transfer>,
-    semesters: Vec<Semester>,
-}
-
-fn process_plan(plan_chunk: &Chunk) -> Result<Plan, Error> {
-    if let Chunk::Chunks(v) = plan_chunk {
-        // The standard plan IDs seem to be in the second to last chunk of
-        // the block.
-        if let Some(s) = v[v.len() - 2].get_string() {
-            return Ok(Plan {
-                name: s.to_string(),
-            });
+fn export_text(
+    args: &Cli,
+    sources: &[PathBuf],
+    output: Option<&Path>,
+) -> Result<ExitStatus, Error> {
+    let mut anonymizer = create_anonymizer(args)?;
+    let options = parse_options(args);
+    let leaks = Rc::new(RefCell::new(LeakCheck::new()));
+    let out = text_output(output).map_err(output_error)?;
+    let mut out = LeakScanner::new(out, Rc::clone(&leaks));
+    let mut status = ExitStatus::Success;
+    for (count, source) in sources.iter().enumerate() {
+        let extracted = load_document(source).and_then(|document| {
+            let (id, identity) = student_identity(&document, &options)?;
+            Ok((
+                id,
+                identity,
+                Transcript::page_chunks_with(&document, &options.page_order)?,
+            ))
+        });
+        let (id, identity, pages) = match extracted {
+            Ok(extracted) => extracted,
+            Err(err) => {
+                eprintln!("Error: {}: {err}", source.display());
+                status = status.and(err.category().into());
+                continue;
+            }
+        };
+        let new_id = anonymizer.anonymize(count, &id);
+        leaks
+            .borrow_mut()
+            .add_identity(&source.display().to_string(), &id, &identity);
+        // The name and birth date are replaced by the anonymized ID as well,
+        // as in redacted transcripts.
+        let targets: Vec<&str> = [
+            Some(&id),
+            identity.name.as_ref(),
+            identity.birth_date.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .collect();
+        writeln!(out, "Student {new_id}").map_err(output_error)?;
+        for (page_index, page) in pages.iter().enumerate() {
+            for (block_index, block) in page.iter().enumerate() {
+                // Strings shown together, such as kerned arrays, make up one
+                // cell of the row.
+                let cells: Vec<String> = match block {
+                    Chunk::Chunks(cells) => cells.iter().map(|c| c.strings().concat()).collect(),
+                    Chunk::String(s) => vec![s.clone()],
+                };
+                let text = targets.iter().fold(cells.join(" | "), |text, target| {
+                    text.replace(target, &new_id)
+                });
+                writeln!(out, "{}.{block_index}\t{text}", page_index + 1).map_err(output_error)?;
+            }
         }
     }
-    Err(Error::new(ErrorKind::InvalidData, "Bad plan chunk found"))
+    out.flush().map_err(output_error)?;
+    check_leaks(args, output, &mut leaks.borrow_mut())?;
+    anonymizer.finish().map_err(output_error)?;
+    Ok(status)
 }
 
-const QUALIFIERS: [&str; 3] = ["W", "Q", "Online"];
-const BREADTH_TAGS: [&str; 3] = ["B-Sci", "B-Hum", "B-Soc"];
-
-fn matches_breadth(s: &str) -> bool {
-    BREADTH_TAGS.iter().any(|b| s.contains(b))
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
 }
 
-fn is_qualifier(s: &str) -> bool {
-    QUALIFIERS.contains(&s)
+// Narrows a parsed student down to the rows requested, once the whole
+// transcript has been parsed.
+fn select_rows(args: &Cli, student: &mut StudentInfo) {
+    if !args.subject.is_empty() {
+        student.retain_subjects(&args.subject);
+    }
+    if args.from_term.is_some() || args.to_term.is_some() {
+        let bound = |term: Option<(u32, usize)>| term.map_or(Bound::Unbounded, Bound::Included);
+        student.retain_terms((bound(args.from_term), bound(args.to_term)));
+    }
+    if args.no_transfers {
+        student.transfers.clear();
+    }
 }
 
-fn is_perm_dt(s: &str) -> bool {
-    s == "Perm.Dt:" || s.split('-').count() == 3
+// Parses a transcript for a dry run and prints what it would contribute.
+// Validation problems that lenient parsing did not already report as
+// warnings fail the transcript, as they would fail a strict run.
+fn dry_run_transcript(
+    args: &Cli,
+    source: &Path,
+    options: &ParseOptions,
+    scale: Option<&GradeScale>,
+) -> Result<StudentInfo, FileError> {
+    // PESC transcripts have no layout to validate beyond parsing them.
+    let (mut student, problems) = if has_extension(source, &["xml"]) {
+        (Transcript::from_path_with(source, options)?, Vec::new())
+    } else {
+        let document = load_document(source)?;
+        let student = Transcript::from_document_with(&document, options)?;
+        let problems: Vec<String> = Transcript::validate_with(&document, options)
+            .into_iter()
+            .filter(|problem| !student.warnings.contains(problem))
+            .collect();
+        (student, problems)
+    };
+    if let Some(scale) = scale {
+        student.apply_grade_scale(scale);
+    }
+    if !problems.is_empty() {
+        return Err(FileError {
+            status: ExitStatus::Validation,
+            message: problems.join("; "),
+        });
+    }
+    select_rows(args, &mut student);
+    let terms: Vec<String> = student
+        .semesters
+        .iter()
+        .map(|semester| format!("{} {}", semester.year, semester.term))
+        .collect();
+    let covered = if terms.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", terms.join(", "))
+    };
+    println!(
+        "{}: {}, {}{covered}",
+        source.display(),
+        plural(long_records(&student.id, &student).count(), "row"),
+        plural(terms.len(), "term")
+    );
+    for warning in &student.warnings {
+        println!("  Warning: {warning}");
+    }
+    Ok(student)
 }
 
-const POSSIBLE_GRADES: [&str; 28] = [
-    // Standard passing grades
-    "A+", "A", "A-", "B+", "B", "B-", "C+", "C", "C-", "D", "P",
-    // Temporary grades
-    "DE", "GN", "IP",
-    // Forms of failing
-    "F", "FD", "N",
-    // Notations
-    "AE", "AU", "CC", "CF", "CN", "CR", "FX", "NC", "WD", "WE", "TR",
-];
+// Reports what an export would write without writing anything, so that new
+// batches can be vetted before they touch a dataset.
+fn dry_run(args: &Cli, sources: &[PathBuf]) -> Result<ExitStatus, Error> {
+    let options = parse_options(args);
+    let mut status = ExitStatus::Success;
+    let (mut rows, mut records, mut failed) = (0, 0, 0);
+    let mut fail = |source: &Path, err: &FileError| {
+        println!("{}: FAILED: {err}", source.display());
+        status = status.and(err.status);
+        failed += 1;
+    };
+    match args.doc_type {
+        DocType::Transcript => {
+            let scale = match &args.grade_scale {
+                Some(path) => Some(GradeScale::from_json(File::open(path)?).map_err(Error::other)?),
+                None => None,
+            };
+            let parse = |source: &Path| dry_run_transcript(args, source, &options, scale.as_ref());
+            let students = if args.merge_snapshots {
+                merge_snapshots(sources, parse)
+            } else {
+                sources
+                    .iter()
+                    .map(|source| (vec![source.as_path()], parse(source)))
+                    .collect()
+            };
+            for (files, result) in students {
+                match result {
+                    Ok(student) => {
+                        rows += long_records(&student.id, &student).count();
+                        records += 1;
+                    }
+                    Err(err) => files.iter().for_each(|file| fail(file, &err)),
+                }
+            }
+        }
+        DocType::Progress => {
+            for source in sources {
+                match ProgressReport::from_path_with(source, &options) {
+                    Ok(report) => {
+                        let count = report.requirements.len();
+                        println!("{}: {}", source.display(), plural(count, "requirement"));
+                        for warning in &report.warnings {
+                            println!("  Warning: {warning}");
+                        }
+                        rows += count;
+                        records += 1;
+                    }
+                    Err(err) => fail(source, &err.into()),
+                }
+            }
+        }
+    }
+    let noun = match args.doc_type {
+        DocType::Transcript => "student",
+        DocType::Progress => "report",
+    };
+    println!(
+        "Would write {} for {} from {}; {} failed",
+        plural(rows, "row"),
+        plural(records, noun),
+        plural(sources.len(), "file"),
+        failed
+    );
+    Ok(status)
+}
 
-// NOTE: The unwraps in the transfer and semester processing should e left in
-// at least for now. As the data cleaning involves some reverse engineering,
-// they help the process fail fast and identify errors.
+fn write_report(
+    path: &Path,
+    report: &CohortReport,
+    options: CsvOptions,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<(), Error> {
+    write_staged(path, |writer| {
+        let writer = LeakScanner::new(writer, Rc::clone(leaks));
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            write_cohort_json(writer, report)
+        } else {
+            write_cohort_csv(writer, report, options)
+        }
+    })
+}
 
-fn process_transfers(chunks: &[Chunk]) -> Vec<Transfer> {
-    // Transform the Chunk sequence into a list of string rows.
-    // We can skip over the initial sequence of single string elements,
-    // as they contain no transfer information.
-    let mut sources = chunks
-        .iter()
-        .filter(|c| c.is_chunks())
-        .filter_map(|c| c.get_contained())
-        .map(|slice| {
-            slice
-                .iter()
-                .filter_map(|c| c.get_string())
-                // WQB Qualifiers create extra columns in anyy row, so identifying
-                // and filtering them evens out the data.
-                .filter(|s| !is_qualifier(s) && !matches_breadth(s))
-                .collect::<Vec<&str>>()
-        })
-        .collect::<Vec<Vec<&str>>>();
-
-    // The first row includes a column from the header but actually needs
-    // another spacer element in order to align with the other rows nicely.
-    sources[0].insert(0, "");
-
-    // Page breaks add a column and split a row into two.
-    let page_break_tag = "SFUSR";
-    let mut i = 0;
-    while i < sources.len() - 1 {
-        let position = sources[i].len() - 1;
-        if sources[i][position].starts_with(page_break_tag) {
-            sources[i].remove(position);
-            let next = sources.remove(i + 1);
-            sources[i].extend_from_slice(&next);
-        }
-        i += 1;
-    }
-
-    let mut transfers = Vec::with_capacity(chunks.len());
-
-    // By default, the rows are ragged, and individual transfer credits are
-    // each split over 2 rows. Extract the course and institution if possible
-    // to create `Transfer`s.
-    i = 0;
-    while i < sources.len() - 1 {
-        // Institution names are on the following rows when present.
-        // Lines with institution names have 10 columns.
-        let school = if [10, 2].contains(&sources[i + 1].len()) {
-            Some(sources[i + 1][1].to_string())
+fn write_risk_report(
+    path: &Path,
+    students: &[StudentRisk],
+    options: CsvOptions,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<(), Error> {
+    write_staged(path, |writer| {
+        let writer = LeakScanner::new(writer, Rc::clone(leaks));
+        if has_extension(path, &["json"]) {
+            write_risk_json(writer, students)
         } else {
-            None
-        };
-        let course_offset = usize::from(sources[i].len() == 10);
-        // Sanity check that the grades are in the possible grades list
-        // to help identify any irregularities in the PDF stream
-        // while reverse engineering.
-        assert!(POSSIBLE_GRADES.contains(&sources[i][course_offset + 6]));
-        transfers.push(Transfer {
-            course: Course {
-                subject: sources[i][course_offset + 1].to_string(),
-                id: sources[i][course_offset + 2].to_string(),
-                grade: sources[i][course_offset + 6].to_string(),
-            },
-            school,
-        });
-        i += 1;
+            write_risk_csv(writer, students, options)
+        }
+    })
+}
+
+// Writes the cohort and at-risk reports that were asked for, checking them
+// for leaks like the output.
+fn write_reports(
+    args: &Cli,
+    cohort: CohortBuilder,
+    risks: Option<&[StudentRisk]>,
+    leaks: &Rc<RefCell<LeakCheck>>,
+) -> Result<(), Error> {
+    if let Some(path) = &args.report {
+        let report = cohort.finish();
+        write_report(path, &report, csv_options(args), leaks).map_err(output_error)?;
+    }
+    if let (Some(path), Some(risks)) = (&args.risk_report, risks) {
+        let flagged = risks.iter().filter(|risk| risk.at_risk).count();
+        eprintln!("Flagged {flagged} of {} students as at risk", risks.len());
+        write_risk_report(path, risks, csv_options(args), leaks).map_err(output_error)?;
     }
+    Ok(())
+}
 
-    transfers
+// Output files are first written to a hidden sibling of the target and only
+// renamed into place once the export succeeds, so an interrupted run never
+// leaves a truncated file behind.
+fn staging_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map_or_else(Default::default, |name| name.to_string_lossy());
+    target.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
 }
 
-fn process_semesters(chunks: &[Chunk]) -> Vec<Semester> {
-    fn get_year_term(s: &str) -> Option<(&str, &str)> {
-        let mut pieces = s.split_ascii_whitespace();
-        match (pieces.next(), pieces.next()) {
-            (Some(year), Some(term)) if ["Spring", "Summer", "Fall"].contains(&term) => {
-                Some((year, term))
-            }
-            _ => None,
+// Writes a file other than the output through its staging path, so that it
+// too is either complete or left as it was.
+fn write_staged(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let staged = staging_path(path);
+    let result = File::create(&staged).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()
+    });
+    match result {
+        Ok(()) => std::fs::rename(&staged, path),
+        Err(error) => {
+            let _ = std::fs::remove_file(&staged);
+            Err(error)
         }
     }
+}
 
-    let grouped = chunks
-        .chunk_by(|_, b| !matches!(b, Chunk::String(s) if get_year_term(s).is_some()))
-        .skip(1)
-        .filter(|s| s.len() >= 2)
-        .map(|s| {
-            (
-                get_year_term(s[0].get_string().unwrap()).unwrap(),
-                s[1..]
-                    .iter()
-                    // Rows are ragged, so map elements to strings and filter out
-                    // conditional elements like qualifiers to make columns align.
-                    .filter_map(|c| c.get_contained())
-                    .map(|row| {
-                        row.iter()
-                            .filter_map(|c| c.get_string())
-                            .filter(|s| !is_qualifier(s) && !matches_breadth(s) && !is_perm_dt(s))
-                            .collect::<Vec<_>>()
-                    })
-                    // Exclude rows for GPA or courses without grades
-                    .filter(|v| !v[0].ends_with("GPA:") && 6 < v.len() && !v[6].is_empty())
-                    .collect::<Vec<_>>(),
-            )
-        })
-        .collect::<Vec<_>>();
+/// The exit status of a run. Failures have distinct codes so that scripts
+/// driving batch runs can triage them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+enum ExitStatus {
+    Success = 0,
+    /// Any failure without a more specific code.
+    Failure = 1,
+    /// A file could not be read or is not a valid PDF.
+    Unreadable = 2,
+    /// A PDF does not have the layout of a transcript.
+    Layout = 3,
+    /// A transcript fails validation or a sanity check of its contents.
+    Validation = 4,
+    /// The output could not be written.
+    Output = 5,
+    /// The command line arguments are invalid (`EX_USAGE`). Clap's usual
+    /// code of 2 already means an unreadable PDF.
+    Usage = 64,
+}
 
-    grouped
-        .iter()
-        .filter(|(_, rows)| !rows.is_empty())
-        .map(|((year, term), rows)| Semester {
-            year: (*year).to_string(),
-            term: (*term).to_string(),
-            is_good_standing: true,
-            courses: rows
-                .iter()
-                // Including asserting inspections helps to sanity check the
-                // correctness of the extraction because of the reverse
-                // engineered format.
-                .inspect(|v| assert!(POSSIBLE_GRADES.contains(&v[6])))
-                .map(|r| Course {
-                    subject: r[1].to_string(),
-                    id: r[2].to_string(),
-                    grade: r[6].to_string(),
-                })
-                .collect(),
-        })
-        .collect::<Vec<_>>()
+impl ExitStatus {
+    // Combines the statuses of the files in a batch, which exits with the
+    // lowest code among the failures.
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Success, status) | (status, Self::Success) => status,
+            (a, b) => a.min(b),
+        }
+    }
 }
 
-fn process_chunks(chunks: &[Chunk]) -> Result<StudentInfo, Error> {
-    fn find_index(chunks: &[Chunk], start: usize, marker: &str, err: &str) -> Result<usize, Error> {
-        let marker_chunk = Chunk::String(marker.to_string());
-        Ok(start
-            + chunks[start..]
-                .iter()
-                .position(|c| c == &marker_chunk)
-                .ok_or_else(|| Error::new(ErrorKind::InvalidData, err))?)
+impl From<ErrorCategory> for ExitStatus {
+    fn from(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::Unreadable => Self::Unreadable,
+            ErrorCategory::Layout => Self::Layout,
+            ErrorCategory::Validation => Self::Validation,
+        }
     }
+}
 
-    let plan_marker = "Plan";
-    let plan_index = 1 + find_index(chunks, 0, plan_marker, "Plan marker not found")?;
+/// An error that ends the run with a specific exit status. Errors travel
+/// through the exporters as `io::Error`, so the status rides inside one.
+#[derive(Debug)]
+struct StatusError {
+    status: ExitStatus,
+    source: Error,
+}
 
-    // This section is optional, so errors are nonfatal
-    let transfer_marker = "TRANSFER COURSES";
-    let transfer_index = find_index(
-        chunks,
-        plan_index,
-        transfer_marker,
-        "Transfer marker not found",
-    );
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.source.fmt(f)
+    }
+}
 
-    let program_marker = "Program:";
-    let program_index = find_index(
-        chunks,
-        plan_index,
-        program_marker,
-        "Program marker not found",
-    )?;
+impl std::error::Error for StatusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
 
-    let end_marker = "TOTAL UNITS PASSED BY ACADEMIC GROUP";
-    let end_index = find_index(chunks, program_index, end_marker, "End marker not found")?;
+fn with_status(status: ExitStatus, source: Error) -> Error {
+    Error::new(source.kind(), StatusError { status, source })
+}
 
-    let id_index = chunks.len() - 3;
+fn output_error(err: Error) -> Error {
+    with_status(ExitStatus::Output, err)
+}
 
-    Ok(StudentInfo {
-        id: chunks[id_index]
-            .get_string()
-            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Bad student id"))?
-            .to_string(),
-        plan: process_plan(&chunks[plan_index])?,
-        transfers: transfer_index.map(|i| process_transfers(&chunks[i..program_index]))?,
-        semesters: process_semesters(&chunks[program_index..end_index]),
-    })
+// Reports a transcript that failed to load or parse along with its path.
+fn transcript_failure(path: &Path, err: &TranscriptError) -> Error {
+    let message = format!("{}: {err}", path.display());
+    with_status(err.category().into(), Error::other(message))
 }
 
-fn write_long_csv<W: std::io::Write>(
-    writer: &mut csv::Writer<W>,
-    student: &StudentInfo,
-    new_id: usize,
-) -> Result<(), Error> {
-    for transfer in &student.transfers {
-        writer.write_record([
-            &new_id.to_string(),
-            &student.plan.name,
-            "None",
-            "None",
-            &transfer.course.subject,
-            &transfer.course.id,
-            &transfer.course.grade,
-            transfer.school.as_deref().unwrap_or("None"),
-        ])?;
-    }
-    for semester in &student.semesters {
-        for course in &semester.courses {
-            writer.write_record([
-                &new_id.to_string(),
-                &student.plan.name,
-                &semester.year,
-                &semester.term,
-                &course.subject,
-                &course.id,
-                &course.grade,
-                "",
-            ])?;
-        }
-    }
-    writer.flush()?;
-    Ok(())
+fn status_of(err: &Error) -> ExitStatus {
+    let inner = err.get_ref();
+    if let Some(err) = inner.and_then(|err| err.downcast_ref::<StatusError>()) {
+        return err.status;
+    }
+    inner
+        .and_then(|err| err.downcast_ref::<TranscriptError>())
+        .map_or(ExitStatus::Failure, |err| err.category().into())
 }
 
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Cli {
-    #[command(flatten)]
-    input: InputSource,
+fn exit_usage(err: &clap::Error) -> ! {
+    // Printing to a closed terminal is not worth reporting.
+    let _ = err.print();
+    let status = if err.use_stderr() {
+        ExitStatus::Usage
+    } else {
+        ExitStatus::Success
+    };
+    std::process::exit(i32::from(status as u8))
+}
 
-    /// Anonymized (first) student ID to use during export
-    #[arg(short, long)]
-    newid: usize,
+fn main() -> ExitCode {
+    let args = Cli::try_parse().unwrap_or_else(|err| exit_usage(&err));
+    match run(&args) {
+        Ok(status) => ExitCode::from(status as u8),
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::from(status_of(&err) as u8)
+        }
+    }
 }
 
-#[derive(Args, Debug)]
-#[group(required = true, multiple = false)]
-struct InputSource {
-    /// Path to input file
-    #[arg(short, long)]
-    pdf: Option<std::path::PathBuf>,
+// Rejects options that do not apply to the chosen document type, format,
+// or layout.
+fn check_options(args: &Cli) -> Result<(), Error> {
+    if args.merge_snapshots && args.doc_type != DocType::Transcript {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Only transcripts can be merged with --merge-snapshots",
+        ));
+    }
 
-    /// Path to input file
-    #[arg(short, long)]
-    dir: Option<std::path::PathBuf>,
-}
-
-fn main() -> Result<(), Error> {
-    let args = Cli::parse();
-
-    let mut sources = match (args.input.pdf, args.input.dir) {
-        (Some(path), None) => vec![path],
-        (None, Some(path)) => std::fs::read_dir(path)?
-            // Only process files that are readable
-            .filter_map(std::result::Result::ok)
-            .filter(|entry| entry.file_type().map_or(false, |f| f.is_file()))
-            .map(|entry| entry.path())
-            // Restrict to PDFs
-            .filter(|path| {
-                path.extension()
-                    .filter(|ext| ext.to_ascii_lowercase() == "pdf")
-                    .is_some()
-            })
-            .collect(),
+    if args.layout != Layout::Long && !matches!(args.format, Format::Csv | Format::Tsv) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The wide, course, and GPA layouts are only supported for CSV and TSV output",
+        ));
+    }
+
+    if (!args.columns.is_empty() || args.printed_gpas || args.sort.is_some())
+        && (args.layout != Layout::Long || !matches!(args.format, Format::Csv | Format::Tsv))
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Columns and row order can only be chosen for the long CSV and TSV layout",
+        ));
+    }
+
+    if args.append
+        && !(matches!(args.format, Format::Csv | Format::Tsv) && args.layout == Layout::Long
+            || args.format == Format::Sqlite)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Only the long CSV and TSV layout and SQLite can be appended to",
+        ));
+    }
+
+    if database_url(args).is_some()
+        && (args.doc_type != DocType::Transcript
+            || args.format != Format::Csv
+            || args.layout != Layout::Long
+            || args.append)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Only the long layout of transcripts can be loaded into PostgreSQL, \
+             where rows are upserted rather than appended",
+        ));
+    }
+
+    if (args.append || database_url(args).is_some())
+        && args.anonymize == Anonymization::Sequential
+        && args.id_map.is_none()
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Appending and upserting need the stable IDs of --anonymize hmac or --id-map",
+        ));
+    }
+
+    let reduced = args.k_anonymity.is_some() || args.coarsen_term.is_some();
+    if reduced && args.doc_type != DocType::Transcript {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Only transcripts can be suppressed or coarsened",
+        ));
+    }
+
+    if args.format == Format::Text
+        && (args.doc_type != DocType::Transcript || args.merge_snapshots || reduced)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Text dumps are only written for individual transcripts",
+        ));
+    }
+    Ok(())
+}
+
+fn run(args: &Cli) -> Result<ExitStatus, Error> {
+    let success = |()| ExitStatus::Success;
+    if let Some(command) = &args.command {
+        return match command {
+            Command::Inspect(inspect_args) => inspect(inspect_args).map(success),
+            Command::Validate(validate_args) => validate(validate_args),
+            Command::Stats(stats_args) => stats(stats_args).map(success),
+            Command::Diff(diff_args) => diff(diff_args).map(success),
+            Command::Prereqs(prereqs_args) => prereqs(prereqs_args).map(success),
+            Command::Audit(audit_args) => audit(audit_args).map(success),
+            Command::Gen(gen_args) => generate(gen_args).map(success),
+            Command::Redact(redact_args) => redact(redact_args).map(success),
+            #[cfg(feature = "vault")]
+            Command::Reveal(reveal_args) => reveal(reveal_args).map(success),
+            #[cfg(feature = "serve")]
+            Command::Serve(serve_args) => serve(serve_args).map(success),
+        };
+    }
+    if args.emit_schema {
+        let mut out = std::io::stdout().lock();
+        serde_json::to_writer_pretty(&mut out, &LONG_COLUMNS)?;
+        return writeln!(out).map(success);
+    }
+    let (path, is_dir) = match (&args.source.pdf, &args.source.dir, &args.source.input) {
+        (Some(path), None, None) => (path.clone(), false),
+        (None, Some(path), None) => (path.clone(), true),
+        (None, None, Some(path)) => (path.clone(), path.is_dir()),
+        (None, None, None) => exit_usage(&Cli::command().error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "one of --pdf, --dir, or --input is required",
+        )),
         _ => unreachable!(),
     };
+    if args.anonymize == Anonymization::Sequential && args.newid.is_none() && args.id_map.is_none()
+    {
+        exit_usage(&Cli::command().error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--newid is required for sequential anonymization",
+        ));
+    }
+
+    let extensions = match args.doc_type {
+        DocType::Transcript => TRANSCRIPTS,
+        DocType::Progress => PDF,
+    };
+    let mut sources = collect_sources(&path, is_dir, &args.walk, extensions)?;
+    sources.retain(|source| !args.exclude.iter().any(|p| p.matches_path(source)));
 
     let mut rng = thread_rng();
     sources.shuffle(&mut rng);
 
-    for (count, source) in sources.iter().enumerate() {
-        match Document::load(source) {
-            Ok(document) => {
-                let chunks = extract_page_chunks(&document).unwrap();
-                let simplified: Vec<Vec<Chunk>> = chunks
-                    .into_iter()
-                    .map(|page| page.into_iter().map(Chunk::simplify).collect())
-                    .collect();
-                let combined = combine_page_chunks(simplified).unwrap();
-                let student = process_chunks(&combined).unwrap();
-
-                let mut writer = csv::Writer::from_writer(std::io::stdout());
-                write_long_csv(&mut writer, &student, count + args.newid)?;
-            }
-            Err(err) => eprintln!("Error: {err}"),
-        }
+    check_options(args)?;
+
+    if args.dry_run {
+        return dry_run(args, &sources);
     }
 
-    Ok(())
+    // PostgreSQL is loaded in place, relying on transactions instead.
+    let target = args.output.as_deref();
+    let staged = target
+        .filter(|_| database_url(args).is_none())
+        .map(staging_path);
+    // Appending, and adding students to an SQLite database, starts from a
+    // copy of the output, which still only replaces it once the whole run
+    // succeeds.
+    if let (Some(staged), Some(target)) = (&staged, target) {
+        if (args.append || args.format == Format::Sqlite) && target.exists() {
+            std::fs::copy(target, staged).map_err(output_error)?;
+        }
+    }
+    let result = match args.doc_type {
+        DocType::Transcript if args.format == Format::Text => {
+            export_text(args, &sources, staged.as_deref().or(target))
+        }
+        DocType::Transcript => export(args, &sources, staged.as_deref().or(target)),
+        DocType::Progress => export_progress(args, &sources, staged.as_deref().or(target)),
+    };
+    // The term table is only written alongside the records of transcripts,
+    // and is staged and replaced together with the output.
+    let mut staged_files: Vec<(PathBuf, &Path)> = staged.into_iter().zip(target).collect();
+    if let Some(term_table) = args.term_table.as_deref() {
+        if args.doc_type == DocType::Transcript && args.format != Format::Text {
+            staged_files.push((staging_path(term_table), term_table));
+        }
+    }
+    if result.is_ok() {
+        for (staged, target) in staged_files {
+            std::fs::rename(staged, target).map_err(output_error)?;
+        }
+    } else {
+        // The partial output is discarded, so a failure to remove it is not
+        // worth masking the original error.
+        for (staged, _) in staged_files {
+            let _ = std::fs::remove_file(staged);
+        }
+    }
+    result
 }
@@ -0,0 +1,158 @@
+//! Combining several transcripts of the same student, such as snapshots
+//! pulled each term, into one canonical record.
+
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use thiserror::Error;
+
+use crate::model::{
+    term_key, Credential, Identity, Milestone, Note, Plan, Semester, StudentInfo, Transfer,
+};
+use crate::parse;
+
+/// Errors that can occur while merging snapshots.
+#[derive(Debug, Error)]
+pub enum MergeError {
+    #[error("No snapshots to merge")]
+    Empty,
+
+    /// The snapshots do not all have the same student ID.
+    #[error("Cannot merge snapshots of different students")]
+    DifferentStudents,
+}
+
+// The most recent term of a snapshot, or the earliest key for one without
+// any terms.
+fn latest_term(student: &StudentInfo) -> (u32, usize) {
+    student
+        .semesters
+        .iter()
+        .map(Semester::term_key)
+        .max()
+        .unwrap_or_default()
+}
+
+fn plan_key(plan: &Plan) -> (u32, usize) {
+    match (&plan.effective_year, &plan.effective_term) {
+        (Some(year), Some(term)) => term_key(year, term),
+        _ => (0, 0),
+    }
+}
+
+// Inserts a value under `key`, replacing any earlier value with the same key
+// in place so that values keep the order they were first seen in.
+fn upsert<K: Ord, V>(values: &mut Vec<V>, index: &mut BTreeMap<K, usize>, key: K, value: V) {
+    match index.entry(key) {
+        Entry::Occupied(entry) => values[*entry.get()] = value,
+        Entry::Vacant(entry) => {
+            entry.insert(values.len());
+            values.push(value);
+        }
+    }
+}
+
+/// Merges snapshots of the same student into one record.
+///
+/// The snapshots are ordered by their most recent term, keeping the given
+/// order for ties, and later snapshots take precedence. The result holds
+/// every term found in any snapshot, each as recorded by the latest snapshot
+/// containing it, so grades recorded or revised since an earlier pull win.
+/// Transfer credits are matched by institution, subject, and course ID,
+/// credentials by program, and milestones by name, in the same way. Notes
+/// and warnings are gathered without repeats, and the unit totals are those
+/// of the latest snapshot listing any. Cumulative GPAs and attempt numbers
+/// are recomputed over the merged terms.
+///
+/// # Errors
+///
+/// Returns an error if there are no snapshots or if they belong to
+/// different students.
+pub fn merge(snapshots: impl IntoIterator<Item = StudentInfo>) -> Result<StudentInfo, MergeError> {
+    let mut snapshots: Vec<StudentInfo> = snapshots.into_iter().collect();
+    let id = snapshots.first().ok_or(MergeError::Empty)?.id.clone();
+    if snapshots.iter().any(|student| student.id != id) {
+        return Err(MergeError::DifferentStudents);
+    }
+    snapshots.sort_by_key(latest_term);
+
+    let mut plans: Vec<Plan> = Vec::new();
+    let mut semesters = BTreeMap::new();
+    let mut transfers: Vec<Transfer> = Vec::new();
+    let mut transfer_index = BTreeMap::new();
+    let mut credentials: Vec<Credential> = Vec::new();
+    let mut credential_index = BTreeMap::new();
+    let mut milestones: Vec<Milestone> = Vec::new();
+    let mut milestone_index = BTreeMap::new();
+    let mut notes: Vec<Note> = Vec::new();
+    let mut identity = Identity::default();
+    let mut unit_totals = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    for snapshot in snapshots {
+        for plan in snapshot.plans {
+            if !plans.contains(&plan) {
+                plans.push(plan);
+            }
+        }
+        // Repeated transfer credits are told apart by their position among
+        // those with the same key.
+        let mut seen: BTreeMap<(Option<String>, String, String), usize> = BTreeMap::new();
+        for transfer in snapshot.transfers {
+            let key = (
+                transfer.school.clone(),
+                transfer.course.subject.clone(),
+                transfer.course.id.clone(),
+            );
+            let occurrence = seen.entry(key.clone()).or_default();
+            let key = (key, *occurrence);
+            *occurrence += 1;
+            upsert(&mut transfers, &mut transfer_index, key, transfer);
+        }
+        for credential in snapshot.credentials {
+            let key = credential.program.clone();
+            upsert(&mut credentials, &mut credential_index, key, credential);
+        }
+        for milestone in snapshot.milestones {
+            let key = milestone.name.clone();
+            upsert(&mut milestones, &mut milestone_index, key, milestone);
+        }
+        if snapshot.identity != Identity::default() {
+            identity = snapshot.identity;
+        }
+        // The totals cover the whole transcript, so the latest ones stand.
+        if !snapshot.unit_totals.is_empty() {
+            unit_totals = snapshot.unit_totals;
+        }
+        for semester in snapshot.semesters {
+            semesters.insert(semester.term_key(), semester);
+        }
+        for note in snapshot.notes {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+        for warning in snapshot.warnings {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    // Plans without an effective term are the ones admitted to, so they
+    // sort first.
+    plans.sort_by_key(plan_key);
+
+    let mut semesters: Vec<Semester> = semesters.into_values().collect();
+    parse::compute_cumulative_gpas(&mut semesters);
+    parse::number_attempts(&mut transfers, &mut semesters);
+    Ok(StudentInfo {
+        id,
+        plans,
+        transfers,
+        semesters,
+        credentials,
+        milestones,
+        notes,
+        unit_totals,
+        warnings,
+        identity,
+    })
+}
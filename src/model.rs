@@ -0,0 +1,502 @@
+use std::ops::RangeBounds;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TranscriptError;
+use crate::grades::GradeScale;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Plan {
+    pub name: String,
+    /// The year of the term the plan took effect, or `None` for the plan
+    /// the student was admitted to.
+    pub effective_year: Option<String>,
+    /// The term the plan took effect, alongside `effective_year`.
+    pub effective_term: Option<String>,
+}
+
+impl Plan {
+    /// Whether the plan took effect in the given term.
+    #[must_use]
+    pub fn starts_in(&self, semester: &Semester) -> bool {
+        self.effective_year.as_deref() == Some(semester.year.as_str())
+            && self.effective_term.as_deref() == Some(semester.term.as_str())
+    }
+}
+
+/// A WQB (writing, quantitative, and breadth) requirement designation of a
+/// course, serialized as the tag printed on the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Designation {
+    #[serde(rename = "W")]
+    Writing,
+    #[serde(rename = "Q")]
+    Quantitative,
+    #[serde(rename = "B-Sci")]
+    BreadthScience,
+    #[serde(rename = "B-Hum")]
+    BreadthHumanities,
+    #[serde(rename = "B-Soc")]
+    BreadthSocialSciences,
+}
+
+impl Designation {
+    /// The tag printed on the transcript for the designation.
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Writing => "W",
+            Self::Quantitative => "Q",
+            Self::BreadthScience => "B-Sci",
+            Self::BreadthHumanities => "B-Hum",
+            Self::BreadthSocialSciences => "B-Soc",
+        }
+    }
+}
+
+/// How an attempt at a course ended, as implied by its grade or notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Passed with a letter grade or earned credit without one.
+    Completed,
+    Failed,
+    /// Withdrawn from, including withdrawals under extenuating circumstances.
+    Withdrawn,
+    /// Currently enrolled, with no grade recorded yet.
+    InProgress,
+    /// A deferred or not yet reported grade.
+    Pending,
+    Audit,
+    /// A grade or notation that is not recognized.
+    Unknown,
+}
+
+impl Outcome {
+    /// The name of the outcome as written in the output formats.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Withdrawn => "withdrawn",
+            Self::InProgress => "in_progress",
+            Self::Pending => "pending",
+            Self::Audit => "audit",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Course {
+    pub subject: String,
+    pub id: String,
+    pub title: String,
+    pub grade: String,
+    /// The grade points of the grade, or `None` for notations that carry
+    /// none. SFU's scale is used unless another is applied with
+    /// [`StudentInfo::apply_grade_scale`].
+    pub grade_points: Option<f64>,
+    pub outcome: Outcome,
+    pub units_attempted: Option<f64>,
+    pub units_earned: Option<f64>,
+    /// The WQB designations the course counts toward.
+    pub designations: Vec<Designation>,
+    /// Which attempt at the course this is, counting from 1.
+    pub attempt: u32,
+    /// Whether no later attempt at the course exists.
+    pub is_latest_attempt: bool,
+    /// Whether the grade is in the grade vocabulary. Courses with unknown
+    /// grades are kept, with a warning, unless parsing is strict.
+    pub grade_valid: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub course: Course,
+    pub school: Option<String>,
+    /// The units of credit granted for the transfer course.
+    pub units: Option<f64>,
+    /// The SFU course the transfer course is articulated as, when an
+    /// equivalency table lists it.
+    pub equivalent: Option<Equivalent>,
+}
+
+/// An SFU course by subject and number, e.g. CMPT 120.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Equivalent {
+    pub subject: String,
+    pub id: String,
+}
+
+impl Transfer {
+    /// The transfer course with the units granted for it as its units
+    /// attempted and earned, where the course row does not print its own.
+    #[must_use]
+    pub fn credited_course(&self) -> Course {
+        let mut course = self.course.clone();
+        course.units_attempted = course.units_attempted.or(self.units);
+        course.units_earned = course.units_earned.or(self.units);
+        course
+    }
+}
+
+/// The kind of term a semester was.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermType {
+    /// A regular term of study.
+    #[default]
+    Academic,
+    /// A co-operative education work term.
+    CoOp,
+    /// A term spent on exchange or study abroad at another institution.
+    Exchange,
+}
+
+impl TermType {
+    /// The name of the term type as written in the output formats.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Academic => "academic",
+            Self::CoOp => "co_op",
+            Self::Exchange => "exchange",
+        }
+    }
+}
+
+/// An honour roll a student was named to for a term.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HonourRoll {
+    DeansHonourRoll,
+    PresidentsHonourRoll,
+}
+
+impl HonourRoll {
+    /// The name of the honour roll as written in the output formats.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DeansHonourRoll => "deans_honour_roll",
+            Self::PresidentsHonourRoll => "presidents_honour_roll",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Semester {
+    pub year: String,
+    pub term: String,
+    pub term_type: TermType,
+    /// The host institution of an exchange term.
+    pub host_institution: Option<String>,
+    /// The career of the program the term was taken in, e.g.
+    /// "Undergraduate" or "Graduate".
+    pub career: Option<String>,
+    /// The academic group of the program the term was taken in, e.g.
+    /// "Applied Sciences".
+    pub academic_group: Option<String>,
+    pub is_good_standing: bool,
+    /// The academic standing as printed for the term, e.g. "Good Standing"
+    /// or "Academic Probation".
+    pub standing: Option<String>,
+    /// The honour roll noted for the term, if any.
+    pub honour_roll: Option<HonourRoll>,
+    pub courses: Vec<Course>,
+    /// The term GPA computed from the courses using SFU's grade point scale.
+    pub gpa: Option<f64>,
+    /// The cumulative GPA over this and all earlier terms.
+    pub cgpa: Option<f64>,
+    /// The term GPA as printed on the transcript, for checking against `gpa`.
+    pub printed_gpa: Option<f64>,
+    /// The cumulative GPA as printed on the transcript.
+    pub printed_cgpa: Option<f64>,
+}
+
+/// The terms of an academic year, in calendar order.
+pub const TERMS: [&str; 3] = ["Spring", "Summer", "Fall"];
+
+/// A key ordering terms chronologically, e.g. `term_key("2019", "Fall")`.
+/// Unrecognized years and terms sort first.
+#[must_use]
+pub fn term_key(year: &str, term: &str) -> (u32, usize) {
+    let term_index = TERMS.iter().position(|t| *t == term).map_or(0, |i| i + 1);
+    (year.parse().unwrap_or(0), term_index)
+}
+
+impl Semester {
+    /// A key ordering semesters chronologically.
+    #[must_use]
+    pub fn term_key(&self) -> (u32, usize) {
+        term_key(&self.year, &self.term)
+    }
+
+    /// The course load of the term. Units that are not printed count as
+    /// zero.
+    #[must_use]
+    pub fn load(&self) -> TermLoad {
+        let courses = &self.courses;
+        // Summing from 0.0 rather than with sum() keeps empty terms at 0
+        // rather than -0.
+        let total = |units: fn(&Course) -> Option<f64>| {
+            courses
+                .iter()
+                .filter_map(units)
+                .fold(0.0, |total, u| total + u)
+        };
+        TermLoad {
+            courses: courses.len(),
+            units_attempted: total(|c| c.units_attempted),
+            units_earned: total(|c| c.units_earned),
+            withdrawals: courses
+                .iter()
+                .filter(|c| c.outcome == Outcome::Withdrawn)
+                .count(),
+        }
+    }
+}
+
+/// How much a student took on in a term, and how much of it they finished.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct TermLoad {
+    pub courses: usize,
+    pub units_attempted: f64,
+    pub units_earned: f64,
+    /// The courses withdrawn from, including under extenuating
+    /// circumstances.
+    pub withdrawals: usize,
+}
+
+/// A degree, diploma, or certificate awarded to the student.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Credential {
+    /// The credential as printed, e.g. "Bachelor of Science".
+    pub program: String,
+    /// The conferral date as printed on the transcript, when given.
+    pub conferred: Option<String>,
+}
+
+/// A milestone on the way to a credential, such as meeting the WQB
+/// requirements or completing the co-op designation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    /// The milestone as printed, e.g. "Writing Requirement Met".
+    pub name: String,
+    /// The year and term the milestone was completed in.
+    pub year: String,
+    pub term: String,
+}
+
+/// A free-text note on the transcript, such as a name change or the outcome
+/// of a grade appeal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    /// The year and term the note was made within, or `None` for notes on
+    /// the transcript as a whole.
+    pub year: Option<String>,
+    pub term: Option<String>,
+    pub text: String,
+}
+
+/// The units passed in one academic group, from the totals that end the
+/// transcript.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupUnits {
+    /// The academic group, e.g. "Applied Sciences".
+    pub group: String,
+    pub units: f64,
+}
+
+/// Text identifying the student, as printed at the top of the transcript.
+/// It is never written to any output, and is only read so that the output
+/// can be checked for it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: Option<String>,
+    pub birth_date: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StudentInfo {
+    pub id: String,
+    /// Every plan the student has been in, in the order they took effect.
+    pub plans: Vec<Plan>,
+    pub transfers: Vec<Transfer>,
+    pub semesters: Vec<Semester>,
+    pub credentials: Vec<Credential>,
+    /// The milestones completed, in the order listed.
+    pub milestones: Vec<Milestone>,
+    /// The notes on the transcript and its terms, in the order printed.
+    pub notes: Vec<Note>,
+    /// The units passed in each academic group, as totalled by the
+    /// transcript.
+    pub unit_totals: Vec<GroupUnits>,
+    /// Anomalies that were tolerated while parsing leniently.
+    pub warnings: Vec<String>,
+    #[serde(skip)]
+    pub identity: Identity,
+}
+
+impl StudentInfo {
+    /// Recomputes the grade points of every course and transfer credit
+    /// with the given scale. Term and cumulative GPAs are unaffected.
+    pub fn apply_grade_scale(&mut self, scale: &GradeScale) {
+        let courses = self
+            .transfers
+            .iter_mut()
+            .map(|transfer| &mut transfer.course)
+            .chain(self.semesters.iter_mut().flat_map(|s| s.courses.iter_mut()));
+        for course in courses {
+            course.grade_points = scale.points(&course.grade);
+        }
+    }
+
+    /// The units earned across every course and transfer credit, counting
+    /// only the latest attempt at a repeated course, as SFU does. Units that
+    /// are not printed count as zero.
+    #[must_use]
+    pub fn units_earned(&self) -> f64 {
+        let transfers = self
+            .transfers
+            .iter()
+            .map(|transfer| (&transfer.course, transfer.units));
+        self.semesters
+            .iter()
+            .flat_map(|semester| &semester.courses)
+            .map(|course| (course, course.units_earned))
+            .chain(transfers)
+            .filter(|(course, _)| course.is_latest_attempt)
+            .filter_map(|(_, units)| units)
+            .fold(0.0, |total, units| total + units)
+    }
+
+    /// Checks the units earned against the units passed by academic group
+    /// that end the transcript, which catches rows dropped while parsing.
+    /// Transcripts without totals pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the totals differ by more than 0.01 units.
+    pub fn check_unit_totals(&self) -> Result<(), TranscriptError> {
+        if self.unit_totals.is_empty() {
+            return Ok(());
+        }
+        let printed = self
+            .unit_totals
+            .iter()
+            .fold(0.0, |total, group| total + group.units);
+        let parsed = self.units_earned();
+        // The totals are printed to two places.
+        if (parsed - printed).abs() > 0.01 {
+            return Err(TranscriptError::UnitTotalMismatch { parsed, printed });
+        }
+        Ok(())
+    }
+
+    /// The terms whose computed term or cumulative GPA differs from the one
+    /// printed on the transcript by more than `tolerance`, as errors to
+    /// report. Terms missing either value are not compared. As printed GPAs
+    /// are rounded to two decimals, the tolerance should be at least 0.005.
+    #[must_use]
+    pub fn gpa_mismatches(&self, tolerance: f64) -> Vec<TranscriptError> {
+        let mut mismatches = Vec::new();
+        for semester in &self.semesters {
+            let pairs = [
+                ("term", semester.gpa, semester.printed_gpa),
+                ("cumulative", semester.cgpa, semester.printed_cgpa),
+            ];
+            for (kind, computed, printed) in pairs {
+                if let (Some(computed), Some(printed)) = (computed, printed) {
+                    if (computed - printed).abs() > tolerance {
+                        mismatches.push(TranscriptError::GpaMismatch {
+                            year: semester.year.clone(),
+                            term: semester.term.clone(),
+                            kind,
+                            computed,
+                            printed,
+                        });
+                    }
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Keeps only the courses and transfer credits in the given subjects,
+    /// ignoring case. Terms are kept even when none of their courses are,
+    /// and their GPAs still cover every course taken.
+    pub fn retain_subjects<S: AsRef<str>>(&mut self, subjects: &[S]) {
+        let keep = |course: &Course| {
+            subjects
+                .iter()
+                .any(|subject| subject.as_ref().eq_ignore_ascii_case(&course.subject))
+        };
+        self.transfers.retain(|transfer| keep(&transfer.course));
+        for semester in &mut self.semesters {
+            semester.courses.retain(keep);
+        }
+    }
+
+    /// Keeps only the terms whose [`term_key`] falls within `range`, such as
+    /// `term_key("2019", "Fall")..=term_key("2023", "Summer")`. Cumulative
+    /// GPAs still cover the terms before the range.
+    pub fn retain_terms(&mut self, range: impl RangeBounds<(u32, usize)>) {
+        self.semesters
+            .retain(|semester| range.contains(&semester.term_key()));
+    }
+
+    /// The plan the student was admitted to.
+    #[must_use]
+    pub fn first_plan(&self) -> Option<&Plan> {
+        self.plans.first()
+    }
+
+    /// The most recent plan the student is in.
+    #[must_use]
+    pub fn current_plan(&self) -> Option<&Plan> {
+        self.plans.last()
+    }
+}
+
+/// Whether a degree requirement has been met.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementStatus {
+    Satisfied,
+    NotSatisfied,
+}
+
+impl RequirementStatus {
+    /// The name of the status as written in the output formats.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Satisfied => "satisfied",
+            Self::NotSatisfied => "not_satisfied",
+        }
+    }
+}
+
+/// A requirement block of a degree progress report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Requirement {
+    pub name: String,
+    pub status: RequirementStatus,
+    pub units_required: Option<f64>,
+    pub units_used: Option<f64>,
+    pub units_needed: Option<f64>,
+}
+
+/// The information extracted from an academic advisement (degree progress)
+/// report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DegreeProgress {
+    pub id: String,
+    pub program: Option<String>,
+    pub requirements: Vec<Requirement>,
+    /// Anomalies that were tolerated while parsing leniently.
+    pub warnings: Vec<String>,
+}
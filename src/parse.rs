@@ -0,0 +1,945 @@
+use std::collections::HashMap;
+
+use crate::chunk::Chunk;
+use crate::error::{Diagnostics, TranscriptError};
+use crate::grades;
+use crate::model::{
+    Course, Credential, Designation, GroupUnits, HonourRoll, Identity, Milestone, Note, Plan,
+    Semester, StudentInfo, TermType, Transfer,
+};
+use crate::vocabulary::Vocabulary;
+
+fn process_plan(
+    plan_chunk: &Chunk,
+    effective: Option<(&str, &str)>,
+) -> Result<Plan, TranscriptError> {
+    if let Chunk::Chunks(v) = plan_chunk {
+        // The standard plan IDs seem to be in the second to last chunk of
+        // the block.
+        if let Some(s) = v.len().checked_sub(2).and_then(|i| v[i].get_string()) {
+            return Ok(Plan {
+                name: s.to_string(),
+                effective_year: effective.map(|(year, _)| year.to_string()),
+                effective_term: effective.map(|(_, term)| term.to_string()),
+            });
+        }
+    }
+    Err(TranscriptError::MalformedSection("Plan"))
+}
+
+// The plan the student was admitted to is listed before the term history.
+// Plan changes are recorded by another plan block within the term they take
+// effect in.
+fn process_plans(
+    chunks: &[Chunk],
+    plan_index: usize,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Plan>, TranscriptError> {
+    let marker = Chunk::String(PLAN_MARKER.to_string());
+    let mut plans = vec![process_plan(
+        chunks
+            .get(plan_index)
+            .ok_or(TranscriptError::MissingSection("Plan"))?,
+        None,
+    )?];
+
+    let mut current_term = None;
+    for (i, chunk) in chunks.iter().enumerate().skip(plan_index + 1) {
+        if let Some(year_term) = chunk.get_string().and_then(get_year_term) {
+            current_term = Some(year_term);
+        } else if chunk == &marker && current_term.is_some() {
+            let Some(plan_chunk) = chunks.get(i + 1) else {
+                diagnostics.report(TranscriptError::MalformedSection("Plan"))?;
+                continue;
+            };
+            match process_plan(plan_chunk, current_term) {
+                // Continuing in the same plan is not a change.
+                Ok(plan) if plans.last().is_some_and(|last| last.name == plan.name) => {}
+                Ok(plan) => plans.push(plan),
+                Err(err) => diagnostics.report(err)?,
+            }
+        }
+    }
+    Ok(plans)
+}
+
+/// The string columns of a row along with the WQB designations that were
+/// taken out of it.
+struct Row<'a> {
+    cells: Vec<&'a str>,
+    designations: Vec<Designation>,
+}
+
+impl<'a> Row<'a> {
+    // WQB Qualifiers create extra columns in any row, so identifying and
+    // filtering them (and any other `skip`ped strings) evens out the data.
+    fn new(chunks: &'a [Chunk], vocabulary: &Vocabulary, skip: impl Fn(&str) -> bool) -> Self {
+        let mut row = Row {
+            cells: Vec::new(),
+            designations: Vec::new(),
+        };
+        for s in chunks.iter().filter_map(|c| c.get_string()) {
+            if vocabulary.is_tag(s) {
+                row.designations.extend(vocabulary.designations(s));
+            } else if !skip(s) {
+                row.cells.push(s);
+            }
+        }
+        row
+    }
+}
+
+pub(crate) const NOTE_LABELS: [&str; 2] = ["Note:", "Comment:"];
+
+// Notes start with a label and may run over several strings, which are
+// joined. They can appear anywhere, including between the rows of a table,
+// so they are taken out before any rows are matched up with columns.
+fn note_text(chunk: &Chunk) -> Option<String> {
+    let strings = chunk.strings();
+    let (first, rest) = strings.split_first()?;
+    let body = NOTE_LABELS
+        .iter()
+        .find_map(|label| first.strip_prefix(label))?;
+    let text = std::iter::once(body)
+        .chain(rest.iter().copied())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+fn is_note(chunk: &Chunk) -> bool {
+    note_text(chunk).is_some()
+}
+
+// Notes within the term history belong to the term they follow.
+fn process_notes(chunks: &[Chunk], program_index: usize, end_index: usize) -> Vec<Note> {
+    let mut current_term = None;
+    let mut notes = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        if let Some(year_term) = chunk.get_string().and_then(get_year_term) {
+            if (program_index..end_index).contains(&i) {
+                current_term = Some(year_term);
+            }
+        } else if let Some(text) = note_text(chunk) {
+            let term = current_term.filter(|_| (program_index..end_index).contains(&i));
+            notes.push(Note {
+                year: term.map(|(year, _)| year.to_string()),
+                term: term.map(|(_, term)| term.to_string()),
+                text,
+            });
+        }
+    }
+    notes
+}
+
+fn is_perm_dt(s: &str) -> bool {
+    s == "Perm.Dt:" || s.split('-').count() == 3
+}
+
+// NOTE: The transfer and semester processing still fails fast on anything
+// unexpected, as the data cleaning involves some reverse engineering and
+// irregularities help to identify errors. They are reported as errors rather
+// than panics so that batch runs can recover and move on.
+
+pub(crate) const COOP_SUBJECT: &str = "COOP";
+const COOP_TITLES: [&[&str]; 3] = [&["co-op"], &["coop"], &["work", "term"]];
+
+// Co-op work terms are taken as courses under the co-op subject or as
+// practicum courses within a department. Titles are matched by whole words,
+// so courses on, say, cooperative games are not mistaken for work terms.
+fn is_coop_course(subject: &str, title: &str) -> bool {
+    let title = title.to_ascii_lowercase();
+    let words: Vec<&str> = title
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .filter(|word| !word.is_empty())
+        .collect();
+    subject == COOP_SUBJECT
+        || COOP_TITLES
+            .iter()
+            .any(|pattern| words.windows(pattern.len()).any(|w| w == *pattern))
+}
+
+fn describe_row(row: &[&str]) -> String {
+    row.join(" | ")
+}
+
+// Sanity check that the grades are in the grade vocabulary to help identify
+// any irregularities in the PDF stream while reverse engineering. Returns
+// whether the grade is known.
+fn check_grade(
+    grade: &str,
+    row: &[&str],
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<bool, TranscriptError> {
+    if vocabulary.is_grade(grade) {
+        return Ok(true);
+    }
+    diagnostics.report_grade(TranscriptError::UnknownGrade {
+        value: grade.to_string(),
+        row: describe_row(row),
+    })?;
+    Ok(false)
+}
+
+// The column that a page break adds to the transfer row it splits.
+pub(crate) const PAGE_BREAK_TAG: &str = "SFUSR";
+
+fn process_transfers(
+    chunks: &[Chunk],
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Transfer>, TranscriptError> {
+    // Transform the Chunk sequence into a list of string rows.
+    // We can skip over the initial sequence of single string elements,
+    // as they contain no transfer information.
+    let mut sources = chunks
+        .iter()
+        .filter(|c| c.is_chunks() && !is_note(c))
+        .filter_map(|c| c.get_contained())
+        .map(|slice| Row::new(slice, vocabulary, |_| false))
+        .collect::<Vec<Row>>();
+
+    // The first row includes a column from the header but actually needs
+    // another spacer element in order to align with the other rows nicely.
+    match sources.first_mut() {
+        Some(first) => first.cells.insert(0, ""),
+        None => return Ok(Vec::new()),
+    }
+
+    // Page breaks add a column and split a row into two.
+    let mut i = 0;
+    while i + 1 < sources.len() {
+        if sources[i]
+            .cells
+            .last()
+            .is_some_and(|last| last.starts_with(PAGE_BREAK_TAG))
+        {
+            sources[i].cells.pop();
+            let next = sources.remove(i + 1);
+            sources[i].cells.extend_from_slice(&next.cells);
+            sources[i].designations.extend(next.designations);
+        }
+        i += 1;
+    }
+
+    let mut transfers = Vec::with_capacity(chunks.len());
+
+    // By default, the rows are ragged, and individual transfer credits are
+    // each split over 2 rows. Extract the course and institution if possible
+    // to create `Transfer`s.
+    i = 0;
+    while i + 1 < sources.len() {
+        // Institution names are on the following rows when present.
+        // Lines with institution names have 10 columns.
+        let next = &sources[i + 1].cells;
+        let school = if [10, 2].contains(&next.len()) {
+            Some(next[1].to_string())
+        } else {
+            None
+        };
+        let Row {
+            cells: row,
+            designations,
+        } = &sources[i];
+        let course_offset = usize::from(row.len() == 10);
+        let Some(columns) = row.get(course_offset..=course_offset + 6) else {
+            // Rows that are too short to hold a course are skipped when
+            // parsing leniently.
+            diagnostics.report(TranscriptError::MalformedRow {
+                section: "Transfer",
+                row: describe_row(row),
+            })?;
+            i += 1;
+            continue;
+        };
+        let grade_valid = check_grade(columns[6], row, vocabulary, diagnostics)?;
+        transfers.push(Transfer {
+            course: Course {
+                subject: columns[1].to_string(),
+                id: columns[2].to_string(),
+                title: columns[3].to_string(),
+                grade: columns[6].to_string(),
+                grade_points: vocabulary.grade_points(columns[6]),
+                outcome: vocabulary.outcome(columns[6]),
+                units_attempted: None,
+                units_earned: None,
+                designations: designations.clone(),
+                attempt: 1,
+                is_latest_attempt: true,
+                grade_valid,
+            },
+            school,
+            // The units granted precede the grade, like the earned units of
+            // term rows.
+            units: columns[5].parse().ok(),
+            equivalent: None,
+        });
+        i += 1;
+    }
+
+    Ok(transfers)
+}
+
+pub(crate) const STANDING_LABEL: &str = "Academic Standing:";
+
+// The academic standing of a term is given by a labelled line within the
+// term block. The standing itself may share a string with the label or
+// follow it as a separate column. Other labelled lines follow the same form.
+fn find_labelled(block: &[Chunk], label: &str) -> Option<String> {
+    let strings: Vec<&str> = block.iter().flat_map(Chunk::strings).collect();
+    strings.iter().enumerate().find_map(|(i, s)| {
+        let rest = s.strip_prefix(label)?.trim();
+        if rest.is_empty() {
+            strings.get(i + 1).map(|next| (*next).to_string())
+        } else {
+            Some(rest.to_string())
+        }
+    })
+}
+
+fn find_standing(block: &[Chunk]) -> Option<String> {
+    find_labelled(block, STANDING_LABEL)
+}
+
+pub(crate) const CAREER_LABEL: &str = "Career:";
+pub(crate) const GROUP_LABEL: &str = "Academic Group:";
+
+// The career and academic group a program block belongs to, labelled before
+// its first term.
+struct ProgramContext {
+    career: Option<String>,
+    academic_group: Option<String>,
+}
+
+impl ProgramContext {
+    fn find(preamble: &[Chunk]) -> Self {
+        Self {
+            career: find_labelled(preamble, CAREER_LABEL),
+            academic_group: find_labelled(preamble, GROUP_LABEL),
+        }
+    }
+}
+
+// Course rows start with the code of their career.
+fn career_name(code: &str) -> Option<&'static str> {
+    match code {
+        "UGRD" => Some("Undergraduate"),
+        "GRAD" => Some("Graduate"),
+        _ => None,
+    }
+}
+
+pub(crate) const HOST_LABEL: &str = "Host Institution:";
+const EXCHANGE_NOTES: [&str; 3] = ["exchange program", "exchange term", "study abroad"];
+
+// Exchange terms name the host institution, or at least note the exchange,
+// within the term block.
+fn is_exchange(block: &[Chunk]) -> bool {
+    block.iter().flat_map(Chunk::strings).any(|s| {
+        let s = s.to_ascii_lowercase();
+        s.starts_with(&HOST_LABEL.to_ascii_lowercase())
+            || EXCHANGE_NOTES.iter().any(|note| s.contains(note))
+    })
+}
+
+// Courses taken on exchange are granted as block transfer credit, with rows
+// of only the subject, number, title, units, and grade.
+fn is_block_credit(cells: &[&str], vocabulary: &Vocabulary) -> bool {
+    cells.len() == 5 && vocabulary.is_grade(cells[4])
+}
+
+fn is_good_standing(standing: &str) -> bool {
+    standing.contains("Good")
+}
+
+// Honour rolls are noted by a line within the term block. The President's
+// Honour Roll is checked first as it is the higher distinction.
+fn find_honour_roll(block: &[Chunk]) -> Option<HonourRoll> {
+    let notes: Vec<String> = block
+        .iter()
+        .flat_map(Chunk::strings)
+        .map(|s| s.to_ascii_lowercase().replace("honor", "honour"))
+        .collect();
+    [
+        ("president's honour roll", HonourRoll::PresidentsHonourRoll),
+        ("dean's honour roll", HonourRoll::DeansHonourRoll),
+    ]
+    .into_iter()
+    .find(|(label, _)| notes.iter().any(|note| note.contains(label)))
+    .map(|(_, honour_roll)| honour_roll)
+}
+
+// The term and cumulative GPAs are printed after the courses of a term, each
+// following its label, as in "Term GPA: 3.50 Cum GPA: 3.21".
+fn find_printed_gpas(block: &[Chunk]) -> (Option<f64>, Option<f64>) {
+    let strings: Vec<&str> = block.iter().flat_map(Chunk::strings).collect();
+    let mut gpas = (None, None);
+    for pair in strings.windows(2) {
+        let (label, value) = (pair[0].trim(), pair[1].trim());
+        if !label.ends_with("GPA:") {
+            continue;
+        }
+        if label.starts_with("Cum") {
+            gpas.1 = gpas.1.or(value.parse().ok());
+        } else {
+            gpas.0 = gpas.0.or(value.parse().ok());
+        }
+    }
+    gpas
+}
+
+fn get_year_term(s: &str) -> Option<(&str, &str)> {
+    let mut pieces = s.split_ascii_whitespace();
+    match (pieces.next(), pieces.next()) {
+        (Some(year), Some(term)) if ["Spring", "Summer", "Fall"].contains(&term) => {
+            Some((year, term))
+        }
+        _ => None,
+    }
+}
+
+fn block_credit(row: &Row, vocabulary: &Vocabulary) -> Course {
+    let r = &row.cells;
+    let units = r[3].parse().ok();
+    Course {
+        subject: r[0].to_string(),
+        id: r[1].to_string(),
+        title: r[2].to_string(),
+        grade: r[4].to_string(),
+        grade_points: vocabulary.grade_points(r[4]),
+        outcome: vocabulary.outcome(r[4]),
+        units_attempted: units,
+        units_earned: units,
+        designations: row.designations.clone(),
+        attempt: 1,
+        is_latest_attempt: true,
+        // Only rows with a known grade are taken as block credit.
+        grade_valid: true,
+    }
+}
+
+// Transcripts covering more than one career have a program block for each,
+// all starting with the program marker.
+fn process_semesters(
+    chunks: &[Chunk],
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Semester>, TranscriptError> {
+    let mut semesters = Vec::new();
+    for block in chunks.chunk_by(|_, b| b.get_string() != Some(PROGRAM_MARKER)) {
+        let preamble = block
+            .iter()
+            .position(|c| c.get_string().and_then(get_year_term).is_some())
+            .unwrap_or(block.len());
+        let context = ProgramContext::find(&block[..preamble]);
+        semesters.extend(process_program(block, &context, vocabulary, diagnostics)?);
+    }
+    compute_cumulative_gpas(&mut semesters);
+    Ok(semesters)
+}
+
+fn process_program(
+    chunks: &[Chunk],
+    context: &ProgramContext,
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Semester>, TranscriptError> {
+    let grouped = chunks
+        .chunk_by(|_, b| !matches!(b, Chunk::String(s) if get_year_term(s).is_some()))
+        .skip(1)
+        .filter(|s| s.len() >= 2)
+        .filter_map(|s| {
+            let exchange = is_exchange(&s[1..]);
+            Some((
+                get_year_term(s[0].get_string()?)?,
+                s[1..]
+                    .iter()
+                    .filter(|c| !is_note(c))
+                    // Rows are ragged, so map elements to strings and filter out
+                    // conditional elements like qualifiers to make columns align.
+                    .filter_map(|c| c.get_contained())
+                    .map(|row| Row::new(row, vocabulary, is_perm_dt))
+                    // Exclude rows for GPA. Courses without grades are kept
+                    // as in progress.
+                    .filter(|Row { cells: v, .. }| {
+                        v.first().is_some_and(|first| !first.ends_with("GPA:"))
+                            && (6 < v.len() || (exchange && is_block_credit(v, vocabulary)))
+                    })
+                    .collect::<Vec<_>>(),
+                &s[1..],
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    grouped
+        .iter()
+        .filter(|(_, rows, _)| !rows.is_empty())
+        .map(|((year, term), rows, block)| {
+            let standing = find_standing(block);
+            let courses = rows
+                .iter()
+                // Checking the grades helps to sanity check the correctness
+                // of the extraction because of the reverse engineered format.
+                .map(|row| {
+                    if row.cells.len() < 7 {
+                        return Ok(block_credit(row, vocabulary));
+                    }
+                    let r = &row.cells;
+                    // Co-op work terms are often recorded without a
+                    // conventional grade.
+                    let grade_valid = r[6].is_empty()
+                        || is_coop_course(r[1], r[3])
+                        || check_grade(r[6], r, vocabulary, diagnostics)?;
+                    Ok(Course {
+                        subject: r[1].to_string(),
+                        id: r[2].to_string(),
+                        title: r[3].to_string(),
+                        grade: r[6].to_string(),
+                        grade_points: vocabulary.grade_points(r[6]),
+                        outcome: vocabulary.outcome(r[6]),
+                        // Attempted and earned units follow the course title.
+                        units_attempted: r[4].parse().ok(),
+                        units_earned: r[5].parse().ok(),
+                        designations: row.designations.clone(),
+                        attempt: 1,
+                        is_latest_attempt: true,
+                        grade_valid,
+                    })
+                })
+                .collect::<Result<Vec<_>, TranscriptError>>()?;
+            let host_institution = find_labelled(block, HOST_LABEL);
+            let term_type = if host_institution.is_some() || is_exchange(block) {
+                TermType::Exchange
+            } else if courses.iter().any(|c| is_coop_course(&c.subject, &c.title)) {
+                TermType::CoOp
+            } else {
+                TermType::Academic
+            };
+            let printed = find_printed_gpas(block);
+            let career = context.career.clone().or_else(|| {
+                rows.iter()
+                    .filter(|row| row.cells.len() >= 7)
+                    .find_map(|row| career_name(row.cells[0]))
+                    .map(str::to_string)
+            });
+            Ok(Semester {
+                year: (*year).to_string(),
+                term: (*term).to_string(),
+                term_type,
+                host_institution,
+                career,
+                academic_group: context.academic_group.clone(),
+                // Terms without a recorded standing are assumed to be in
+                // good standing.
+                is_good_standing: standing.as_deref().is_none_or(is_good_standing),
+                honour_roll: find_honour_roll(block),
+                standing,
+                gpa: grades::gpa(&courses),
+                cgpa: None,
+                printed_gpa: printed.0,
+                printed_cgpa: printed.1,
+                courses,
+            })
+        })
+        .collect()
+}
+
+// The cumulative GPA at each term covers every course up to and including
+// that term. As with the units earned, only the latest attempt at a repeated
+// course counts, so an attempt is superseded from the term it is retaken in.
+pub(crate) fn compute_cumulative_gpas(semesters: &mut [Semester]) {
+    for i in 0..semesters.len() {
+        let courses: Vec<&Course> = semesters[..=i].iter().flat_map(|s| &s.courses).collect();
+        let latest: HashMap<(&str, &str), usize> = courses
+            .iter()
+            .enumerate()
+            .map(|(index, c)| ((c.subject.as_str(), c.id.as_str()), index))
+            .collect();
+        let cgpa = grades::gpa(
+            courses
+                .iter()
+                .enumerate()
+                .filter(|(index, c)| latest[&(c.subject.as_str(), c.id.as_str())] == *index)
+                .map(|(_, c)| *c),
+        );
+        semesters[i].cgpa = cgpa;
+    }
+}
+
+// Courses taken more than once are matched by subject and number. Transfer
+// credits count as the earliest attempts, followed by terms in order.
+pub(crate) fn number_attempts(transfers: &mut [Transfer], semesters: &mut [Semester]) {
+    let mut courses: Vec<&mut Course> = transfers
+        .iter_mut()
+        .map(|transfer| &mut transfer.course)
+        .chain(semesters.iter_mut().flat_map(|s| s.courses.iter_mut()))
+        .collect();
+    let mut attempts: HashMap<(String, String), u32> = HashMap::new();
+    for course in &mut courses {
+        let count = attempts
+            .entry((course.subject.clone(), course.id.clone()))
+            .or_default();
+        *count += 1;
+        course.attempt = *count;
+    }
+    for course in &mut courses {
+        course.is_latest_attempt =
+            attempts[&(course.subject.clone(), course.id.clone())] == course.attempt;
+    }
+}
+
+const CONFERRED_LABEL: &str = "conferred";
+
+// Awarded credentials are listed after the term history, one per line, as
+// the credential followed by its conferral date, e.g.
+// "Bachelor of Science, conferred 2020-06-12". The date may also be split
+// into the following string.
+fn process_credentials(chunks: &[Chunk]) -> Vec<Credential> {
+    let strings: Vec<&str> = chunks.iter().flat_map(Chunk::strings).collect();
+    strings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| {
+            let position = s.to_ascii_lowercase().find(CONFERRED_LABEL)?;
+            let program = s[..position].trim().trim_end_matches(',').trim();
+            let date = s[position + CONFERRED_LABEL.len()..]
+                .trim()
+                .trim_start_matches(':')
+                .trim();
+            let program = if program.is_empty() {
+                strings.get(i.checked_sub(1)?)?.trim()
+            } else {
+                program
+            };
+            let conferred = if date.is_empty() {
+                strings.get(i + 1).map(|next| next.trim().to_string())
+            } else {
+                Some(date.to_string())
+            };
+            Some(Credential {
+                program: program.to_string(),
+                conferred,
+            })
+        })
+        .collect()
+}
+
+// The units passed in each academic group are listed under the end marker,
+// one row per group, as the group followed by its units, e.g.
+// "Applied Sciences 45.00". The rows end at the next heading, such as the
+// milestones.
+fn process_unit_totals(chunks: &[Chunk]) -> Vec<GroupUnits> {
+    let Some(start) = chunks
+        .iter()
+        .position(|c| c.get_string() == Some(END_MARKER))
+    else {
+        return Vec::new();
+    };
+    chunks[start + 1..]
+        .iter()
+        .take_while(|chunk| chunk.is_chunks())
+        .filter_map(|row| match row.strings().as_slice() {
+            [group, units] => Some(GroupUnits {
+                group: group.trim().to_string(),
+                units: units.trim().parse().ok()?,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// Milestones are listed after the end marker under their own heading, one
+// row per milestone, as the milestone followed by the term it was completed
+// in, e.g. "Writing Requirement Met 2019 Fall".
+fn process_milestones(chunks: &[Chunk]) -> Vec<Milestone> {
+    let Some(start) = chunks
+        .iter()
+        .position(|c| c.get_string() == Some(MILESTONE_MARKER))
+    else {
+        return Vec::new();
+    };
+    chunks[start + 1..]
+        .iter()
+        .filter(|chunk| chunk.is_chunks())
+        .filter_map(|row| match row.strings().as_slice() {
+            [name, completed] => {
+                let (year, term) = get_year_term(completed)?;
+                Some(Milestone {
+                    name: name.trim().to_string(),
+                    year: year.to_string(),
+                    term: term.to_string(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) const NAME_LABEL: &str = "Name:";
+pub(crate) const BIRTH_DATE_LABELS: [&str; 3] = ["Birth Date:", "Date of Birth:", "Birthdate:"];
+
+// The name and birth date are labelled lines in the header of the first
+// page, before the plan.
+fn process_identity(header: &[Chunk]) -> Identity {
+    Identity {
+        name: find_labelled(header, NAME_LABEL),
+        birth_date: BIRTH_DATE_LABELS
+            .iter()
+            .find_map(|label| find_labelled(header, label)),
+    }
+}
+
+/// Reads the name and birth date from the header of the combined `Chunk`s
+/// of a transcript, up to its plan, even when the rest of it does not parse.
+#[must_use]
+pub fn header_identity(chunks: &[Chunk]) -> Identity {
+    let plan = Chunk::String(PLAN_MARKER.to_string());
+    let end = chunks
+        .iter()
+        .position(|c| c == &plan)
+        .unwrap_or(chunks.len());
+    process_identity(&chunks[..end])
+}
+
+pub(crate) const PLAN_MARKER: &str = "Plan";
+pub(crate) const TRANSFER_MARKER: &str = "TRANSFER COURSES";
+pub(crate) const PROGRAM_MARKER: &str = "Program:";
+pub(crate) const END_MARKER: &str = "TOTAL UNITS PASSED BY ACADEMIC GROUP";
+pub(crate) const MILESTONE_MARKER: &str = "MILESTONES";
+
+/// Returns the names of the required sections whose markers do not appear
+/// anywhere in the combined `Chunk`s.
+#[must_use]
+pub fn missing_sections(chunks: &[Chunk]) -> Vec<&'static str> {
+    [
+        (PLAN_MARKER, "Plan"),
+        (PROGRAM_MARKER, "Program"),
+        (END_MARKER, "End"),
+    ]
+    .into_iter()
+    .filter(|(marker, _)| !chunks.iter().any(|c| c.get_string() == Some(marker)))
+    .map(|(_, section)| section)
+    .collect()
+}
+
+/// Extracts the structured student information from the combined, simplified
+/// `Chunk`s of a transcript.
+///
+/// # Errors
+///
+/// Returns an error if any of the required transcript sections are missing or
+/// contain rows that do not match the expected layout.
+pub fn process_chunks(chunks: &[Chunk]) -> Result<StudentInfo, TranscriptError> {
+    process_chunks_with(chunks, &mut Diagnostics::default())
+}
+
+/// Extracts the structured student information like [`process_chunks`],
+/// reporting recoverable anomalies through `diagnostics`. Any warnings
+/// recorded, including those from earlier stages, are attached to the result.
+///
+/// # Errors
+///
+/// Returns an error if any of the required transcript sections are missing,
+/// or if an anomaly is found and `diagnostics` is not lenient.
+pub fn process_chunks_with(
+    chunks: &[Chunk],
+    diagnostics: &mut Diagnostics,
+) -> Result<StudentInfo, TranscriptError> {
+    process_chunks_using(chunks, &Vocabulary::default(), diagnostics)
+}
+
+/// Extracts the structured student information like
+/// [`process_chunks_with`], recognizing the grades and tags of `vocabulary`
+/// instead of SFU's.
+///
+/// # Errors
+///
+/// Returns an error if any of the required transcript sections are missing,
+/// or if an anomaly is found and `diagnostics` is not lenient.
+pub fn process_chunks_using(
+    chunks: &[Chunk],
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<StudentInfo, TranscriptError> {
+    fn find_index(
+        chunks: &[Chunk],
+        start: usize,
+        marker: &str,
+        section: &'static str,
+    ) -> Result<usize, TranscriptError> {
+        let marker_chunk = Chunk::String(marker.to_string());
+        chunks
+            .get(start..)
+            .and_then(|rest| rest.iter().position(|c| c == &marker_chunk))
+            .map(|position| start + position)
+            .ok_or(TranscriptError::MissingSection(section))
+    }
+
+    let plan_index = 1 + find_index(chunks, 0, PLAN_MARKER, "Plan")?;
+
+    // This section is optional, so errors are nonfatal
+    let transfer_index = find_index(chunks, plan_index, TRANSFER_MARKER, "Transfer").ok();
+
+    let program_index = find_index(chunks, plan_index, PROGRAM_MARKER, "Program")?;
+
+    let end_index = find_index(chunks, program_index, END_MARKER, "End")?;
+
+    let id = chunks
+        .len()
+        .checked_sub(3)
+        .and_then(|i| chunks[i].get_string())
+        .ok_or(TranscriptError::MalformedSection("Student ID"))?;
+
+    let mut transfers = match transfer_index {
+        Some(i) if i < program_index => {
+            process_transfers(&chunks[i..program_index], vocabulary, diagnostics)?
+        }
+        _ => Vec::new(),
+    };
+    let mut semesters =
+        process_semesters(&chunks[program_index..end_index], vocabulary, diagnostics)?;
+    number_attempts(&mut transfers, &mut semesters);
+
+    let mut student = StudentInfo {
+        id: id.to_string(),
+        plans: process_plans(&chunks[..end_index], plan_index, diagnostics)?,
+        transfers,
+        semesters,
+        credentials: process_credentials(&chunks[end_index..]),
+        milestones: process_milestones(&chunks[end_index..]),
+        notes: process_notes(chunks, program_index, end_index),
+        unit_totals: process_unit_totals(&chunks[end_index..]),
+        warnings: Vec::new(),
+        identity: process_identity(&chunks[..plan_index]),
+    };
+    if let Err(err) = student.check_unit_totals() {
+        diagnostics.report(err)?;
+    }
+    student.warnings = diagnostics.warnings().to_vec();
+    Ok(student)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::sample::Index;
+
+    use super::*;
+
+    fn cell() -> impl Strategy<Value = String> {
+        let vocabulary = Vocabulary::default();
+        "[A-Za-z0-9.:-]{1,8}"
+            .prop_filter("a column rather than a tag", move |s| !vocabulary.is_tag(s))
+    }
+
+    // Tags and nested chunks, neither of which is ever a column.
+    fn extra() -> impl Strategy<Value = Chunk> {
+        let vocabulary = Vocabulary::default();
+        let qualifiers: Vec<String> = vocabulary.qualifiers.into_keys().collect();
+        let breadth_tags: Vec<String> = vocabulary.breadth_tags.into_keys().collect();
+        prop_oneof![
+            prop::sample::select(qualifiers).prop_map(Chunk::String),
+            prop::sample::select(breadth_tags).prop_map(Chunk::String),
+            prop::collection::vec(cell().prop_map(Chunk::String), 0..3).prop_map(Chunk::Chunks),
+        ]
+    }
+
+    fn with_extras(cells: &[String], extras: Vec<(Index, Chunk)>) -> Vec<Chunk> {
+        let mut row: Vec<Chunk> = cells.iter().cloned().map(Chunk::String).collect();
+        for (index, extra) in extras {
+            let at = index.index(row.len() + 1);
+            row.insert(at, extra);
+        }
+        row
+    }
+
+    proptest! {
+        #[test]
+        fn tags_never_reorder_columns(
+            cells in prop::collection::vec(cell(), 0..12),
+            extras in prop::collection::vec((any::<Index>(), extra()), 0..6),
+        ) {
+            let row = with_extras(&cells, extras);
+            prop_assert_eq!(Row::new(&row, &Vocabulary::default(), |_| false).cells, cells);
+        }
+
+        #[test]
+        fn every_tag_is_a_designation(
+            cells in prop::collection::vec(cell(), 0..12),
+            extras in prop::collection::vec((any::<Index>(), extra()), 0..6),
+        ) {
+            let row = with_extras(&cells, extras);
+            let vocabulary = Vocabulary::default();
+            let expected: Vec<Designation> = row
+                .iter()
+                .filter_map(Chunk::get_string)
+                .flat_map(|s| vocabulary.designations(s))
+                .collect();
+            prop_assert_eq!(Row::new(&row, &vocabulary, |_| false).designations, expected);
+        }
+
+        #[test]
+        fn skipping_keeps_the_remaining_order(
+            cells in prop::collection::vec(cell(), 0..12),
+            extras in prop::collection::vec((any::<Index>(), extra()), 0..6),
+            skipped in prop::collection::vec("[A-Za-z0-9.:-]{1,8}", 0..4),
+        ) {
+            let row = with_extras(&cells, extras);
+            let skip = |s: &str| skipped.iter().any(|k| k == s);
+            let expected: Vec<&str> = cells.iter().map(String::as_str).filter(|s| !skip(s)).collect();
+            prop_assert_eq!(Row::new(&row, &Vocabulary::default(), skip).cells, expected);
+        }
+    }
+
+    fn row(cells: &[&str]) -> Chunk {
+        Chunk::Chunks(
+            cells
+                .iter()
+                .map(|c| Chunk::String((*c).to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn coop_titles_match_whole_words() {
+        assert!(is_coop_course(COOP_SUBJECT, "Practicum I"));
+        assert!(is_coop_course("CMPT", "Co-op Practicum"));
+        assert!(is_coop_course("ENSC", "Work Term 2"));
+        assert!(!is_coop_course("ECON", "Cooperative Game Theory"));
+        assert!(!is_coop_course("EDUC", "Co-operative Learning"));
+        assert!(!is_coop_course("CMPT", "Networked Work and Terminals"));
+    }
+
+    #[test]
+    fn unit_totals_end_at_the_next_heading() {
+        let chunks = [
+            row(&["Total", "99.00"]),
+            Chunk::String(END_MARKER.to_string()),
+            row(&["Applied Sciences", "18.00"]),
+            row(&["Science", "54.00"]),
+            Chunk::String(MILESTONE_MARKER.to_string()),
+            row(&["Units Completed", "72.00"]),
+        ];
+        let totals = process_unit_totals(&chunks);
+        let groups: Vec<(&str, f64)> = totals.iter().map(|t| (t.group.as_str(), t.units)).collect();
+        assert_eq!(groups, [("Applied Sciences", 18.0), ("Science", 54.0)]);
+        assert!(process_unit_totals(&chunks[2..]).is_empty());
+    }
+
+    #[test]
+    fn the_header_identity_ends_at_the_plan() {
+        let chunks = [
+            Chunk::String("Name: Doe, Jane".to_string()),
+            row(&["Birth Date:", "1999-01-31"]),
+            Chunk::String(PLAN_MARKER.to_string()),
+            Chunk::String("Name: Plan Name".to_string()),
+        ];
+        let identity = header_identity(&chunks);
+        assert_eq!(identity.name.as_deref(), Some("Doe, Jane"));
+        assert_eq!(identity.birth_date.as_deref(), Some("1999-01-31"));
+        assert_eq!(header_identity(&chunks[2..]), Identity::default());
+    }
+}
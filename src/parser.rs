@@ -0,0 +1,144 @@
+//! The extension point for institution-specific transcript layouts. The PDF
+//! chunk extraction is shared, while each [`TranscriptParser`] decides
+//! whether it recognizes a document and how to turn its chunks into a
+//! [`StudentInfo`].
+
+use crate::chunk::Chunk;
+use crate::error::{Diagnostics, TranscriptError};
+use crate::model::StudentInfo;
+use crate::parse;
+use crate::pdf::{self, TranscriptLayout};
+use crate::vocabulary::Vocabulary;
+
+pub trait TranscriptParser {
+    /// A short name for the institution or layout handled by the parser.
+    fn name(&self) -> &'static str;
+
+    /// Whether the simplified `Chunk`s of the pages look like a transcript
+    /// this parser understands.
+    fn detect(&self, page_chunks: &[Vec<Chunk>]) -> bool;
+
+    /// Removes page decorations such as headers and footers and joins the
+    /// pages into the single sequence of `Chunk`s expected by `parse`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pages do not have the expected decorations
+    /// and `diagnostics` is not lenient.
+    fn combine(
+        &self,
+        page_chunks: Vec<Vec<Chunk>>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Vec<Chunk>, TranscriptError>;
+
+    /// Extracts the student information from the combined `Chunk`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if required sections are missing or malformed.
+    fn parse(
+        &self,
+        chunks: &[Chunk],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<StudentInfo, TranscriptError>;
+
+    /// Returns the names of the required sections missing from the combined
+    /// `Chunk`s, for validating documents without parsing them.
+    fn missing_sections(&self, _chunks: &[Chunk]) -> Vec<&'static str> {
+        Vec::new()
+    }
+}
+
+/// Parses SFU SIMS transcripts, in both the official and unofficial layouts.
+#[derive(Clone, Debug, Default)]
+pub struct SfuParser {
+    vocabulary: Vocabulary,
+}
+
+impl SfuParser {
+    /// A parser recognizing the grades and tags of `vocabulary` instead of
+    /// only SFU's built-in ones.
+    #[must_use]
+    pub fn new(vocabulary: Vocabulary) -> Self {
+        Self { vocabulary }
+    }
+}
+
+impl TranscriptParser for SfuParser {
+    fn name(&self) -> &'static str {
+        "sfu"
+    }
+
+    // The university name is printed in the footer banner of the official
+    // layout and in the header of the unofficial one, with or without the
+    // letter spacing.
+    fn detect(&self, page_chunks: &[Vec<Chunk>]) -> bool {
+        page_chunks
+            .iter()
+            .flatten()
+            .flat_map(Chunk::strings)
+            .any(|s| {
+                let letters: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+                letters.eq_ignore_ascii_case("SimonFraserUniversity")
+            })
+    }
+
+    fn combine(
+        &self,
+        page_chunks: Vec<Vec<Chunk>>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Vec<Chunk>, TranscriptError> {
+        match TranscriptLayout::detect(&page_chunks) {
+            TranscriptLayout::Official => pdf::combine_page_chunks_with(page_chunks, diagnostics),
+            TranscriptLayout::Unofficial => {
+                pdf::combine_unofficial_page_chunks_with(page_chunks, diagnostics)
+            }
+        }
+    }
+
+    fn parse(
+        &self,
+        chunks: &[Chunk],
+        diagnostics: &mut Diagnostics,
+    ) -> Result<StudentInfo, TranscriptError> {
+        parse::process_chunks_using(chunks, &self.vocabulary, diagnostics)
+    }
+
+    fn missing_sections(&self, chunks: &[Chunk]) -> Vec<&'static str> {
+        parse::missing_sections(chunks)
+    }
+}
+
+/// The parsers for every supported institution, in detection order.
+#[must_use]
+pub fn parsers() -> Vec<Box<dyn TranscriptParser>> {
+    parsers_with(&Vocabulary::default())
+}
+
+/// The parsers for every supported institution, recognizing the grades and
+/// tags of `vocabulary`.
+#[must_use]
+pub fn parsers_with(vocabulary: &Vocabulary) -> Vec<Box<dyn TranscriptParser>> {
+    vec![Box::new(SfuParser::new(vocabulary.clone()))]
+}
+
+/// Chooses the first parser that recognizes the pages. Documents that no
+/// parser recognizes are treated as SFU transcripts, which the tool was
+/// originally written for.
+#[must_use]
+pub fn detect(page_chunks: &[Vec<Chunk>]) -> Box<dyn TranscriptParser> {
+    detect_with(page_chunks, &Vocabulary::default())
+}
+
+/// Chooses a parser like [`detect`], recognizing the grades and tags of
+/// `vocabulary`.
+#[must_use]
+pub fn detect_with(
+    page_chunks: &[Vec<Chunk>],
+    vocabulary: &Vocabulary,
+) -> Box<dyn TranscriptParser> {
+    parsers_with(vocabulary)
+        .into_iter()
+        .find(|parser| parser.detect(page_chunks))
+        .unwrap_or_else(|| Box::new(SfuParser::new(vocabulary.clone())))
+}
@@ -0,0 +1,1143 @@
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+use std::path::Path;
+
+use lopdf::content::{Content, Operation};
+use lopdf::Error as LopdfError;
+use lopdf::Result as LopdfResult;
+use lopdf::{Dictionary, Document, Object, ObjectId, Reader};
+
+use crate::chunk::Chunk;
+use crate::cmap::ToUnicodeMap;
+use crate::error::{Diagnostics, TranscriptError};
+
+/// Maps the file at `path` into memory, so that large batches parse PDFs
+/// straight from the page cache instead of copying each file into a heap
+/// buffer first.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or mapped.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn map_file(path: &Path) -> io::Result<memmap2::Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: The mapping is only read. Truncating the file while it is
+    // mapped is undefined behaviour, which is accepted here as transcripts
+    // are not modified while they are being extracted.
+    unsafe { memmap2::Mmap::map(&file) }
+}
+
+/// Loads the PDF at `path`, from a memory mapping where one is available.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a valid PDF.
+pub fn load_document(path: &Path) -> Result<Document, TranscriptError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return load_document_mem(&map_file(path)?);
+    #[cfg(target_arch = "wasm32")]
+    return load_document_mem(&std::fs::read(path)?);
+}
+
+/// Loads a PDF from memory, rejecting files whose objects nest too deeply
+/// to be parsed safely.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not a valid PDF.
+pub fn load_document_mem(bytes: &[u8]) -> Result<Document, TranscriptError> {
+    check_nesting(bytes)?;
+    check_xref_chain(bytes)?;
+    let reader = Reader {
+        buffer: bytes,
+        document: Document::new(),
+    };
+    Ok(reader.read(Some(check_object_stream))?)
+}
+
+// The objects within object streams are only parsed once the stream is
+// decompressed, so the nesting of each object stream is checked here, where
+// the reader passes every object before expanding the object streams.
+// Streams that nest too deeply are dropped, which leaves the document
+// without their objects. The reader keeps its own copy of top-level objects
+// and only uses the returned copy of objects from within object streams,
+// which are never streams themselves, so streams are not cloned.
+fn check_object_stream(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    match object {
+        Object::Stream(stream) => {
+            if stream.dict.type_is(b"ObjStm") {
+                stream.decompress();
+                check_nesting(&stream.content).ok()?;
+            }
+            Some((id, Object::Null))
+        }
+        _ => Some((id, object.clone())),
+    }
+}
+
+/// The deepest nesting of arrays and dictionaries accepted in a PDF. The
+/// PDF parser recurses once per level, so the far deeper nesting of crafted
+/// files would overflow the stack instead of failing with an error.
+pub const MAX_NESTING: usize = 64;
+
+// Whether the keyword starts at `i` as a token of its own.
+fn is_keyword_at(data: &[u8], i: usize, keyword: &[u8]) -> bool {
+    let preceded = i == 0 || data[i - 1].is_ascii_whitespace() || data[i - 1] == b'>';
+    let followed = data
+        .get(i + keyword.len())
+        .is_some_and(u8::is_ascii_whitespace);
+    preceded && followed && data[i..].starts_with(keyword)
+}
+
+// The index of the last byte of the next occurrence of `needle` at or after
+// `from`, or the end of the data.
+fn skip_to(data: &[u8], from: usize, needle: &[u8]) -> usize {
+    data.get(from..)
+        .and_then(|rest| rest.windows(needle.len()).position(|w| w == needle))
+        .map_or(data.len(), |offset| from + offset + needle.len() - 1)
+}
+
+// The index of the parenthesis closing the literal string opened at `i`.
+fn skip_literal(data: &[u8], mut i: usize) -> usize {
+    let mut open = 0;
+    while i < data.len() {
+        match data[i] {
+            b'\\' => i += 1,
+            b'(' => open += 1,
+            b')' => {
+                open -= 1;
+                if open == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+// Reads the unsigned integer at the start of `data` after any whitespace,
+// returning it with the data that follows it.
+fn leading_integer(data: &[u8]) -> Option<(u64, &[u8])> {
+    let start = data.iter().position(|b| !b.is_ascii_whitespace())?;
+    let digits = data[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    let number = std::str::from_utf8(&data[start..start + digits])
+        .ok()?
+        .parse()
+        .ok()?;
+    Some((number, &data[start + digits..]))
+}
+
+// Rejects files whose cross-reference sections link back to one already
+// read. Each section names the previous one by the offset in its `/Prev`
+// entry, and the PDF reader follows the links without end if they form a
+// cycle. Any cycle links to some offset twice, counting the final
+// `startxref` offset, so repeated offsets are rejected. The `/Prev` entries
+// of outline items are references rather than offsets and are skipped.
+fn check_xref_chain(data: &[u8]) -> LopdfResult<()> {
+    let find_all = |key: &'static [u8]| {
+        data.windows(key.len())
+            .enumerate()
+            .filter(move |(_, window)| *window == key)
+            .filter_map(move |(i, _)| leading_integer(&data[i + key.len()..]))
+    };
+    let is_reference = |rest: &[u8]| {
+        leading_integer(rest).is_some_and(|(_, rest)| rest.trim_ascii_start().starts_with(b"R"))
+    };
+    let start = find_all(b"startxref").next_back().map(|(offset, _)| offset);
+    let links = find_all(b"/Prev")
+        .filter(|(_, rest)| !is_reference(rest))
+        .map(|(offset, _)| offset);
+    let mut seen = BTreeSet::new();
+    for offset in start.into_iter().chain(links) {
+        if !seen.insert(offset) {
+            return Err(LopdfError::Syntax(
+                "cross-reference sections link in a cycle".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Rejects data whose arrays and dictionaries nest deeper than `MAX_NESTING`.
+// Strings, comments, and the data of streams and inline images are skipped,
+// as the brackets within them are not syntax.
+fn check_nesting(data: &[u8]) -> LopdfResult<()> {
+    let mut depth = 0_usize;
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'[' => depth += 1,
+            b'<' if data.get(i + 1) == Some(&b'<') => {
+                depth += 1;
+                i += 1;
+            }
+            b']' => depth = depth.saturating_sub(1),
+            b'>' if data.get(i + 1) == Some(&b'>') => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b'<' => i = skip_to(data, i, b">"),
+            b'(' => i = skip_literal(data, i),
+            b'%' => {
+                while data
+                    .get(i + 1)
+                    .is_some_and(|&b| !matches!(b, b'\r' | b'\n'))
+                {
+                    i += 1;
+                }
+            }
+            b's' if is_keyword_at(data, i, b"stream") => i = skip_to(data, i, b"endstream"),
+            b'I' if is_keyword_at(data, i, b"ID") => i = skip_to(data, i, b"EI"),
+            _ => {}
+        }
+        if depth > MAX_NESTING {
+            return Err(LopdfError::Syntax(format!(
+                "objects nested deeper than {MAX_NESTING} levels"
+            )));
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// How the strings shown with a font are decoded into text.
+#[derive(Clone, Debug)]
+pub(crate) enum FontDecoder<'a> {
+    /// A simple font decoded through its base encoding.
+    Encoding(&'a str),
+    /// A simple font with an embedded `ToUnicode` `CMap`.
+    ToUnicode(ToUnicodeMap),
+    /// A composite (`Type0`) font, whose strings are sequences of two-byte
+    /// CIDs rather than single-byte codes.
+    Composite {
+        to_unicode: Option<ToUnicodeMap>,
+        /// Whether the encoding is one of the predefined Unicode `CMap`s,
+        /// whose codes are already UTF-16.
+        is_unicode: bool,
+    },
+}
+
+// The predefined `CMap`s whose codes are UTF-16BE, such as `UniGB-UCS2-H`
+// or `UniJIS-UTF16-V`.
+fn is_unicode_cmap(name: &str) -> bool {
+    name.starts_with("Uni") && (name.contains("-UCS2-") || name.contains("-UTF16-"))
+}
+
+impl<'a> FontDecoder<'a> {
+    fn new(doc: &Document, font: &'a Dictionary) -> Self {
+        let to_unicode = font
+            .get_deref(b"ToUnicode", doc)
+            .and_then(Object::as_stream)
+            .ok()
+            .and_then(|stream| {
+                stream
+                    .decompressed_content()
+                    .ok()
+                    .or_else(|| Some(stream.content.clone()))
+            })
+            .map(|data| ToUnicodeMap::parse(&data))
+            .filter(|map| !map.is_empty());
+        if font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0") {
+            // The encoding of a composite font is either the name of a
+            // predefined `CMap` (usually `Identity-H`) or an embedded `CMap`
+            // stream. Both use two-byte codes in practice.
+            let is_unicode = font
+                .get(b"Encoding")
+                .and_then(Object::as_name_str)
+                .is_ok_and(is_unicode_cmap);
+            return Self::Composite {
+                to_unicode,
+                is_unicode,
+            };
+        }
+        match to_unicode {
+            Some(map) => Self::ToUnicode(map),
+            None => Self::Encoding(font.get_font_encoding()),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Encoding(encoding) => Document::decode_text(Some(encoding), bytes),
+            Self::ToUnicode(map) => map.decode(bytes),
+            Self::Composite {
+                to_unicode: Some(map),
+                ..
+            } => map.decode_with_width(bytes, 2),
+            Self::Composite {
+                to_unicode: None,
+                is_unicode: true,
+            } => ToUnicodeMap::decode_utf16(bytes),
+            // Without a `ToUnicode` map, the CIDs of `Identity` encodings
+            // are glyph indices. Some print drivers number their glyphs by
+            // code point, so that is the best remaining guess.
+            Self::Composite {
+                to_unicode: None,
+                is_unicode: false,
+            } => bytes
+                .chunks(2)
+                .map(|pair| {
+                    let code =
+                        (u32::from(pair[0]) << 8) | u32::from(pair.get(1).copied().unwrap_or(0));
+                    char::from_u32(code)
+                        .filter(|c| !c.is_control())
+                        .unwrap_or(char::REPLACEMENT_CHARACTER)
+                })
+                .collect(),
+        }
+    }
+
+    // Encodes text to be shown with the font, or `None` if any of its
+    // characters cannot be shown. Base encodings silently drop characters
+    // they lack, so every encoding is checked by decoding it again.
+    pub(crate) fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        let bytes = match self {
+            Self::Encoding(encoding) => Document::encode_text(Some(encoding), text),
+            Self::ToUnicode(map) => map.encode(text)?,
+            Self::Composite {
+                to_unicode: Some(map),
+                ..
+            } => map.encode_with_width(text, 2)?,
+            Self::Composite {
+                to_unicode: None,
+                is_unicode: true,
+            } => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Self::Composite {
+                to_unicode: None,
+                is_unicode: false,
+            } => text
+                .chars()
+                .map(|c| u16::try_from(u32::from(c)).ok().map(u16::to_be_bytes))
+                .collect::<Option<Vec<_>>>()?
+                .concat(),
+        };
+        (self.decode(&bytes) == text).then_some(bytes)
+    }
+}
+
+pub(crate) fn objects_to_chunk(decoder: Option<&FontDecoder>, operands: &[Object]) -> Chunk {
+    let mut chunks = Vec::with_capacity(operands.len());
+    for operand in operands {
+        match operand {
+            Object::String(bytes, _) => {
+                let text = match decoder {
+                    Some(decoder) => decoder.decode(bytes),
+                    None => Document::decode_text(None, bytes),
+                };
+                chunks.push(Chunk::String(text));
+            }
+            Object::Array(arr) => {
+                chunks.push(objects_to_chunk(decoder, arr));
+            }
+            _ => {}
+        }
+    }
+    Chunk::Chunks(chunks)
+}
+
+// The positioning state of the text operators within a page. Only the
+// translation of the text matrices is needed to order text, so glyph
+// advances and the graphics state are not tracked.
+#[derive(Clone, Copy, Debug, Default)]
+struct TextState {
+    // The text line matrix [a b c d e f], which `Tm`, `Td`, `TD`, and `T*`
+    // update. Strings are placed at its translation (e, f).
+    line: [f32; 6],
+    leading: f32,
+}
+
+// The position of a block's first string, in text space.
+type Origin = (f32, f32);
+
+const IDENTITY: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+impl TextState {
+    fn numbers(operation: &Operation) -> Vec<f32> {
+        operation
+            .operands
+            .iter()
+            .filter_map(|o| o.as_float().ok())
+            .collect()
+    }
+
+    fn move_line(&mut self, tx: f32, ty: f32) {
+        let [a, b, c, d, ..] = self.line;
+        self.line[4] += tx.mul_add(a, ty * c);
+        self.line[5] += tx.mul_add(b, ty * d);
+    }
+
+    fn apply(&mut self, operation: &Operation) {
+        match (
+            operation.operator.as_ref(),
+            Self::numbers(operation).as_slice(),
+        ) {
+            ("BT", _) => self.line = IDENTITY,
+            ("Tm", matrix) if matrix.len() == 6 => self.line.copy_from_slice(matrix),
+            ("Td", &[tx, ty]) => self.move_line(tx, ty),
+            ("TD", &[tx, ty]) => {
+                self.leading = -ty;
+                self.move_line(tx, ty);
+            }
+            ("TL", &[leading]) => self.leading = leading,
+            ("T*" | "'" | "\"", _) => self.move_line(0.0, -self.leading),
+            _ => {}
+        }
+    }
+
+    fn position(&self) -> Origin {
+        (self.line[4], self.line[5])
+    }
+}
+
+fn block_to_chunk(
+    operations: &[Operation],
+    decoders: &BTreeMap<Vec<u8>, FontDecoder>,
+    state: &mut TextState,
+    runs: &mut Vec<TextRun>,
+) -> LopdfResult<(Chunk, Option<Origin>)> {
+    let mut current_decoder = None;
+    let mut chunks = Vec::new();
+    let mut origin = None;
+    for operation in operations {
+        state.apply(operation);
+        match operation.operator.as_ref() {
+            "Tf" => {
+                let current_font = operation
+                    .operands
+                    .first()
+                    .ok_or_else(|| LopdfError::Syntax("missing font operand".to_string()))?
+                    .as_name()?;
+                current_decoder = decoders.get(current_font);
+            }
+            "Tj" | "TJ" => {
+                let (x, y) = state.position();
+                origin.get_or_insert((x, y));
+                let chunk = objects_to_chunk(current_decoder, &operation.operands);
+                runs.push(TextRun {
+                    x,
+                    y,
+                    text: chunk.strings().concat(),
+                });
+                chunks.push(chunk);
+            }
+            _ => {}
+        }
+    }
+    Ok((Chunk::Chunks(chunks), origin))
+}
+
+fn group_text_blocks(content: &Content) -> Vec<&[Operation]> {
+    content
+        .operations
+        .as_slice()
+        .split(|o| matches!(o.operator.as_ref(), "ET"))
+        .collect()
+}
+
+// Whether blocks starting at the given origins are already in reading
+// order. Text never returns above a line once a block has started that line
+// at the left margin, nor moves left along a line. Blocks of a cell that
+// wraps onto the lines below, followed by the next cell of the row, are
+// still in order. Baselines are rounded to whole points so that the blocks
+// of one line compare equal.
+fn in_reading_order(origins: &[Origin]) -> bool {
+    let margin = origins
+        .iter()
+        .map(|&(x, _)| x)
+        .fold(f32::INFINITY, f32::min);
+    let mut line = None;
+    let mut previous: Option<Origin> = None;
+    origins.iter().all(|&(x, y)| {
+        let y = y.round();
+        let above = line.is_some_and(|line| y > line);
+        let backward = previous.is_some_and(|(px, py)| py.total_cmp(&y).is_eq() && x < px);
+        if x <= margin + COLUMN_TOLERANCE {
+            line = Some(y);
+        }
+        previous = Some((x, y));
+        !above && !backward
+    })
+}
+
+// Some generators emit text out of visual order, so the blocks of such pages
+// are sorted from the top of the page down and then from left to right.
+// Pages already in reading order keep their stream order, which the layouts
+// rely on. Blocks without text keep their place after the preceding block,
+// and the sort is stable.
+fn sort_blocks(blocks: Vec<(Chunk, Option<Origin>)>) -> Vec<Chunk> {
+    let mut previous = None;
+    let mut positioned: Vec<(Origin, Chunk)> = blocks
+        .into_iter()
+        .map(|(chunk, origin)| {
+            let origin = origin.or(previous).unwrap_or((0.0, f32::INFINITY));
+            previous = Some(origin);
+            (origin, chunk)
+        })
+        .collect();
+    let origins: Vec<Origin> = positioned.iter().map(|&(origin, _)| origin).collect();
+    if !in_reading_order(&origins) {
+        positioned.sort_by(|((x1, y1), _), ((x2, y2), _)| {
+            y2.round().total_cmp(&y1.round()).then(x1.total_cmp(x2))
+        });
+    }
+    positioned.into_iter().map(|(_, chunk)| chunk).collect()
+}
+
+// Whether following the `Parent` links up from a page revisits a node. The
+// resources of a page are inherited through its parents, which are looked
+// up recursively without end in a cyclic page tree.
+fn has_cyclic_parents(doc: &Document, page_id: ObjectId) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut current = Some(page_id);
+    while let Some(id) = current {
+        if !seen.insert(id) {
+            return true;
+        }
+        current = doc
+            .get_dictionary(id)
+            .and_then(|node| node.get(b"Parent"))
+            .and_then(Object::as_reference)
+            .ok();
+    }
+    false
+}
+
+// The decoders of the fonts a page uses, keyed by their resource names, and
+// its decoded content stream.
+pub(crate) fn decode_page(
+    doc: &Document,
+    page_id: ObjectId,
+) -> LopdfResult<(BTreeMap<Vec<u8>, FontDecoder<'_>>, Content)> {
+    if has_cyclic_parents(doc, page_id) {
+        return Err(LopdfError::Syntax("cyclic page tree".to_string()));
+    }
+    let fonts = doc.get_page_fonts(page_id);
+    let decoders = fonts
+        .into_iter()
+        .map(|(name, font)| (name, FontDecoder::new(doc, font)))
+        .collect();
+    let content_data = doc.get_page_content(page_id)?;
+    check_nesting(&content_data)?;
+    Ok((decoders, Content::decode(&content_data)?))
+}
+
+// Extracts the text blocks of every page along with the text runs they
+// contain.
+fn extract_pages(doc: &Document) -> LopdfResult<Vec<(Vec<Chunk>, Vec<TextRun>)>> {
+    let mut pages = Vec::new();
+    for page_id in doc.get_pages().values().copied() {
+        // The first stage per page extracts general page information
+        // required to extract the text later.
+        let (decoders, content) = decode_page(doc, page_id)?;
+
+        // After extracting general page information, we can proceed to the
+        // text extraction itself.
+        let blocks = group_text_blocks(&content);
+        let mut state = TextState::default();
+        let mut runs = Vec::new();
+        let as_chunks: LopdfResult<Vec<(Chunk, Option<Origin>)>> = blocks
+            .iter()
+            .map(|b| block_to_chunk(b, &decoders, &mut state, &mut runs))
+            .collect();
+        pages.push((sort_blocks(as_chunks?), runs));
+    }
+    Ok(pages)
+}
+
+/// Extracts the raw text `Chunk`s of every page in the document, in page
+/// order. The text blocks of a page written out of visual order are ordered
+/// by their position.
+///
+/// # Errors
+///
+/// Returns an error if a page's content stream cannot be read or decoded.
+pub fn extract_page_chunks(doc: &Document) -> LopdfResult<Vec<Vec<Chunk>>> {
+    Ok(extract_pages(doc)?
+        .into_iter()
+        .map(|(chunks, _)| chunks)
+        .collect())
+}
+
+/// A string shown on a page, placed at the start of its baseline in text
+/// space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+}
+
+/// Extracts the positioned text runs of every page in the document, in page
+/// order.
+///
+/// # Errors
+///
+/// Returns an error if a page's content stream cannot be read or decoded.
+pub fn extract_page_runs(doc: &Document) -> LopdfResult<Vec<Vec<TextRun>>> {
+    Ok(extract_pages(doc)?
+        .into_iter()
+        .map(|(_, runs)| runs)
+        .collect())
+}
+
+/// The largest distance in points between runs on the same line or in the
+/// same column.
+pub const COLUMN_TOLERANCE: f32 = 2.0;
+
+// Groups sorted positions into clusters whose neighbours are within
+// `tolerance`, returning the start of each cluster.
+fn cluster_starts(mut positions: Vec<f32>, tolerance: f32) -> Vec<f32> {
+    positions.sort_by(f32::total_cmp);
+    let mut starts: Vec<f32> = Vec::new();
+    let mut previous = None;
+    for position in positions {
+        if previous.is_none_or(|previous: f32| position - previous > tolerance) {
+            starts.push(position);
+        }
+        previous = Some(position);
+    }
+    starts
+}
+
+/// Reconstructs the table rows of a page from the positions of its text
+/// runs rather than from the order and nesting of its text blocks. Runs are
+/// grouped into lines by their baselines, and their x-coordinates are
+/// clustered into columns shared by the whole page. Every row has one cell
+/// per column, left empty where a line has no text in that column, and runs
+/// falling into the same cell are joined with a space.
+#[must_use]
+pub fn reconstruct_rows(runs: &[TextRun], tolerance: f32) -> Vec<Vec<String>> {
+    let columns = cluster_starts(runs.iter().map(|run| run.x).collect(), tolerance);
+    let lines = cluster_starts(runs.iter().map(|run| -run.y).collect(), tolerance);
+    let index_of = |starts: &[f32], position: f32| {
+        starts
+            .partition_point(|&start| start <= position)
+            .saturating_sub(1)
+    };
+    let mut rows = vec![vec![String::new(); columns.len()]; lines.len()];
+    let mut sorted: Vec<&TextRun> = runs.iter().collect();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x));
+    for run in sorted {
+        let text = run.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let cell = &mut rows[index_of(&lines, -run.y)][index_of(&columns, run.x)];
+        if !cell.is_empty() {
+            cell.push(' ');
+        }
+        cell.push_str(text);
+    }
+    rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+    rows
+}
+
+/// How the pages of a document are put into reading order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PageOrder {
+    /// Use the printed page numbers when every page has one, then the page
+    /// labels, and otherwise the order of the page tree.
+    #[default]
+    Auto,
+    /// The order of the document's page tree.
+    Tree,
+    /// The numeric page labels of the document catalog.
+    Labels,
+    /// The "Page N of M" numbers printed on the pages.
+    Printed,
+    /// An explicit sequence of 1-based page indices in page tree order.
+    Explicit(Vec<usize>),
+}
+
+impl std::str::FromStr for PageOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "tree" => Ok(Self::Tree),
+            "labels" => Ok(Self::Labels),
+            "printed" => Ok(Self::Printed),
+            _ => s
+                .split(',')
+                .map(|page| match page.trim().parse::<usize>() {
+                    Ok(page) if page > 0 => Ok(page),
+                    _ => Err(format!(
+                        "expected auto, tree, labels, printed, or a list of pages like 2,1,3, \
+                         found `{s}`"
+                    )),
+                })
+                .collect::<Result<Vec<usize>, String>>()
+                .map(Self::Explicit),
+        }
+    }
+}
+
+// The start of each page label range, keyed by page index. The number tree
+// is usually flat, but intermediate `Kids` nodes are followed as well, up to
+// `MAX_NESTING` levels deep so that cyclic trees end.
+fn collect_label_ranges(
+    doc: &Document,
+    node: &Dictionary,
+    ranges: &mut BTreeMap<i64, (Vec<u8>, i64, bool)>,
+    depth: usize,
+) {
+    if depth > MAX_NESTING {
+        return;
+    }
+    if let Ok(nums) = node.get_deref(b"Nums", doc).and_then(Object::as_array) {
+        for pair in nums.chunks(2) {
+            let [index, label] = pair else { continue };
+            let Ok(index) = index.as_i64() else { continue };
+            let Ok(label) = doc.dereference(label).and_then(|(_, o)| o.as_dict()) else {
+                continue;
+            };
+            let prefix = label
+                .get(b"P")
+                .and_then(Object::as_str)
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default();
+            let start = label.get(b"St").and_then(Object::as_i64).unwrap_or(1);
+            let is_numbered = label.get(b"S").is_ok();
+            ranges.insert(index, (prefix, start, is_numbered));
+        }
+    }
+    if let Ok(kids) = node.get_deref(b"Kids", doc).and_then(Object::as_array) {
+        for kid in kids {
+            if let Ok((_, Object::Dictionary(kid))) = doc.dereference(kid) {
+                collect_label_ranges(doc, kid, ranges, depth + 1);
+            }
+        }
+    }
+}
+
+// The (prefix, number) label of each page in page tree order, if every
+// page has a numbered label.
+fn page_labels(doc: &Document, num_pages: usize) -> Option<Vec<(Vec<u8>, i64)>> {
+    let root = doc
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"PageLabels", doc))
+        .and_then(Object::as_dict)
+        .ok()?;
+    let mut ranges = BTreeMap::new();
+    collect_label_ranges(doc, root, &mut ranges, 0);
+    (0..num_pages)
+        .map(|page| {
+            let page = i64::try_from(page).ok()?;
+            let (&first, (prefix, start, is_numbered)) = ranges.range(..=page).next_back()?;
+            let number = page.checked_sub(first)?.checked_add(*start)?;
+            is_numbered.then(|| (prefix.clone(), number))
+        })
+        .collect()
+}
+
+// The "Page N of M" number printed on a page.
+fn printed_page_number(page: &[Chunk]) -> Option<usize> {
+    page.iter().flat_map(Chunk::strings).find_map(|s| {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["Page", n, "of", m] if m.parse::<usize>().is_ok() => n.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+// Sorts the page indices by their keys, or returns `None` unless every page
+// has a distinct key.
+fn order_by_keys<K: Ord + Clone>(keys: Option<Vec<K>>) -> Option<Vec<usize>> {
+    let keys = keys?;
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by_key(|&page| keys[page].clone());
+    let is_distinct = order.windows(2).all(|w| keys[w[0]] != keys[w[1]]);
+    is_distinct.then_some(order)
+}
+
+/// Resolves the reading order of extracted pages, given in page tree order,
+/// as a sequence of indices into `pages`.
+///
+/// # Errors
+///
+/// Returns an error if the requested order cannot be resolved, such as when
+/// pages have no labels or an explicit order does not list every page
+/// exactly once.
+pub fn resolve_page_order(
+    doc: &Document,
+    pages: &[Vec<Chunk>],
+    order: &PageOrder,
+) -> Result<Vec<usize>, TranscriptError> {
+    let printed = || order_by_keys(pages.iter().map(|p| printed_page_number(p)).collect());
+    let labels = || order_by_keys(page_labels(doc, pages.len()));
+    let tree = (0..pages.len()).collect();
+    match order {
+        PageOrder::Auto => Ok(printed().or_else(labels).unwrap_or(tree)),
+        PageOrder::Tree => Ok(tree),
+        PageOrder::Labels => {
+            labels().ok_or_else(|| TranscriptError::PageOrder("pages lack numbered labels".into()))
+        }
+        PageOrder::Printed => printed().ok_or_else(|| {
+            TranscriptError::PageOrder("pages lack distinct printed page numbers".into())
+        }),
+        PageOrder::Explicit(explicit) => {
+            let mut sorted = explicit.clone();
+            sorted.sort_unstable();
+            if !sorted.iter().copied().eq(1..=pages.len()) {
+                return Err(TranscriptError::PageOrder(format!(
+                    "the order must list each of the {} pages exactly once",
+                    pages.len()
+                )));
+            }
+            Ok(explicit.iter().map(|&page| page - 1).collect())
+        }
+    }
+}
+
+/// Extracts the raw text `Chunk`s of every page like
+/// [`extract_page_chunks`], with the pages put into the given reading order.
+///
+/// # Errors
+///
+/// Returns an error if a page's content stream cannot be read or decoded,
+/// or if the order cannot be resolved.
+pub fn extract_ordered_page_chunks(
+    doc: &Document,
+    order: &PageOrder,
+) -> Result<Vec<Vec<Chunk>>, TranscriptError> {
+    let pages = extract_page_chunks(doc)?;
+    let resolved = resolve_page_order(doc, &pages, order)?;
+    Ok(resolved
+        .into_iter()
+        .map(|page| pages[page].clone())
+        .collect())
+}
+
+pub const FOOTER_BANNER: &str = "S I M O N   F R A S E R   U N I V E R S I T Y";
+
+/// The title printed at the top of unofficial transcripts.
+pub const UNOFFICIAL_MARKER: &str = "Unofficial Transcript";
+
+/// The variants of the transcript PDF. Both share the same sections, but
+/// differ in their page headers, footers, and column spacing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptLayout {
+    /// The official transcript issued by the registrar.
+    Official,
+    /// The unofficial transcript printed from the web or by advisors.
+    Unofficial,
+}
+
+impl TranscriptLayout {
+    /// Detects the layout from the raw `Chunk`s of the pages, based on the
+    /// title of the first page.
+    #[must_use]
+    pub fn detect(page_chunks: &[Vec<Chunk>]) -> Self {
+        let marker = UNOFFICIAL_MARKER.to_ascii_lowercase();
+        let is_unofficial = page_chunks.first().is_some_and(|page| {
+            page.iter()
+                .flat_map(Chunk::strings)
+                .any(|s| s.to_ascii_lowercase().contains(&marker))
+        });
+        if is_unofficial {
+            Self::Unofficial
+        } else {
+            Self::Official
+        }
+    }
+}
+
+/// How many chunks from the end of a page are searched for the footer
+/// banner.
+pub const FOOTER_SEARCH_WINDOW: usize = 20;
+
+fn is_footer_banner(chunk: &Chunk) -> bool {
+    matches!(chunk, Chunk::Chunks(v)
+        if v.first().and_then(Chunk::get_string) == Some(FOOTER_BANNER))
+}
+
+/// Strips the page footers from all but the last page and joins the pages
+/// into a single sequence of `Chunk`s.
+///
+/// # Errors
+///
+/// Returns an error if a page footer is not found where it is expected.
+pub fn combine_page_chunks(page_chunks: Vec<Vec<Chunk>>) -> Result<Vec<Chunk>, TranscriptError> {
+    combine_page_chunks_with(page_chunks, &mut Diagnostics::default())
+}
+
+/// Strips the page footers like [`combine_page_chunks`], reporting missing
+/// footers through `diagnostics`. When parsing leniently, pages without a
+/// footer are kept intact.
+///
+/// # Errors
+///
+/// Returns an error if a page footer is not found where it is expected and
+/// `diagnostics` is not lenient.
+pub fn combine_page_chunks_with(
+    mut page_chunks: Vec<Vec<Chunk>>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Chunk>, TranscriptError> {
+    let num_pages = page_chunks.len();
+    for (index, page) in page_chunks
+        .iter_mut()
+        .take(num_pages.saturating_sub(1))
+        .enumerate()
+    {
+        // The footer usually starts 7 indices before the end of every page
+        // except for the last page, but we leave it on the last page anyway.
+        // Trailing notes can push it further up, so it is searched for
+        // backward within a window from the end of the page.
+        let window_start = page.len().saturating_sub(FOOTER_SEARCH_WINDOW);
+        let footer_start = page[window_start..]
+            .iter()
+            .rposition(is_footer_banner)
+            .map(|offset| window_start + offset);
+        match footer_start {
+            Some(start) => page.truncate(start),
+            None => diagnostics.report(TranscriptError::FooterNotFound { page: index + 1 })?,
+        }
+    }
+    Ok(page_chunks.into_iter().flatten().collect())
+}
+
+// Unofficial pages end with a "Page N of M" line.
+fn is_page_number(chunk: &Chunk) -> bool {
+    chunk.strings().into_iter().any(|s| {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        matches!(words.as_slice(), ["Page", n, "of", m]
+            if n.parse::<usize>().is_ok() && m.parse::<usize>().is_ok())
+    })
+}
+
+// The unofficial layout pads its columns with spaces and splits its blank
+// spacer columns into whitespace, so trimming each string restores the
+// columns of the official layout.
+fn trim_strings(chunk: Chunk) -> Chunk {
+    match chunk {
+        Chunk::Chunks(v) => Chunk::Chunks(v.into_iter().map(trim_strings).collect()),
+        Chunk::String(s) => Chunk::String(s.trim().to_string()),
+    }
+}
+
+/// Strips the page headers and footers of the unofficial layout and joins
+/// the pages into a single sequence of `Chunk`s, reporting pages without a
+/// footer through `diagnostics`. Every page after the first repeats the
+/// header of the first page, and every page but the last ends with a footer
+/// starting at its page number.
+///
+/// # Errors
+///
+/// Returns an error if a page footer is not found and `diagnostics` is not
+/// lenient.
+pub fn combine_unofficial_page_chunks_with(
+    page_chunks: Vec<Vec<Chunk>>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Chunk>, TranscriptError> {
+    let pages: Vec<Vec<Chunk>> = page_chunks
+        .into_iter()
+        .map(|page| page.into_iter().map(trim_strings).collect())
+        .collect();
+    let num_pages = pages.len();
+    let mut combined = Vec::new();
+    let mut header: &[Chunk] = &[];
+    for (index, page) in pages.iter().enumerate() {
+        let mut page = page.as_slice();
+        if index == 0 {
+            header = page;
+        } else {
+            let repeated = page
+                .iter()
+                .zip(header)
+                .take_while(|(chunk, first)| chunk == first)
+                .count();
+            page = &page[repeated..];
+        }
+        // As with the official layout, the footer of the last page is left
+        // in place.
+        if index + 1 < num_pages {
+            match page.iter().rposition(is_page_number) {
+                Some(footer_start) => page = &page[..footer_start],
+                None => diagnostics.report(TranscriptError::FooterNotFound { page: index + 1 })?,
+            }
+        }
+        combined.extend_from_slice(page);
+    }
+    Ok(combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use lopdf::{dictionary, Stream};
+
+    use super::*;
+
+    fn blocks(texts: &[(&str, f32, f32)]) -> Vec<(Chunk, Option<Origin>)> {
+        texts
+            .iter()
+            .map(|&(text, x, y)| (Chunk::String(text.to_string()), Some((x, y))))
+            .collect()
+    }
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().filter_map(Chunk::get_string).collect()
+    }
+
+    #[test]
+    fn pages_in_reading_order_keep_their_stream_order() {
+        // The title of the course wraps onto a second line before the grade.
+        let page = blocks(&[
+            ("CMPT", 36.0, 500.0),
+            ("Introduction to", 100.0, 500.0),
+            ("Computing", 100.0, 490.0),
+            ("A", 300.0, 500.0),
+            ("MATH", 36.0, 480.0),
+        ]);
+        assert_eq!(
+            texts(&sort_blocks(page)),
+            ["CMPT", "Introduction to", "Computing", "A", "MATH"]
+        );
+    }
+
+    #[test]
+    fn pages_out_of_order_are_sorted_by_position() {
+        // The baselines of the first line differ by less than a point.
+        let page = blocks(&[
+            ("MATH", 36.0, 489.8),
+            ("A", 300.0, 500.3),
+            ("CMPT", 36.0, 499.6),
+            ("B", 300.0, 490.2),
+        ]);
+        assert_eq!(texts(&sort_blocks(page)), ["CMPT", "A", "MATH", "B"]);
+
+        let backward = blocks(&[("A", 300.0, 500.0), ("CMPT", 36.0, 500.0)]);
+        assert_eq!(texts(&sort_blocks(backward)), ["CMPT", "A"]);
+    }
+
+    fn run(text: &str, x: f32, y: f32) -> TextRun {
+        TextRun {
+            x,
+            y,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn rows_are_reconstructed_from_run_positions() {
+        let runs = [
+            run("A", 300.4, 499.1),
+            run("CMPT", 36.0, 500.0),
+            run("120", 71.2, 500.0),
+            run("Introduction", 100.0, 500.0),
+            run("MATH", 36.0, 480.0),
+            run("B", 299.0, 480.0),
+            run(" ", 100.0, 460.0),
+        ];
+        assert_eq!(
+            reconstruct_rows(&runs, COLUMN_TOLERANCE),
+            [["CMPT", "120", "Introduction", "A"], ["MATH", "", "", "B"]]
+        );
+    }
+
+    fn page(strings: &[&str]) -> Vec<Chunk> {
+        strings
+            .iter()
+            .map(|s| Chunk::Chunks(vec![Chunk::String((*s).to_string())]))
+            .collect()
+    }
+
+    #[test]
+    fn footers_are_found_within_the_search_window() {
+        let mut first = page(&["CMPT"; 4]);
+        first.extend(page(&[FOOTER_BANNER]));
+        first.extend(page(&["note"; FOOTER_SEARCH_WINDOW - 1]));
+        let combined = combine_page_chunks(vec![first, page(&["MATH"])]).unwrap();
+        assert_eq!(combined, [page(&["CMPT"; 4]), page(&["MATH"])].concat());
+    }
+
+    #[test]
+    fn footers_beyond_the_search_window_are_not_found() {
+        let mut first = page(&[FOOTER_BANNER]);
+        first.extend(page(&["note"; FOOTER_SEARCH_WINDOW]));
+        let pages = vec![first.clone(), page(&["MATH"])];
+        assert!(matches!(
+            combine_page_chunks(pages.clone()),
+            Err(TranscriptError::FooterNotFound { page: 1 })
+        ));
+        let mut diagnostics = Diagnostics::new(true);
+        let combined = combine_page_chunks_with(pages, &mut diagnostics).unwrap();
+        assert_eq!(combined, [first, page(&["MATH"])].concat());
+    }
+
+    #[test]
+    fn page_orders_are_parsed() {
+        assert_eq!("auto".parse(), Ok(PageOrder::Auto));
+        assert_eq!("tree".parse(), Ok(PageOrder::Tree));
+        assert_eq!("labels".parse(), Ok(PageOrder::Labels));
+        assert_eq!("printed".parse(), Ok(PageOrder::Printed));
+        assert_eq!("2, 1,3".parse(), Ok(PageOrder::Explicit(vec![2, 1, 3])));
+        assert!("2,0".parse::<PageOrder>().is_err());
+        assert!("2,,1".parse::<PageOrder>().is_err());
+        assert!("reverse".parse::<PageOrder>().is_err());
+    }
+
+    #[test]
+    fn page_orders_are_resolved() {
+        let doc = Document::new();
+        let pages = [page(&["Page 2 of 2"]), page(&["Page 1 of 2"])];
+        let resolve = |order| resolve_page_order(&doc, &pages, &order);
+        assert_eq!(resolve(PageOrder::Auto).unwrap(), [1, 0]);
+        assert_eq!(resolve(PageOrder::Printed).unwrap(), [1, 0]);
+        assert_eq!(resolve(PageOrder::Tree).unwrap(), [0, 1]);
+        assert_eq!(resolve(PageOrder::Explicit(vec![2, 1])).unwrap(), [1, 0]);
+        assert!(matches!(
+            resolve(PageOrder::Labels),
+            Err(TranscriptError::PageOrder(_))
+        ));
+        for explicit in [vec![3, 1], vec![1, 1, 2], vec![2], vec![]] {
+            assert!(matches!(
+                resolve(PageOrder::Explicit(explicit)),
+                Err(TranscriptError::PageOrder(_))
+            ));
+        }
+
+        // Without distinct printed numbers, the page tree order is kept.
+        let unnumbered = [page(&["CMPT"]), page(&["Page 1 of 2"])];
+        let auto = resolve_page_order(&doc, &unnumbered, &PageOrder::Auto);
+        assert_eq!(auto.unwrap(), [0, 1]);
+    }
+
+    #[test]
+    fn composite_fonts_decode_two_byte_codes() {
+        let mut doc = Document::with_version("1.5");
+        let cmap = b"1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+                     2 beginbfchar <0001> <0043> <0002> <004D> endbfchar";
+        let stream = Stream::new(Dictionary::new(), cmap.to_vec());
+        let to_unicode = doc.add_object(stream);
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "Encoding" => "Identity-H",
+            "ToUnicode" => to_unicode,
+        };
+        let decoder = FontDecoder::new(&doc, &font);
+        assert!(matches!(decoder, FontDecoder::Composite { .. }));
+        assert_eq!(decoder.decode(&[0, 1, 0, 2]), "CM");
+        // A trailing odd byte is not a code of its own.
+        assert_eq!(decoder.decode(&[0, 2, 0, 1, 0]), "MC\u{FFFD}");
+        assert_eq!(decoder.encode("MC"), Some(vec![0, 2, 0, 1]));
+
+        // Without a map, the codes of a Unicode encoding are UTF-16.
+        let font = dictionary! {
+            "Subtype" => "Type0",
+            "Encoding" => "UniGB-UCS2-H",
+        };
+        let decoder = FontDecoder::new(&doc, &font);
+        assert_eq!(decoder.decode(&[0x4E, 0x03, 0, 0x41]), "\u{4E03}A");
+    }
+}
@@ -0,0 +1,310 @@
+//! Reading PESC College Transcript XML, as sent by partner institutions,
+//! into the same [`StudentInfo`] model as the PDF transcripts.
+//!
+//! The first `AcademicRecord` of the student is taken as the record of the
+//! sending institution, and each of its `AcademicSession`s becomes a term.
+//! Courses listed in a record outside of any session, and every course of
+//! the records of other institutions, become transfer credits. Elements are
+//! matched by their local names, so any namespace prefixes may be used.
+
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use crate::error::{Diagnostics, TranscriptError};
+use crate::model::{
+    Course, Credential, Identity, Note, Plan, Semester, StudentInfo, TermType, Transfer,
+};
+use crate::parse::{compute_cumulative_gpas, number_attempts};
+use crate::vocabulary::Vocabulary;
+use crate::{grades, ParseOptions};
+
+/// Whether `bytes` look like an XML document rather than a PDF, allowing for
+/// a byte order mark and leading whitespace.
+#[must_use]
+pub fn is_xml(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    bytes.trim_ascii_start().starts_with(b"<")
+}
+
+/// Loads the PESC College Transcript at `path` and extracts its student
+/// information.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed as XML, or if it
+/// does not hold a student record.
+pub fn from_path<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+) -> Result<StudentInfo, TranscriptError> {
+    from_bytes(&std::fs::read(path)?, options)
+}
+
+/// Extracts the student information from an in-memory PESC College
+/// Transcript.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not UTF-8 XML, or if the document does
+/// not hold a student record.
+pub fn from_bytes(bytes: &[u8], options: &ParseOptions) -> Result<StudentInfo, TranscriptError> {
+    let text = std::str::from_utf8(bytes).map_err(|err| {
+        TranscriptError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    })?;
+    from_str(text.strip_prefix('\u{feff}').unwrap_or(text), options)
+}
+
+/// Extracts the student information from the text of a PESC College
+/// Transcript.
+///
+/// # Errors
+///
+/// Returns an error if the text is not well-formed XML, if it has no
+/// `Student` with an ID and an `AcademicRecord`, or if an anomaly such as an
+/// unknown grade is found and `options` is not lenient.
+pub fn from_str(text: &str, options: &ParseOptions) -> Result<StudentInfo, TranscriptError> {
+    let document = Document::parse(text)?;
+    let student = document
+        .descendants()
+        .find(|node| is(*node, "Student"))
+        .ok_or(TranscriptError::MissingSection("Student"))?;
+    let person = child(student, "Person");
+    let id = person
+        .and_then(|person| {
+            [
+                "SchoolAssignedPersonID",
+                "AgencyAssignedID",
+                "RecipientAssignedID",
+            ]
+            .iter()
+            .find_map(|name| text_of(person, name))
+        })
+        .ok_or(TranscriptError::MalformedSection("Student ID"))?;
+    let records: Vec<Node> = children(student, "AcademicRecord").collect();
+    let home = *records
+        .first()
+        .ok_or(TranscriptError::MissingSection("AcademicRecord"))?;
+
+    let mut diagnostics = Diagnostics::new(options.lenient).with_strict_grades(options.strict);
+    let vocabulary = &options.vocabulary;
+    let mut transfers = Vec::new();
+    for (index, &record) in records.iter().enumerate() {
+        let school = child(record, "School").and_then(|school| text_of(school, "OrganizationName"));
+        // Courses at the sending institution outside of a term, such as
+        // advanced placement, have no school of their own.
+        let school = if index == 0 { None } else { school };
+        let courses = children(record, "Course").chain(
+            children(record, "AcademicSession")
+                .filter(|_| index > 0)
+                .flat_map(|session| children(session, "Course")),
+        );
+        for node in courses {
+            let course = course(node, vocabulary, &mut diagnostics)?;
+            transfers.push(Transfer {
+                units: course.units_earned,
+                school: school.clone(),
+                course,
+                equivalent: None,
+            });
+        }
+    }
+
+    let mut semesters = Vec::new();
+    let mut notes = Vec::new();
+    for session in children(home, "AcademicSession") {
+        let Some(semester) = semester(session, vocabulary, &mut diagnostics)? else {
+            continue;
+        };
+        notes.extend(children(session, "NoteMessage").map(|note| Note {
+            year: Some(semester.year.clone()),
+            term: Some(semester.term.clone()),
+            text: note_text(note),
+        }));
+        semesters.push(semester);
+    }
+    semesters.sort_by_key(Semester::term_key);
+    compute_cumulative_gpas(&mut semesters);
+    number_attempts(&mut transfers, &mut semesters);
+    notes.extend(children(home, "NoteMessage").map(|note| Note {
+        year: None,
+        term: None,
+        text: note_text(note),
+    }));
+
+    Ok(StudentInfo {
+        id,
+        plans: children(home, "AcademicProgram")
+            .filter_map(|program| text_of(program, "AcademicProgramName"))
+            .map(|name| Plan {
+                name,
+                effective_year: None,
+                effective_term: None,
+            })
+            .collect(),
+        transfers,
+        semesters,
+        credentials: children(home, "AcademicAward")
+            .filter_map(|award| {
+                Some(Credential {
+                    program: text_of(award, "AcademicAwardTitle")?,
+                    conferred: text_of(award, "AcademicAwardDate")
+                        .or_else(|| text_of(award, "AcademicCompletionDate")),
+                })
+            })
+            .collect(),
+        milestones: Vec::new(),
+        notes,
+        unit_totals: Vec::new(),
+        warnings: diagnostics.warnings().to_vec(),
+        identity: person.map(identity).unwrap_or_default(),
+    })
+}
+
+fn is(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |child| is(*child, name))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+// The trimmed text of the named child, if it has any.
+fn text_of(node: Node, name: &'static str) -> Option<String> {
+    child(node, name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn number_of(node: Node, name: &'static str) -> Option<f64> {
+    text_of(node, name).and_then(|text| text.parse().ok())
+}
+
+fn note_text(note: Node) -> String {
+    note.text().unwrap_or_default().trim().to_string()
+}
+
+// The name as "Last, First", the way SFU transcripts print it, so that the
+// leak check finds it in the output.
+fn identity(person: Node) -> Identity {
+    let name = child(person, "Name").and_then(|name| {
+        let last = text_of(name, "LastName");
+        let first = text_of(name, "FirstName");
+        match (last, first) {
+            (Some(last), Some(first)) => Some(format!("{last}, {first}")),
+            (last, first) => last.or(first),
+        }
+    });
+    Identity {
+        name,
+        birth_date: child(person, "Birth").and_then(|birth| text_of(birth, "BirthDate")),
+    }
+}
+
+fn course(
+    node: Node,
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<Course, TranscriptError> {
+    let subject = text_of(node, "CourseSubjectAbbreviation").unwrap_or_default();
+    let id = text_of(node, "CourseNumber").unwrap_or_default();
+    let title = text_of(node, "CourseTitle").unwrap_or_default();
+    let grade = text_of(node, "CourseAcademicGrade").unwrap_or_default();
+    let grade_valid = grade.is_empty() || vocabulary.is_grade(&grade);
+    if !grade_valid {
+        diagnostics.report_grade(TranscriptError::UnknownGrade {
+            value: grade.clone(),
+            row: [subject.as_str(), &id, &title].join(" | "),
+        })?;
+    }
+    Ok(Course {
+        grade_points: vocabulary.grade_points(&grade),
+        outcome: vocabulary.outcome(&grade),
+        units_attempted: number_of(node, "CourseCreditValue"),
+        units_earned: number_of(node, "CourseCreditEarned"),
+        designations: Vec::new(),
+        attempt: 1,
+        is_latest_attempt: true,
+        grade_valid,
+        subject,
+        id,
+        title,
+        grade,
+    })
+}
+
+// The year and term of a session, from the year and month of its designator
+// such as "2019-09", or else from a name such as "2019 Fall".
+fn year_term(detail: Node) -> Option<(String, String)> {
+    if let Some((year, month)) = text_of(detail, "SessionDesignator")
+        .as_deref()
+        .and_then(|designator| designator.split_once('-'))
+    {
+        let term = match month.parse::<u32>().ok()? {
+            1..=4 => "Spring",
+            5..=8 => "Summer",
+            9..=12 => "Fall",
+            _ => return None,
+        };
+        return Some((year.to_string(), term.to_string()));
+    }
+    let name = text_of(detail, "SessionName")?;
+    let (year, term) = name.split_once(' ')?;
+    let term = crate::model::TERMS
+        .iter()
+        .find(|t| t.eq_ignore_ascii_case(term.trim()))?;
+    Some((year.to_string(), (*term).to_string()))
+}
+
+fn semester(
+    session: Node,
+    vocabulary: &Vocabulary,
+    diagnostics: &mut Diagnostics,
+) -> Result<Option<Semester>, TranscriptError> {
+    let detail = child(session, "AcademicSessionDetail").unwrap_or(session);
+    let Some((year, term)) = year_term(detail) else {
+        let name = text_of(detail, "SessionName").unwrap_or_default();
+        diagnostics.report(TranscriptError::MalformedRow {
+            section: "AcademicSession",
+            row: name,
+        })?;
+        return Ok(None);
+    };
+    let courses = children(session, "Course")
+        .map(|node| course(node, vocabulary, diagnostics))
+        .collect::<Result<Vec<_>, _>>()?;
+    let host_institution =
+        child(session, "School").and_then(|school| text_of(school, "OrganizationName"));
+    let printed_gpa = child(session, "AcademicSummary")
+        .and_then(|summary| child(summary, "GPA"))
+        .and_then(|gpa| number_of(gpa, "GradePointAverage"));
+    Ok(Some(Semester {
+        year,
+        term,
+        term_type: if host_institution.is_some() {
+            TermType::Exchange
+        } else {
+            TermType::Academic
+        },
+        host_institution,
+        career: None,
+        academic_group: None,
+        is_good_standing: true,
+        standing: None,
+        honour_roll: None,
+        gpa: grades::gpa(&courses),
+        cgpa: None,
+        printed_gpa,
+        printed_cgpa: None,
+        courses,
+    }))
+}
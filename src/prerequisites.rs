@@ -0,0 +1,178 @@
+//! Checking that courses were taken after their prerequisites, given rules
+//! listing the prerequisites of each course.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grades;
+use crate::model::{Course, Outcome, StudentInfo};
+
+#[derive(Deserialize)]
+struct RawRules {
+    #[serde(default)]
+    minimum_grade: Option<String>,
+    courses: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+// Courses are matched ignoring case and spacing, so "cmpt  225" is the same
+// course as "CMPT 225".
+pub(crate) fn normalize(course: &str) -> String {
+    course
+        .split_whitespace()
+        .map(str::to_uppercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn course_name(subject: &str, id: &str) -> String {
+    normalize(&format!("{subject} {id}"))
+}
+
+/// The prerequisites of courses, each a list of groups that must all be met
+/// by completing any one course of the group.
+#[derive(Clone, Debug, Default)]
+pub struct PrerequisiteRules {
+    minimum_points: Option<f64>,
+    courses: HashMap<String, Vec<Vec<String>>>,
+}
+
+/// A completed course taken before some of its prerequisites were met.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UnmetPrerequisites {
+    pub year: String,
+    pub term: String,
+    pub subject: String,
+    pub id: String,
+    /// The groups of prerequisites that no earlier course met.
+    pub missing: Vec<Vec<String>>,
+}
+
+impl PrerequisiteRules {
+    /// Reads rules from a JSON object such as
+    /// `{"minimum_grade": "C-", "courses": {"CMPT 225": [["CMPT 125",
+    /// "CMPT 135"], ["MACM 101"]]}}`, in which CMPT 225 requires MACM 101
+    /// and either CMPT 125 or CMPT 135. Without a minimum grade, any
+    /// completed course meets a prerequisite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader does not hold such an object, or if
+    /// the minimum grade has no grade points on SFU's scale.
+    pub fn from_json<R: Read>(reader: R) -> serde_json::Result<Self> {
+        let raw: RawRules = serde_json::from_reader(reader)?;
+        let minimum_points = match raw.minimum_grade {
+            Some(grade) => Some(grades::grade_points(grade.trim()).ok_or_else(|| {
+                <serde_json::Error as serde::de::Error>::custom(format!(
+                    "minimum grade {grade} has no grade points"
+                ))
+            })?),
+            None => None,
+        };
+        let courses = raw
+            .courses
+            .into_iter()
+            .map(|(course, groups)| {
+                let groups = groups
+                    .into_iter()
+                    .map(|group| group.iter().map(|course| normalize(course)).collect())
+                    .collect();
+                (normalize(&course), groups)
+            })
+            .collect();
+        Ok(Self {
+            minimum_points,
+            courses,
+        })
+    }
+
+    /// The prerequisite groups of a course, if the rules list it.
+    #[must_use]
+    pub fn prerequisites(&self, subject: &str, id: &str) -> Option<&[Vec<String>]> {
+        self.courses
+            .get(&course_name(subject, id))
+            .map(Vec::as_slice)
+    }
+
+    // Whether a course attempt counts toward a prerequisite. Grades without
+    // grade points, such as P and TR, meet any minimum grade.
+    fn meets(&self, course: &Course) -> bool {
+        course.outcome == Outcome::Completed
+            && self.minimum_points.is_none_or(|minimum| {
+                grades::grade_points(&course.grade).is_none_or(|points| points >= minimum)
+            })
+    }
+
+    /// Finds the completed courses of the student that were taken before
+    /// their prerequisites, in term order. A prerequisite is met by a course
+    /// completed in an earlier term, or by a transfer credit for the course
+    /// or with it as the SFU equivalent. Courses taken in the same term don't
+    /// count, as corequisites aren't modelled.
+    #[must_use]
+    pub fn check(&self, student: &StudentInfo) -> Vec<UnmetPrerequisites> {
+        let transferred: Vec<String> = student
+            .transfers
+            .iter()
+            .filter(|transfer| self.meets(&transfer.course))
+            .flat_map(|transfer| {
+                let course = &transfer.course;
+                let equivalent = transfer
+                    .equivalent
+                    .as_ref()
+                    .map(|equivalent| course_name(&equivalent.subject, &equivalent.id));
+                std::iter::once(course_name(&course.subject, &course.id)).chain(equivalent)
+            })
+            .collect();
+        let completed: Vec<((u32, usize), String)> = student
+            .semesters
+            .iter()
+            .flat_map(|semester| {
+                semester
+                    .courses
+                    .iter()
+                    .filter(|course| self.meets(course))
+                    .map(|course| {
+                        (
+                            semester.term_key(),
+                            course_name(&course.subject, &course.id),
+                        )
+                    })
+            })
+            .collect();
+
+        let mut unmet = Vec::new();
+        for semester in &student.semesters {
+            let term = semester.term_key();
+            let met = |prerequisite: &String| {
+                transferred.contains(prerequisite)
+                    || completed
+                        .iter()
+                        .any(|(taken, course)| *taken < term && course == prerequisite)
+            };
+            for course in &semester.courses {
+                if course.outcome != Outcome::Completed {
+                    continue;
+                }
+                let Some(groups) = self.prerequisites(&course.subject, &course.id) else {
+                    continue;
+                };
+                let missing: Vec<Vec<String>> = groups
+                    .iter()
+                    .filter(|group| !group.iter().any(met))
+                    .cloned()
+                    .collect();
+                if !missing.is_empty() {
+                    unmet.push(UnmetPrerequisites {
+                        year: semester.year.clone(),
+                        term: semester.term.clone(),
+                        subject: course.subject.clone(),
+                        id: course.id.clone(),
+                        missing,
+                    });
+                }
+            }
+        }
+        unmet
+    }
+}
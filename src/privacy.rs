@@ -0,0 +1,153 @@
+//! Reducing the detail of students' records before release, so that rare
+//! attributes or combinations of them cannot single out a student even
+//! under an anonymized ID.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::model::StudentInfo;
+
+/// The text that replaces suppressed values.
+pub const SUPPRESSED: &str = "Suppressed";
+
+// How many students hold each value, counting every student once.
+fn holders<'a, I>(
+    students: &'a [StudentInfo],
+    values: impl Fn(&'a StudentInfo) -> I,
+) -> BTreeMap<I::Item, usize>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    let mut counts = BTreeMap::new();
+    for student in students {
+        for value in values(student).into_iter().collect::<BTreeSet<_>>() {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+// The plan a student was admitted to, along with the term they started in.
+fn admission(student: &StudentInfo) -> Option<(String, String, String)> {
+    let plan = student.first_plan()?;
+    let first = student.semesters.first()?;
+    Some((plan.name.clone(), first.year.clone(), first.term.clone()))
+}
+
+/// Suppresses the quasi-identifiers that fewer than `k` of `students`
+/// share, returning the number of values suppressed.
+///
+/// The quasi-identifiers are the names of plans and credentials and the
+/// institutions of transfer credits, along with the pairing of the plan a
+/// student was admitted to with the term they started in, as a unique major
+/// and start term can identify a student as well as a unique major can.
+/// Rare values are replaced with [`SUPPRESSED`] wherever they appear, and
+/// rare pairings lose the name of their admitted plan, so that every value
+/// and pairing left named is shared by at least `k` students.
+pub fn suppress_rare(students: &mut [StudentInfo], k: usize) -> usize {
+    let plans = holders(students, |s| s.plans.iter().map(|p| p.name.clone()));
+    let credentials = holders(students, |s| {
+        s.credentials.iter().map(|c| c.program.clone())
+    });
+    let schools = holders(students, |s| {
+        s.transfers.iter().filter_map(|t| t.school.clone())
+    });
+    let admissions = holders(students, admission);
+    let rare =
+        |counts: &BTreeMap<String, usize>, value: &str| counts.get(value).is_some_and(|&n| n < k);
+
+    let mut suppressed = 0;
+    let mut suppress = |value: &mut String| {
+        *value = SUPPRESSED.to_string();
+        suppressed += 1;
+    };
+    for student in students.iter_mut() {
+        let rare_admission = admission(student)
+            .and_then(|admission| admissions.get(&admission))
+            .is_some_and(|&n| n < k);
+        for (i, plan) in student.plans.iter_mut().enumerate() {
+            if rare(&plans, &plan.name) || (i == 0 && rare_admission) {
+                suppress(&mut plan.name);
+            }
+        }
+        for credential in &mut student.credentials {
+            if rare(&credentials, &credential.program) {
+                suppress(&mut credential.program);
+            }
+        }
+        for school in student
+            .transfers
+            .iter_mut()
+            .filter_map(|t| t.school.as_mut())
+        {
+            if rare(&schools, school) {
+                suppress(school);
+            }
+        }
+    }
+    suppressed
+}
+
+/// How finely the terms of a coarsened release are given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TermGranularity {
+    /// Terms become the academic year they fall in, which starts in the
+    /// Fall and is given by the year it starts in, with the term
+    /// `Academic Year`.
+    AcademicYear,
+    /// Terms become their calendar year, with the term `Year`.
+    Year,
+}
+
+impl TermGranularity {
+    fn coarsen(self, year: &mut String, term: &mut String) {
+        match self {
+            Self::AcademicYear => {
+                if term != "Fall" {
+                    if let Ok(start) = year.parse::<u32>() {
+                        *year = start.saturating_sub(1).to_string();
+                    }
+                }
+                "Academic Year".clone_into(term);
+            }
+            Self::Year => "Year".clone_into(term),
+        }
+    }
+}
+
+/// Replaces every term of `student` with the coarser period it falls in, and
+/// every conferral date with its year, or with nothing if it has no year, for
+/// releases where the exact term someone studied or graduated in could
+/// identify them. Semesters are kept apart, so a coarsened period can hold
+/// several of them.
+pub fn coarsen_terms(student: &mut StudentInfo, granularity: TermGranularity) {
+    for semester in &mut student.semesters {
+        granularity.coarsen(&mut semester.year, &mut semester.term);
+    }
+    for plan in &mut student.plans {
+        if let (Some(year), Some(term)) = (&mut plan.effective_year, &mut plan.effective_term) {
+            granularity.coarsen(year, term);
+        }
+    }
+    for milestone in &mut student.milestones {
+        granularity.coarsen(&mut milestone.year, &mut milestone.term);
+    }
+    for note in &mut student.notes {
+        if let (Some(year), Some(term)) = (&mut note.year, &mut note.term) {
+            granularity.coarsen(year, term);
+        }
+    }
+    for credential in &mut student.credentials {
+        credential.conferred = credential
+            .conferred
+            .as_deref()
+            .and_then(conferral_year)
+            .map(str::to_string);
+    }
+}
+
+// The first four digit number of a conferral date, whatever its format.
+fn conferral_year(date: &str) -> Option<&str> {
+    date.split(|c: char| !c.is_ascii_digit())
+        .find(|part| part.len() == 4)
+}
@@ -0,0 +1,109 @@
+//! Parsing of SFU academic advisement (degree progress) reports. These share
+//! the PDF chunk extraction with transcripts, but list requirement blocks
+//! with their status instead of terms of courses.
+
+use crate::chunk::Chunk;
+use crate::error::{Diagnostics, TranscriptError};
+use crate::model::{DegreeProgress, Requirement, RequirementStatus};
+
+const ID_LABELS: [&str; 2] = ["Student ID:", "ID:"];
+const PROGRAM_LABEL: &str = "Program:";
+const UNITS_LABEL: &str = "Units:";
+
+const STATUS_LABELS: [(&str, RequirementStatus); 2] = [
+    ("Not Satisfied:", RequirementStatus::NotSatisfied),
+    ("Satisfied:", RequirementStatus::Satisfied),
+];
+
+// A labelled value may share a string with its label or follow it as the
+// next string.
+fn labelled<'a>(strings: &[&'a str], i: usize, label: &str) -> Option<&'a str> {
+    let rest = strings[i].strip_prefix(label)?.trim();
+    if rest.is_empty() {
+        strings.get(i + 1).map(|next| next.trim())
+    } else {
+        Some(rest)
+    }
+}
+
+// Unit summaries take the form "Units: 24.00 required, 18.00 used,
+// 6.00 needed", where older reports say "taken" instead of "used".
+fn parse_units(requirement: &mut Requirement, summary: &str) {
+    let words: Vec<&str> = summary
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .collect();
+    for pair in words.windows(2) {
+        let Ok(units) = pair[0].parse::<f64>() else {
+            continue;
+        };
+        match pair[1] {
+            "required" => requirement.units_required = Some(units),
+            "used" | "taken" => requirement.units_used = Some(units),
+            "needed" => requirement.units_needed = Some(units),
+            _ => {}
+        }
+    }
+}
+
+/// Extracts the requirement blocks of a degree progress report from the
+/// simplified `Chunk`s of its pages, reporting recoverable anomalies through
+/// `diagnostics`.
+///
+/// # Errors
+///
+/// Returns an error if the student ID or the requirements cannot be found,
+/// or if a requirement is malformed and `diagnostics` is not lenient.
+pub fn process_progress_chunks(
+    chunks: &[Chunk],
+    diagnostics: &mut Diagnostics,
+) -> Result<DegreeProgress, TranscriptError> {
+    let strings: Vec<&str> = chunks.iter().flat_map(Chunk::strings).collect();
+
+    let id = (0..strings.len())
+        .find_map(|i| {
+            ID_LABELS
+                .iter()
+                .find_map(|label| labelled(&strings, i, label))
+        })
+        .ok_or(TranscriptError::MissingSection("Student ID"))?;
+    let program = (0..strings.len()).find_map(|i| labelled(&strings, i, PROGRAM_LABEL));
+
+    let mut requirements: Vec<Requirement> = Vec::new();
+    for i in 0..strings.len() {
+        if let Some((name, status)) = STATUS_LABELS
+            .iter()
+            .find_map(|(label, status)| Some((labelled(&strings, i, label)?, *status)))
+        {
+            if name.is_empty() {
+                diagnostics.report(TranscriptError::MalformedRow {
+                    section: "Requirement",
+                    row: strings[i].to_string(),
+                })?;
+                continue;
+            }
+            requirements.push(Requirement {
+                name: name.to_string(),
+                status,
+                units_required: None,
+                units_used: None,
+                units_needed: None,
+            });
+        } else if let Some(summary) = labelled(&strings, i, UNITS_LABEL) {
+            // Unit summaries belong to the requirement above them.
+            if let Some(requirement) = requirements.last_mut() {
+                parse_units(requirement, summary);
+            }
+        }
+    }
+    if requirements.is_empty() {
+        return Err(TranscriptError::MissingSection("Requirements"));
+    }
+
+    Ok(DegreeProgress {
+        id: id.to_string(),
+        program: program.map(str::to_string),
+        requirements,
+        warnings: diagnostics.warnings().to_vec(),
+    })
+}
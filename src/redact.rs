@@ -0,0 +1,131 @@
+//! Rewriting transcript PDFs with identifying text replaced, so that a
+//! transcript can be shared with auditors while still parsing to the same
+//! records under an anonymized ID.
+
+use std::collections::BTreeMap;
+
+use lopdf::content::Operation;
+use lopdf::Result as LopdfResult;
+use lopdf::{Document, Object, StringFormat};
+
+use crate::model::StudentInfo;
+use crate::pdf::{decode_page, objects_to_chunk, FontDecoder};
+
+/// Replaces every occurrence of the `targets` in the text shown on each page
+/// of `document` with `replacement`, returning the number of strings that
+/// were rewritten.
+///
+/// Text is matched within each string shown, after decoding it through its
+/// font, so a target split over several strings is not found. Rewritten
+/// strings are re-encoded for the same font, and where the font cannot show
+/// the replacement the string is emptied instead, so the identifying text is
+/// removed either way. The document information dictionary and the XMP
+/// metadata are dropped as well, as they often name the student.
+///
+/// # Errors
+///
+/// Returns an error if a page's content stream cannot be read, decoded, or
+/// encoded again.
+pub fn redact(document: &mut Document, targets: &[&str], replacement: &str) -> LopdfResult<usize> {
+    let targets: Vec<&str> = targets.iter().copied().filter(|t| !t.is_empty()).collect();
+    let mut rewritten = 0;
+    for page_id in document.get_pages().values().copied() {
+        let (count, content) = {
+            let (decoders, mut content) = decode_page(document, page_id)?;
+            let count =
+                redact_operations(&mut content.operations, &decoders, &targets, replacement);
+            (count, content)
+        };
+        if count > 0 {
+            document.change_page_content(page_id, content.encode()?)?;
+            rewritten += count;
+        }
+    }
+    document.trailer.remove(b"Info");
+    if let Ok(catalog) = document.catalog_mut() {
+        catalog.remove(b"Metadata");
+    }
+    Ok(rewritten)
+}
+
+/// The text identifying `student` that is replaced by default: the student
+/// ID, and the name and birth date where the header prints them.
+#[must_use]
+pub fn identifying_text(student: &StudentInfo) -> Vec<&str> {
+    let identity = &student.identity;
+    [
+        Some(&student.id),
+        identity.name.as_ref(),
+        identity.birth_date.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|text| text.trim())
+    .collect()
+}
+
+/// Whether `redacted`, parsed again from a redacted copy of the transcript
+/// of `original`, holds the same records under `new_id`. The name and birth
+/// date are left out, as replacing them rewrites the header they are read
+/// from.
+#[must_use]
+pub fn keeps_records(original: &StudentInfo, redacted: &StudentInfo, new_id: &str) -> bool {
+    let expected = StudentInfo {
+        id: new_id.to_string(),
+        identity: redacted.identity.clone(),
+        ..original.clone()
+    };
+    *redacted == expected
+}
+
+fn redact_operations(
+    operations: &mut [Operation],
+    decoders: &BTreeMap<Vec<u8>, FontDecoder>,
+    targets: &[&str],
+    replacement: &str,
+) -> usize {
+    let mut decoder = None;
+    let mut rewritten = 0;
+    for operation in operations {
+        match operation.operator.as_ref() {
+            "Tf" => {
+                decoder = operation
+                    .operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .and_then(|name| decoders.get(name));
+            }
+            // The string shown is the last operand of every text showing
+            // operator.
+            "Tj" | "TJ" | "'" | "\"" => {
+                let Some(shown) = operation.operands.last_mut() else {
+                    continue;
+                };
+                let text = objects_to_chunk(decoder, std::slice::from_ref(shown))
+                    .strings()
+                    .concat();
+                if !targets.iter().any(|target| text.contains(target)) {
+                    continue;
+                }
+                let redacted = targets
+                    .iter()
+                    .fold(text, |text, target| text.replace(target, replacement));
+                let bytes = match decoder {
+                    Some(decoder) => decoder.encode(&redacted),
+                    None => Some(Document::encode_text(None, &redacted))
+                        .filter(|bytes| Document::decode_text(None, bytes) == redacted),
+                };
+                // Kerning adjustments within an array are dropped along with
+                // the strings they separated.
+                let string = Object::String(bytes.unwrap_or_default(), StringFormat::Hexadecimal);
+                *shown = match shown {
+                    Object::Array(_) => Object::Array(vec![string]),
+                    _ => string,
+                };
+                rewritten += 1;
+            }
+            _ => {}
+        }
+    }
+    rewritten
+}
@@ -0,0 +1,174 @@
+//! Flagging students at risk for early-alert programs, with configurable
+//! rules such as a term GPA below 2.0 in two terms or a failed core course.
+
+use std::io::Read;
+
+use serde::{de, Deserialize, Serialize};
+
+use crate::model::{Outcome, StudentInfo};
+use crate::prerequisites::normalize;
+
+/// What a rule counts in a student's record.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum Condition {
+    /// Terms with a GPA below the threshold.
+    TermGpaBelow { threshold: f64 },
+    /// A latest cumulative GPA below the threshold, counted once.
+    CgpaBelow { threshold: f64 },
+    /// Failed attempts at the courses, or at any course if none are listed.
+    Failed {
+        #[serde(default)]
+        courses: Vec<String>,
+    },
+    /// Withdrawals from the courses, or from any course if none are listed.
+    Withdrawn {
+        #[serde(default)]
+        courses: Vec<String>,
+    },
+    /// Terms not in good academic standing, such as on probation.
+    NotGoodStanding,
+}
+
+fn once() -> usize {
+    1
+}
+
+/// A named condition that flags a student when it occurs at least `times`
+/// times, once by default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RiskRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: Condition,
+    #[serde(default = "once")]
+    pub times: usize,
+}
+
+/// A rule that flagged a student, and how often its condition occurred.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RiskFlag {
+    pub rule: String,
+    pub count: usize,
+}
+
+/// The flags raised for a student, under their anonymized ID.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StudentRisk {
+    pub id: String,
+    pub at_risk: bool,
+    pub flags: Vec<RiskFlag>,
+}
+
+impl StudentRisk {
+    #[must_use]
+    pub fn new(id: String, flags: Vec<RiskFlag>) -> Self {
+        Self {
+            id,
+            at_risk: !flags.is_empty(),
+            flags,
+        }
+    }
+}
+
+/// The rules that flag a student as at risk, any one of which is enough.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RiskRules {
+    pub rules: Vec<RiskRule>,
+}
+
+impl Condition {
+    fn count(&self, student: &StudentInfo) -> usize {
+        let attempts = |outcome: Outcome, courses: &[String]| {
+            student
+                .semesters
+                .iter()
+                .flat_map(|semester| &semester.courses)
+                .filter(|course| course.outcome == outcome)
+                .filter(|course| {
+                    courses.is_empty()
+                        || courses
+                            .contains(&normalize(&format!("{} {}", course.subject, course.id)))
+                })
+                .count()
+        };
+        match self {
+            Self::TermGpaBelow { threshold } => student
+                .semesters
+                .iter()
+                .filter(|semester| semester.gpa.is_some_and(|gpa| gpa < *threshold))
+                .count(),
+            Self::CgpaBelow { threshold } => {
+                let cgpa = student.semesters.iter().rev().find_map(|s| s.cgpa);
+                usize::from(cgpa.is_some_and(|cgpa| cgpa < *threshold))
+            }
+            Self::Failed { courses } => attempts(Outcome::Failed, courses),
+            Self::Withdrawn { courses } => attempts(Outcome::Withdrawn, courses),
+            Self::NotGoodStanding => student
+                .semesters
+                .iter()
+                .filter(|semester| !semester.is_good_standing)
+                .count(),
+        }
+    }
+}
+
+impl RiskRules {
+    /// Reads rules from a JSON object such as `{"rules": [{"name": "Low
+    /// term GPA", "condition": "term_gpa_below", "threshold": 2.0, "times":
+    /// 2}]}`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader does not hold such an object, or if a
+    /// rule can never flag a student.
+    pub fn from_json<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader::<_, Self>(reader)?.validated()
+    }
+
+    /// Reads rules from TOML, with each rule as a `[[rules]]` table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not such a document, or if a rule can
+    /// never flag a student.
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str::<Self>(text)?.validated()
+    }
+
+    // Rejects rules that need no occurrences, which would flag every
+    // student, and normalizes the course names.
+    fn validated<E: de::Error>(mut self) -> Result<Self, E> {
+        for rule in &mut self.rules {
+            if rule.times == 0 {
+                return Err(E::custom(format!(
+                    "rule {} needs times of 1 or more",
+                    rule.name
+                )));
+            }
+            if let Condition::Failed { courses } | Condition::Withdrawn { courses } =
+                &mut rule.condition
+            {
+                for course in courses {
+                    *course = normalize(course);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// The rules that flag the student, in the order they are defined.
+    #[must_use]
+    pub fn flags(&self, student: &StudentInfo) -> Vec<RiskFlag> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let count = rule.condition.count(student);
+                (count >= rule.times).then(|| RiskFlag {
+                    rule: rule.name.clone(),
+                    count,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Summaries of grade distributions across one or many transcripts.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::model::{Course, Outcome, StudentInfo};
+
+/// The grades with their own count in a distribution, in the order they are
+/// reported. All other grades are counted together as `other`.
+pub const DISTRIBUTION_GRADES: [&str; 12] = [
+    "A+", "A", "A-", "B+", "B", "B-", "C+", "C", "C-", "D", "F", "WD",
+];
+
+/// The grade counts and summary measures of a group of course attempts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GradeDistribution {
+    /// Every attempt in the group, including those still in progress.
+    pub enrollments: usize,
+    /// The attempts with each grade in [`DISTRIBUTION_GRADES`].
+    pub counts: BTreeMap<String, usize>,
+    /// The attempts with any other grade.
+    pub other: usize,
+    /// The attempts that were completed, failed, or withdrawn from.
+    pub finished: usize,
+    /// The finished attempts with a D, a failing grade, or a withdrawal.
+    pub dfw: usize,
+    /// The withdrawals among the finished attempts.
+    pub withdrawn: usize,
+    graded_units: f64,
+    grade_points: f64,
+}
+
+impl GradeDistribution {
+    /// Adds a course attempt to the distribution.
+    pub fn add(&mut self, course: &Course) {
+        self.enrollments += 1;
+        if DISTRIBUTION_GRADES.contains(&course.grade.as_str()) {
+            *self.counts.entry(course.grade.clone()).or_default() += 1;
+        } else {
+            self.other += 1;
+        }
+        match course.outcome {
+            Outcome::Completed if course.grade == "D" => {
+                self.finished += 1;
+                self.dfw += 1;
+            }
+            Outcome::Completed => self.finished += 1,
+            Outcome::Failed => {
+                self.finished += 1;
+                self.dfw += 1;
+            }
+            Outcome::Withdrawn => {
+                self.finished += 1;
+                self.dfw += 1;
+                self.withdrawn += 1;
+            }
+            Outcome::InProgress | Outcome::Pending | Outcome::Audit | Outcome::Unknown => {}
+        }
+        // Attempts without units count once, so that the mean still covers
+        // them.
+        if let Some(points) = course.grade_points {
+            let units = course.units_attempted.filter(|u| *u > 0.0).unwrap_or(1.0);
+            self.grade_points += points * units;
+            self.graded_units += units;
+        }
+    }
+
+    /// The number of attempts with `grade`.
+    #[must_use]
+    pub fn count(&self, grade: &str) -> usize {
+        self.counts.get(grade).copied().unwrap_or_default()
+    }
+
+    /// The unit-weighted mean grade points of the graded attempts.
+    #[must_use]
+    pub fn mean_grade_points(&self) -> Option<f64> {
+        (self.graded_units > 0.0).then(|| self.grade_points / self.graded_units)
+    }
+
+    /// The share of finished attempts ending in a D, a fail, or a
+    /// withdrawal.
+    #[must_use]
+    pub fn dfw_rate(&self) -> Option<f64> {
+        ratio(self.dfw, self.finished)
+    }
+
+    /// The share of finished attempts that were withdrawn from.
+    #[must_use]
+    pub fn withdrawal_rate(&self) -> Option<f64> {
+        ratio(self.withdrawn, self.finished)
+    }
+}
+
+fn ratio(count: usize, total: usize) -> Option<f64> {
+    let count = u32::try_from(count).ok()?;
+    let total = u32::try_from(total).ok().filter(|t| *t > 0)?;
+    Some(f64::from(count) / f64::from(total))
+}
+
+/// Adds the SFU course attempts of a student to distributions grouped by
+/// subject and course ID, for batches summarized one student at a time.
+pub fn add_by_course(
+    groups: &mut BTreeMap<(String, String), GradeDistribution>,
+    student: &StudentInfo,
+) {
+    for course in student.semesters.iter().flat_map(|s| &s.courses) {
+        groups
+            .entry((course.subject.clone(), course.id.clone()))
+            .or_default()
+            .add(course);
+    }
+}
+
+/// Groups every SFU course attempt by subject and course ID. Transfer
+/// credits are left out.
+pub fn by_course<'a>(
+    students: impl IntoIterator<Item = &'a StudentInfo>,
+) -> BTreeMap<(String, String), GradeDistribution> {
+    let mut groups = BTreeMap::new();
+    for student in students {
+        add_by_course(&mut groups, student);
+    }
+    groups
+}
+
+/// Groups every SFU course attempt by the term it was taken in, keyed by
+/// year and term in chronological order.
+pub fn by_term<'a>(
+    students: impl IntoIterator<Item = &'a StudentInfo>,
+) -> BTreeMap<((u32, usize), String, String), GradeDistribution> {
+    let mut groups: BTreeMap<_, GradeDistribution> = BTreeMap::new();
+    for student in students {
+        for semester in &student.semesters {
+            let key = (
+                semester.term_key(),
+                semester.year.clone(),
+                semester.term.clone(),
+            );
+            let group = groups.entry(key).or_default();
+            for course in &semester.courses {
+                group.add(course);
+            }
+        }
+    }
+    groups
+}
@@ -0,0 +1,663 @@
+//! Synthetic transcripts in the official SFU layout, for testing and
+//! benchmarking the parser without committing real student records.
+//!
+//! [`student`] makes up a student from a [`StudentConfig`], and [`render`]
+//! lays out any `StudentInfo` as a transcript PDF, so that parsing the
+//! rendered document gives back the student it was rendered from.
+
+use std::collections::BTreeMap;
+
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, StringFormat};
+
+use crate::grades;
+use crate::model::{
+    Course, Credential, Designation, GroupUnits, HonourRoll, Identity, Milestone, Note, Plan,
+    Semester, StudentInfo, TermType, Transfer, TERMS,
+};
+use crate::parse::{
+    self, BIRTH_DATE_LABELS, CAREER_LABEL, COOP_SUBJECT, END_MARKER, GROUP_LABEL, HOST_LABEL,
+    MILESTONE_MARKER, NAME_LABEL, NOTE_LABELS, PAGE_BREAK_TAG, PLAN_MARKER, PROGRAM_MARKER,
+    STANDING_LABEL, TRANSFER_MARKER,
+};
+use crate::pdf::FOOTER_BANNER;
+
+/// The shape of a made up student.
+#[derive(Clone, Debug)]
+pub struct StudentConfig {
+    /// The student ID printed in the page footers.
+    pub id: String,
+    /// The number of consecutive terms, starting in Fall 2017.
+    pub terms: usize,
+    /// The number of courses in each regular term.
+    pub courses_per_term: usize,
+    /// The number of transfer credits.
+    pub transfers: usize,
+    /// Whether to include the records that the parser treats specially:
+    /// WQB designations, a retaken course, failing, withdrawn, and
+    /// in-progress grades, a co-op term, an exchange term, a plan change,
+    /// probation, an honour roll, a transfer credit without an institution,
+    /// and a conferred credential. Each appears in a fixed term, so all of
+    /// them need at least eight terms and two transfer credits.
+    pub edge_cases: bool,
+}
+
+impl Default for StudentConfig {
+    fn default() -> Self {
+        Self {
+            id: "301000001".to_string(),
+            terms: 8,
+            courses_per_term: 4,
+            transfers: 2,
+            edge_cases: false,
+        }
+    }
+}
+
+const SUBJECTS: [&str; 5] = ["CMPT", "MATH", "STAT", "PHYS", "ENGL"];
+const GRADES: [&str; 8] = ["A+", "A", "A-", "B+", "B", "B-", "C+", "C"];
+const UNITS: f64 = 3.0;
+
+fn course(subject: &str, id: &str, title: &str, grade: &str, earned: Option<f64>) -> Course {
+    Course {
+        subject: subject.to_string(),
+        id: id.to_string(),
+        title: title.to_string(),
+        grade: grade.to_string(),
+        grade_points: grades::grade_points(grade),
+        outcome: grades::outcome(grade),
+        units_attempted: Some(UNITS),
+        units_earned: earned,
+        designations: Vec::new(),
+        attempt: 1,
+        is_latest_attempt: true,
+        grade_valid: true,
+    }
+}
+
+fn regular_courses(term: usize, config: &StudentConfig) -> Vec<Course> {
+    (0..config.courses_per_term)
+        .map(|index| {
+            let subject = SUBJECTS[(term + index) % SUBJECTS.len()];
+            let id = (100 + 10 * term + index).to_string();
+            let grade = GRADES[(term * config.courses_per_term + index) % GRADES.len()];
+            course(subject, &id, &format!("Course {id}"), grade, Some(UNITS))
+        })
+        .collect()
+}
+
+// The academic groups that the units of each subject are totalled under.
+fn academic_group(subject: &str) -> &'static str {
+    match subject {
+        "CMPT" => "Applied Sciences",
+        "ENGL" => "Arts and Social Sciences",
+        _ => "Science",
+    }
+}
+
+// Totals the units earned in each academic group, in the order of the
+// groups' names, as printed to two places. Only the latest attempt at a
+// repeated course counts.
+fn unit_totals(transfers: &[Transfer], semesters: &[Semester]) -> Vec<GroupUnits> {
+    let mut totals: BTreeMap<&str, f64> = BTreeMap::new();
+    let courses = transfers
+        .iter()
+        .map(|transfer| (&transfer.course, transfer.units))
+        .chain(
+            semesters
+                .iter()
+                .flat_map(|s| &s.courses)
+                .map(|course| (course, course.units_earned)),
+        );
+    for (course, units) in courses.filter(|(course, _)| course.is_latest_attempt) {
+        *totals.entry(academic_group(&course.subject)).or_default() += units.unwrap_or(0.0);
+    }
+    totals
+        .into_iter()
+        .map(|(group, total)| GroupUnits {
+            group: group.to_string(),
+            units: units(Some(total)).parse().unwrap_or(total),
+        })
+        .collect()
+}
+
+fn semester(term: usize, courses: Vec<Course>) -> Semester {
+    // Terms are counted from Spring 2017, and the first is Fall 2017.
+    let calendar = term + 2;
+    Semester {
+        year: (2017 + calendar / 3).to_string(),
+        term: TERMS[calendar % 3].to_string(),
+        term_type: TermType::Academic,
+        host_institution: None,
+        career: Some("Undergraduate".to_string()),
+        academic_group: Some("Applied Sciences".to_string()),
+        is_good_standing: true,
+        standing: Some("Good Standing".to_string()),
+        honour_roll: None,
+        gpa: grades::gpa(&courses),
+        cgpa: None,
+        printed_gpa: None,
+        printed_cgpa: None,
+        courses,
+    }
+}
+
+// Adds the edge case that belongs to the term, if any.
+fn add_edge_case(index: usize, is_last: bool, semester: &mut Semester, first_term: &[Course]) {
+    let courses = &mut semester.courses;
+    match index {
+        0 => {
+            if let [first, second, ..] = courses.as_mut_slice() {
+                first.designations = vec![Designation::Writing];
+                second.designations = vec![
+                    Designation::Quantitative,
+                    Designation::BreadthScience,
+                    Designation::BreadthSocialSciences,
+                ];
+            }
+        }
+        1 => {
+            if let Some(retaken) = first_term.first() {
+                courses.insert(0, retaken.clone());
+            }
+            courses.push(course("MATH", "152", "Calculus II", "F", Some(0.0)));
+            courses.push(course("PHYS", "120", "Mechanics", "WD", Some(0.0)));
+        }
+        2 => {
+            *courses = vec![course(COOP_SUBJECT, "101", "Work Term 1", "P", Some(UNITS))];
+            semester.term_type = TermType::CoOp;
+        }
+        3 => {
+            // Exchange courses are granted as block transfer credit.
+            for course in courses.iter_mut() {
+                *course = self::course(
+                    &course.subject,
+                    &course.id,
+                    &course.title,
+                    "TR",
+                    Some(UNITS),
+                );
+            }
+            semester.term_type = TermType::Exchange;
+            semester.host_institution = Some("Synthetic University Abroad".to_string());
+        }
+        5 => {
+            semester.standing = Some("Academic Probation".to_string());
+            semester.is_good_standing = false;
+        }
+        6 => semester.honour_roll = Some(HonourRoll::DeansHonourRoll),
+        _ if is_last => {
+            for course in courses.iter_mut() {
+                *course = self::course(&course.subject, &course.id, &course.title, "", None);
+            }
+        }
+        _ => {}
+    }
+    semester.gpa = grades::gpa(&semester.courses);
+}
+
+/// Makes up a student with the given shape. The same configuration always
+/// gives the same student, with cumulative GPAs and attempt numbers
+/// computed as the parser computes them.
+#[must_use]
+pub fn student(config: &StudentConfig) -> StudentInfo {
+    let mut transfers: Vec<Transfer> = (0..config.transfers)
+        .map(|index| {
+            let subject = SUBJECTS[index % SUBJECTS.len()];
+            let id = (100 + index).to_string();
+            let grade = if index % 2 == 0 { "TR" } else { "B" };
+            let mut course = course(subject, &id, &format!("Transfer {id}"), grade, None);
+            course.units_attempted = None;
+            let is_last = index + 1 == config.transfers;
+            Transfer {
+                course,
+                school: (!(config.edge_cases && is_last && index > 0))
+                    .then(|| format!("Synthetic College {}", index + 1)),
+                units: Some(UNITS),
+                equivalent: None,
+            }
+        })
+        .collect();
+    if config.edge_cases {
+        if let Some(first) = transfers.first_mut() {
+            first.course.designations = vec![Designation::BreadthHumanities];
+        }
+    }
+
+    let mut plans = vec![Plan {
+        name: "CMPTMAJ".to_string(),
+        effective_year: None,
+        effective_term: None,
+    }];
+    let mut semesters: Vec<Semester> = Vec::with_capacity(config.terms);
+    for index in 0..config.terms {
+        let mut semester = semester(index, regular_courses(index, config));
+        if config.edge_cases {
+            let first_term = semesters.first().map_or(&[][..], |s| &s.courses);
+            add_edge_case(index, index + 1 == config.terms, &mut semester, first_term);
+            if index == 4 {
+                plans.push(Plan {
+                    name: "CMPTHON".to_string(),
+                    effective_year: Some(semester.year.clone()),
+                    effective_term: Some(semester.term.clone()),
+                });
+            }
+        }
+        semesters.push(semester);
+    }
+    parse::compute_cumulative_gpas(&mut semesters);
+    // The transcript prints the GPAs rounded to two places.
+    for semester in &mut semesters {
+        semester.printed_gpa = units(semester.gpa).parse().ok();
+        semester.printed_cgpa = units(semester.cgpa).parse().ok();
+    }
+    parse::number_attempts(&mut transfers, &mut semesters);
+
+    let credentials = if config.edge_cases {
+        vec![Credential {
+            program: "Bachelor of Science".to_string(),
+            conferred: Some("2021-06-10".to_string()),
+        }]
+    } else {
+        Vec::new()
+    };
+    // The writing requirement is met in the second term, when the first
+    // writing intensive course is done.
+    let milestones = semesters
+        .get(1)
+        .filter(|_| config.edge_cases)
+        .map(|semester| Milestone {
+            name: "Writing Requirement Met".to_string(),
+            year: semester.year.clone(),
+            term: semester.term.clone(),
+        })
+        .into_iter()
+        .collect();
+    // A note on the whole transcript, printed before the term history, and
+    // one on a grade appeal in the third term.
+    let notes = if config.edge_cases {
+        let appeal = semesters.get(2).map(|semester| Note {
+            year: Some(semester.year.clone()),
+            term: Some(semester.term.clone()),
+            text: "Grade changed on appeal".to_string(),
+        });
+        let name_change = Note {
+            year: None,
+            term: None,
+            text: "Name changed".to_string(),
+        };
+        std::iter::once(name_change).chain(appeal).collect()
+    } else {
+        Vec::new()
+    };
+    StudentInfo {
+        id: config.id.clone(),
+        plans,
+        milestones,
+        notes,
+        unit_totals: unit_totals(&transfers, &semesters),
+        transfers,
+        semesters,
+        credentials,
+        warnings: Vec::new(),
+        identity: Identity {
+            name: Some("Student, Synthetic".to_string()),
+            birth_date: Some("2000-01-01".to_string()),
+        },
+    }
+}
+
+/// How a rendered transcript is broken into pages.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    /// The number of lines on each page before its footer. Lower values
+    /// break terms across pages.
+    pub lines_per_page: usize,
+    /// Whether to split the second transfer credit over a page break, as
+    /// happens to credits that fall at the bottom of a page.
+    pub split_transfer: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            lines_per_page: 40,
+            split_transfer: false,
+        }
+    }
+}
+
+// A text block on its own baseline, drawn as one string with `Tj` or as a
+// row of cells with `TJ`.
+enum Line {
+    Text(String),
+    Row(Vec<String>),
+    PageBreak,
+}
+
+fn text(s: impl Into<String>) -> Line {
+    Line::Text(s.into())
+}
+
+fn row<S: Into<String>>(cells: impl IntoIterator<Item = S>) -> Line {
+    Line::Row(cells.into_iter().map(Into::into).collect())
+}
+
+fn units(units: Option<f64>) -> String {
+    units.map(|units| format!("{units:.2}")).unwrap_or_default()
+}
+
+fn tags(designations: &[Designation]) -> impl Iterator<Item = String> + '_ {
+    designations.iter().map(|d| d.tag().to_string())
+}
+
+fn plan_row(plan: &Plan) -> Line {
+    row(["Major", plan.name.as_str(), "Active"])
+}
+
+// Transfer rows are ragged. The first lacks the leading column, and the
+// institution of each credit is printed at the start of the row after it,
+// or on a row of its own after the last credit.
+fn transfer_lines(transfers: &[Transfer], layout: &Layout) -> Vec<Line> {
+    let mut lines = vec![text(TRANSFER_MARKER)];
+    let mut previous_school = None;
+    for (index, transfer) in transfers.iter().enumerate() {
+        let course = &transfer.course;
+        let mut cells = vec![
+            course.subject.clone(),
+            course.id.clone(),
+            course.title.clone(),
+            "Transfer".to_string(),
+            units(transfer.units),
+            course.grade.clone(),
+        ];
+        if index > 0 {
+            let mut leading = vec!["UGRD".to_string()];
+            if let Some(school) = previous_school.take() {
+                leading.push(school);
+                cells.extend([String::new(), String::new()]);
+            }
+            cells.splice(0..0, leading);
+        }
+        cells.extend(tags(&course.designations));
+        if layout.split_transfer && index == 1 {
+            let rest = cells.split_off(3);
+            cells.push(format!("{PAGE_BREAK_TAG}0001"));
+            lines.extend([Line::Row(cells), Line::PageBreak, Line::Row(rest)]);
+        } else {
+            lines.push(Line::Row(cells));
+        }
+        previous_school.clone_from(&transfer.school);
+    }
+    lines.push(match previous_school {
+        Some(school) => row(["UGRD".to_string(), school]),
+        None => row(["Transfer", "Totals", "0.00"]),
+    });
+    lines
+}
+
+fn course_row(course: &Course, term_type: TermType) -> Line {
+    let mut cells = if term_type == TermType::Exchange {
+        vec![
+            course.subject.clone(),
+            course.id.clone(),
+            course.title.clone(),
+            units(course.units_earned),
+            course.grade.clone(),
+        ]
+    } else {
+        let points = course
+            .grade_points
+            .zip(course.units_attempted)
+            .map(|(points, units)| points * units);
+        vec![
+            "UGRD".to_string(),
+            course.subject.clone(),
+            course.id.clone(),
+            course.title.clone(),
+            units(course.units_attempted),
+            units(course.units_earned),
+            course.grade.clone(),
+            units(points),
+        ]
+    };
+    cells.extend(tags(&course.designations));
+    Line::Row(cells)
+}
+
+fn semester_lines(semester: &Semester, plans: &[Plan]) -> Vec<Line> {
+    let mut lines = vec![text(format!("{} {}", semester.year, semester.term))];
+    for plan in plans.iter().filter(|plan| plan.starts_in(semester)) {
+        lines.extend([text(PLAN_MARKER), plan_row(plan)]);
+    }
+    if let Some(host) = &semester.host_institution {
+        lines.push(text(format!("{HOST_LABEL} {host}")));
+    }
+    lines.extend(
+        semester
+            .courses
+            .iter()
+            .map(|course| course_row(course, semester.term_type)),
+    );
+    lines.push(row([
+        "Term GPA:".to_string(),
+        units(semester.printed_gpa),
+        "Cum GPA:".to_string(),
+        units(semester.printed_cgpa),
+    ]));
+    if let Some(standing) = &semester.standing {
+        lines.push(text(format!("{STANDING_LABEL} {standing}")));
+    }
+    match semester.honour_roll {
+        Some(HonourRoll::DeansHonourRoll) => lines.push(text("Dean's Honour Roll")),
+        Some(HonourRoll::PresidentsHonourRoll) => lines.push(text("President's Honour Roll")),
+        None => {}
+    }
+    lines
+}
+
+// A program block starts every run of terms in the same career and academic
+// group.
+fn program_lines(career: Option<&str>, academic_group: Option<&str>) -> Vec<Line> {
+    let program = if career == Some("Graduate") {
+        "Master of Science"
+    } else {
+        "Bachelor of Science"
+    };
+    let mut lines = vec![text(PROGRAM_MARKER), text(program)];
+    lines.extend(career.map(|career| text(format!("{CAREER_LABEL} {career}"))));
+    lines.extend(academic_group.map(|group| text(format!("{GROUP_LABEL} {group}"))));
+    lines
+}
+
+fn footer(id: &str) -> Vec<Line> {
+    vec![
+        row([FOOTER_BANNER, "Official Transcript"]),
+        text("Student Number"),
+        text(id),
+        text("Issued 2021-07-01"),
+    ]
+}
+
+fn paginate(lines: Vec<Line>, layout: &Layout) -> Vec<Vec<Line>> {
+    let mut pages: Vec<Vec<Line>> = vec![Vec::new()];
+    for line in lines {
+        let page = pages.last().map_or(0, Vec::len);
+        if matches!(line, Line::PageBreak) || page >= layout.lines_per_page.max(1) {
+            pages.push(Vec::new());
+        }
+        if let (Some(page), false) = (pages.last_mut(), matches!(line, Line::PageBreak)) {
+            page.push(line);
+        }
+    }
+    pages
+}
+
+fn encode(s: &str) -> Object {
+    Object::String(
+        Document::encode_text(Some("WinAnsiEncoding"), s),
+        StringFormat::Literal,
+    )
+}
+
+fn page_content(lines: &[Line]) -> Vec<u8> {
+    let mut operations = Vec::new();
+    let mut y = 760;
+    for line in lines {
+        let shown = match line {
+            Line::Text(s) => Operation::new("Tj", vec![encode(s)]),
+            Line::Row(cells) => Operation::new(
+                "TJ",
+                vec![Object::Array(
+                    cells.iter().map(|cell| encode(cell)).collect(),
+                )],
+            ),
+            Line::PageBreak => continue,
+        };
+        operations.extend([
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 8.into()]),
+            Operation::new("Td", vec![36.into(), y.into()]),
+            shown,
+            Operation::new("ET", vec![]),
+        ]);
+        y -= 10;
+    }
+    // Encoding only fails for operands that cannot be written, and every
+    // operand here is a number, name, string, or array of strings.
+    Content { operations }.encode().unwrap_or_default()
+}
+
+// The unit totals, milestones, and credentials that follow the term history.
+fn closing_lines(student: &StudentInfo) -> Vec<Line> {
+    let mut lines = vec![text(END_MARKER)];
+    for total in &student.unit_totals {
+        lines.push(row([total.group.clone(), units(Some(total.units))]));
+    }
+    if !student.milestones.is_empty() {
+        lines.push(text(MILESTONE_MARKER));
+        for milestone in &student.milestones {
+            let completed = format!("{} {}", milestone.year, milestone.term);
+            lines.push(row([milestone.name.clone(), completed]));
+        }
+    }
+    for credential in &student.credentials {
+        let conferred = credential.conferred.as_deref().unwrap_or_default();
+        lines.push(text(format!(
+            "{}, conferred {conferred}",
+            credential.program
+        )));
+    }
+    lines
+}
+
+/// Lays out a student as a transcript PDF in the official layout, which
+/// the parser reads back as the same student. Text is encoded with
+/// `WinAnsiEncoding`, so only characters in that encoding survive, and
+/// credentials are printed with their conferral dates, so credentials
+/// without one are printed with an empty date.
+#[must_use]
+pub fn render(student: &StudentInfo, layout: &Layout) -> Document {
+    let mut lines = vec![text("Academic Record")];
+    let identity = &student.identity;
+    lines.extend(
+        identity
+            .name
+            .as_ref()
+            .map(|name| text(format!("{NAME_LABEL} {name}"))),
+    );
+    lines.extend(
+        identity
+            .birth_date
+            .as_ref()
+            .map(|date| text(format!("{} {date}", BIRTH_DATE_LABELS[0]))),
+    );
+    lines.push(text(PLAN_MARKER));
+    if let Some(plan) = student.plans.first() {
+        lines.push(plan_row(plan));
+    }
+    if !student.transfers.is_empty() {
+        lines.extend(transfer_lines(&student.transfers, layout));
+    }
+    // Notes without a term follow the transfer credits, where they once
+    // threw off the pairing of transfer rows.
+    let note_row = |note: &Note| row([NOTE_LABELS[0], note.text.as_str()]);
+    lines.extend(
+        student
+            .notes
+            .iter()
+            .filter(|note| note.year.is_none())
+            .map(note_row),
+    );
+    if student.semesters.is_empty() {
+        lines.extend(program_lines(None, None));
+    }
+    let later_plans = student.plans.get(1..).unwrap_or_default();
+    let mut context = None;
+    for semester in &student.semesters {
+        let next = Some((&semester.career, &semester.academic_group));
+        if context != next {
+            context = next;
+            lines.extend(program_lines(
+                semester.career.as_deref(),
+                semester.academic_group.as_deref(),
+            ));
+        }
+        lines.extend(semester_lines(semester, later_plans));
+        lines.extend(
+            student
+                .notes
+                .iter()
+                .filter(|note| {
+                    note.year.as_ref() == Some(&semester.year)
+                        && note.term.as_ref() == Some(&semester.term)
+                })
+                .map(note_row),
+        );
+    }
+    lines.extend(closing_lines(student));
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let pages = paginate(lines, layout);
+    let count = pages.len();
+    let kids: Vec<Object> = pages
+        .into_iter()
+        .map(|mut page| {
+            page.extend(footer(&student.id));
+            let content_id =
+                doc.add_object(lopdf::Stream::new(dictionary! {}, page_content(&page)));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            })
+            .into()
+        })
+        .collect();
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => i64::try_from(count).unwrap_or(i64::MAX),
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc
+}
@@ -0,0 +1,163 @@
+//! Encrypted vaults of the real student IDs behind anonymized ones, from
+//! which a data steward holding the secret key can re-identify specific
+//! records.
+//!
+//! IDs are encrypted with [age](https://age-encryption.org) to the public
+//! key of the steward, so the machines running extractions only ever hold
+//! the public key and cannot read the vault they write.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::{self, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+pub use age::x25519::{Identity, Recipient};
+
+use crate::anonymize::create_private;
+
+/// Real student IDs encrypted under the anonymized IDs they were replaced
+/// with, stored as a two column CSV of anonymized IDs and hex encoded age
+/// ciphertexts.
+///
+/// Each ciphertext holds the anonymized ID along with the real one, so
+/// that entries moved to another row are rejected rather than revealing the
+/// wrong student.
+#[derive(Clone, Debug)]
+pub struct Vault {
+    path: PathBuf,
+    entries: BTreeMap<String, Vec<u8>>,
+    modified: bool,
+}
+
+impl Vault {
+    /// Loads the vault at `path`, or starts an empty one if the file does
+    /// not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing file cannot be read or is malformed.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = BTreeMap::new();
+        if path.exists() {
+            let mut reader = csv::Reader::from_path(&path)?;
+            for record in reader.deserialize() {
+                let (anonymized, sealed): (String, String) = record?;
+                let sealed = from_hex(&sealed).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("The vault entry of {anonymized} is not hex encoded"),
+                    )
+                })?;
+                entries.insert(anonymized, sealed);
+            }
+        }
+        Ok(Self {
+            path,
+            entries,
+            modified: false,
+        })
+    }
+
+    /// Encrypts `real_id` to `recipient` under `anonymized_id`, replacing
+    /// any earlier entry, as the latest output is the one to re-identify.
+    ///
+    /// # Panics
+    ///
+    /// Never panics in practice, as encrypting into memory cannot fail.
+    pub fn seal(&mut self, recipient: &Recipient, anonymized_id: &str, real_id: &str) {
+        let plaintext = format!("{anonymized_id}\n{real_id}");
+        let sealed = age::encrypt(recipient, plaintext.as_bytes())
+            .expect("encrypting into memory cannot fail");
+        self.entries.insert(anonymized_id.to_string(), sealed);
+        self.modified = true;
+    }
+
+    /// The anonymized IDs with an entry in the vault.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Decrypts the real ID behind `anonymized_id`, if the vault has it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be decrypted with `identity` or
+    /// belongs to another anonymized ID.
+    pub fn reveal(&self, identity: &Identity, anonymized_id: &str) -> io::Result<Option<String>> {
+        let Some(sealed) = self.entries.get(anonymized_id) else {
+            return Ok(None);
+        };
+        let invalid = |reason: &str| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("The vault entry of {anonymized_id} {reason}"),
+            )
+        };
+        let plaintext = age::decrypt(identity, sealed)
+            .map_err(|_| invalid("cannot be decrypted with this identity"))?;
+        let plaintext = String::from_utf8(plaintext).map_err(|_| invalid("is not text"))?;
+        match plaintext.split_once('\n') {
+            Some((sealed_id, real_id)) if sealed_id == anonymized_id => {
+                Ok(Some(real_id.to_string()))
+            }
+            _ => Err(invalid("belongs to another anonymized ID")),
+        }
+    }
+
+    /// Writes the vault back to its file if any IDs were sealed. The file
+    /// is replaced atomically so a failed write never loses existing
+    /// entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.modified {
+            return Ok(());
+        }
+        let staged = self.path.with_extension("tmp");
+        let mut writer = csv::Writer::from_writer(create_private(&staged)?);
+        writer.write_record(["anonymized_id", "sealed_id"])?;
+        for (anonymized, sealed) in &self.entries {
+            writer.write_record([anonymized.as_str(), to_hex(sealed).as_str()])?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(staged, &self.path)
+    }
+}
+
+/// Reads the first identity of an age identity file, as written by
+/// `age-keygen`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or holds no identity.
+pub fn read_identity<P: AsRef<Path>>(path: P) -> io::Result<Identity> {
+    let text = std::fs::read_to_string(path)?;
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The identity file is empty"))?;
+    line.parse()
+        .map_err(|err: &str| Error::new(ErrorKind::InvalidData, err))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! The grades and WQB tags that may appear in the rows of a transcript. New
+//! notations appear on transcripts occasionally, so the vocabulary is read
+//! from a profile that can be extended at runtime instead of being fixed in
+//! the parser.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grades;
+use crate::model::{Designation, Outcome};
+
+static SFU: LazyLock<Vocabulary> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../profiles/sfu.json"))
+        .expect("the built-in SFU profile is valid")
+});
+
+/// The grade vocabulary and the WQB tags of a transcript layout.
+///
+/// Profiles are JSON objects such as
+/// `{"grades": ["XP"], "qualifiers": {"Online": null}, "breadth_tags": {"B-Sci": "B-Sci"}}`,
+/// where qualifiers are tags matched exactly and breadth tags are matched
+/// anywhere within a string, as several can share one. Each tag maps to the
+/// designation it grants, if any. Grades can be given an outcome and grade
+/// points with `"outcomes": {"XP": "completed"}` and
+/// `"grade_points": {"XP": 4.0}`, and otherwise follow SFU's. Every field
+/// may be left out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Vocabulary {
+    /// Every grade and notation a course can be recorded with.
+    #[serde(default)]
+    pub grades: BTreeSet<String>,
+    /// Tags that make up a whole column, like `W` and `Online`.
+    #[serde(default)]
+    pub qualifiers: BTreeMap<String, Option<Designation>>,
+    /// Tags found within a column, like the `B-Sci` of `B-Sci/Soc`.
+    #[serde(default)]
+    pub breadth_tags: BTreeMap<String, Designation>,
+    /// How attempts with a grade ended, where SFU's grades do not say.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outcomes: BTreeMap<String, Outcome>,
+    /// The grade points of grades, where SFU's scale does not give them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub grade_points: BTreeMap<String, f64>,
+}
+
+impl Default for Vocabulary {
+    /// The vocabulary of SFU transcripts.
+    fn default() -> Self {
+        SFU.clone()
+    }
+}
+
+impl Vocabulary {
+    /// Reads a profile from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader does not hold a profile.
+    pub fn from_json<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Adds the grades and tags of `other`, which take precedence where both
+    /// list the same tag.
+    pub fn extend(&mut self, other: Vocabulary) {
+        self.grades.extend(other.grades);
+        self.qualifiers.extend(other.qualifiers);
+        self.breadth_tags.extend(other.breadth_tags);
+        self.outcomes.extend(other.outcomes);
+        self.grade_points.extend(other.grade_points);
+    }
+
+    /// Whether `grade` is a known grade or notation.
+    #[must_use]
+    pub fn is_grade(&self, grade: &str) -> bool {
+        self.grades.contains(grade)
+    }
+
+    /// Classifies how a course attempt ended from its grade, as
+    /// [`grades::outcome`] does for grades the profile gives no outcome.
+    #[must_use]
+    pub fn outcome(&self, grade: &str) -> Outcome {
+        self.outcomes
+            .get(grade)
+            .copied()
+            .unwrap_or_else(|| grades::outcome(grade))
+    }
+
+    /// The grade points of `grade`, as [`grades::grade_points`] gives them
+    /// for grades the profile gives no points.
+    #[must_use]
+    pub fn grade_points(&self, grade: &str) -> Option<f64> {
+        self.grade_points
+            .get(grade)
+            .copied()
+            .or_else(|| grades::grade_points(grade))
+    }
+
+    /// Whether `s` is a qualifier or holds a breadth tag, rather than being
+    /// a column of its row.
+    #[must_use]
+    pub fn is_tag(&self, s: &str) -> bool {
+        self.qualifiers.contains_key(s)
+            || self.breadth_tags.keys().any(|tag| s.contains(tag.as_str()))
+    }
+
+    /// The designations granted by a tag. A single tag can carry more than
+    /// one breadth designation, which are listed in declaration order.
+    #[must_use]
+    pub fn designations(&self, s: &str) -> Vec<Designation> {
+        let mut breadth: Vec<Designation> = self
+            .breadth_tags
+            .iter()
+            .filter(|(tag, _)| s.contains(tag.as_str()))
+            .map(|(_, designation)| *designation)
+            .collect();
+        breadth.sort_unstable();
+        breadth.dedup();
+        self.qualifiers
+            .get(s)
+            .copied()
+            .flatten()
+            .into_iter()
+            .chain(breadth)
+            .collect()
+    }
+}
@@ -0,0 +1,27 @@
+//! JavaScript bindings for extracting transcripts in the browser, so that
+//! transcripts never have to leave the advisor's machine.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{ParseOptions, Transcript};
+
+/// Extracts the student information from the bytes of a transcript PDF
+/// (such as a `Uint8Array` read from a file input) as a plain object.
+/// Anomalies are recorded in its `warnings` rather than thrown.
+///
+/// # Errors
+///
+/// Throws an error message if the bytes are not an SFU SIMS transcript.
+#[wasm_bindgen]
+pub fn parse_transcript(bytes: &[u8]) -> Result<JsValue, JsError> {
+    let options = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let student = Transcript::from_bytes_with(bytes, &options)?;
+    // Missing values become `null` rather than `undefined`, matching the
+    // JSON output.
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(student.serialize(&serializer)?)
+}
@@ -0,0 +1,94 @@
+//! Tests that appending to an output leaves out the rows it already has.
+#![cfg(feature = "export")]
+
+mod common;
+
+use scrape_sfu_transcript::export::csv::{
+    default_long_columns, read_long_row_keys, CsvOptions, LongCsvExporter,
+};
+use scrape_sfu_transcript::export::sqlite::{read_sqlite_row_keys, SqliteExporter};
+use scrape_sfu_transcript::export::{long_records, remove_present_rows, Exporter};
+use scrape_sfu_transcript::StudentInfo;
+
+fn long_csv(student: &StudentInfo) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut exporter = LongCsvExporter::new(&mut output, CsvOptions::default());
+    exporter.write_student("7", student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+    output
+}
+
+#[test]
+fn only_new_terms_are_appended_to_csv() {
+    let earlier = long_csv(&common::with_terms(3));
+    let present = read_long_row_keys(
+        earlier.as_slice(),
+        CsvOptions::default(),
+        &default_long_columns(),
+    )
+    .unwrap();
+
+    let mut later = common::with_terms(5);
+    let removed = remove_present_rows(&mut later, "7", &present);
+    assert_eq!(removed, long_records("7", &common::with_terms(3)).count());
+    assert!(later.transfers.is_empty());
+    assert!(later.semesters[..3].iter().all(|s| s.courses.is_empty()));
+    assert!(later.semesters[3..].iter().all(|s| !s.courses.is_empty()));
+}
+
+#[test]
+fn transfers_from_different_schools_are_told_apart() {
+    let mut earlier = common::one_course_per_term(1, 1);
+    let transfer = &mut earlier.transfers[0];
+    transfer.course.subject = "ENGL".to_string();
+    transfer.course.id = "1XX".to_string();
+    transfer.school = Some("Douglas".to_string());
+    let present = read_long_row_keys(
+        long_csv(&earlier).as_slice(),
+        CsvOptions::default(),
+        &default_long_columns(),
+    )
+    .unwrap();
+
+    let mut later = earlier.clone();
+    let mut other = later.transfers[0].clone();
+    other.school = Some("Langara".to_string());
+    later.transfers.push(other);
+    assert_eq!(remove_present_rows(&mut later, "7", &present), 2);
+    assert_eq!(later.transfers.len(), 1);
+    assert_eq!(later.transfers[0].school.as_deref(), Some("Langara"));
+}
+
+#[test]
+fn csv_with_other_columns_is_rejected() {
+    let earlier = long_csv(&common::with_terms(1));
+    let columns = &default_long_columns()[..6];
+    assert!(read_long_row_keys(earlier.as_slice(), CsvOptions::default(), columns).is_err());
+}
+
+#[test]
+fn students_already_in_sqlite_are_extended() {
+    let path = std::env::temp_dir().join(format!(
+        "scrape-sfu-transcript-append-{}.db",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let mut exporter = SqliteExporter::new(&path).unwrap();
+    exporter.write_student("7", &common::with_terms(3)).unwrap();
+    drop(exporter);
+
+    let present = read_sqlite_row_keys(&path).unwrap();
+    let mut later = common::with_terms(5);
+    remove_present_rows(&mut later, "7", &present);
+    let mut exporter = SqliteExporter::new(&path).unwrap().appending();
+    exporter.write_student("7", &later).unwrap();
+    drop(exporter);
+
+    let keys = read_sqlite_row_keys(&path).unwrap();
+    assert_eq!(
+        keys.len(),
+        long_records("7", &common::with_terms(5)).count()
+    );
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,99 @@
+//! Tests that degree requirements are audited against transcripts.
+
+mod common;
+
+use scrape_sfu_transcript::audit::DegreeRequirements;
+use scrape_sfu_transcript::{Designation, RequirementStatus, StudentInfo};
+
+const REQUIREMENTS: &str = r#"
+minimum_grade = "C-"
+
+[[requirements]]
+name = "Core"
+courses = ["CMPT 120", "cmpt 125"]
+
+[[requirements]]
+name = "Statistics"
+courses = ["STAT 270", "STAT 271"]
+choose = 1
+
+[[requirements]]
+name = "Upper division"
+units = 6
+subjects = ["CMPT"]
+minimum_level = 300
+
+[[requirements]]
+name = "Writing"
+units = 3
+designation = "W"
+"#;
+
+fn statuses(requirements: &DegreeRequirements, student: &StudentInfo) -> Vec<RequirementStatus> {
+    requirements
+        .audit(student)
+        .iter()
+        .map(|audited| audited.requirement.status)
+        .collect()
+}
+
+#[test]
+fn requirements_report_what_is_missing() {
+    use RequirementStatus::{NotSatisfied, Satisfied};
+    let requirements = DegreeRequirements::from_toml(REQUIREMENTS).unwrap();
+    let mut student = common::taking(
+        &[
+            ("CMPT", "120", "B"),
+            ("CMPT", "125", "D"),
+            ("STAT", "271", "A"),
+            ("CMPT", "307", "C"),
+            ("CMPT", "307", "B"),
+            ("CMPT", "225", "A"),
+        ],
+        &[],
+    );
+    let audited = requirements.audit(&student);
+    assert_eq!(
+        statuses(&requirements, &student),
+        [NotSatisfied, Satisfied, NotSatisfied, NotSatisfied]
+    );
+    // The D is below the minimum grade, and the retaken course counts once.
+    assert_eq!(audited[0].missing, ["CMPT 125"]);
+    assert_eq!(audited[2].requirement.units_used, Some(3.0));
+    assert_eq!(audited[2].requirement.units_needed, Some(3.0));
+
+    student.semesters[5].courses[0].id = "310".to_string();
+    student.semesters[4].courses[0].designations = vec![Designation::Writing];
+    student.semesters[1].courses[0].grade = "C-".to_string();
+    assert_eq!(
+        statuses(&requirements, &student),
+        [Satisfied, Satisfied, Satisfied, Satisfied]
+    );
+}
+
+#[test]
+fn json_and_toml_define_the_same_requirements() {
+    let json = r#"{
+        "minimum_grade": "C-",
+        "requirements": [
+            {"name": "Core", "courses": ["CMPT 120", "CMPT 125"]},
+            {"name": "Statistics", "courses": ["STAT 270", "STAT 271"], "choose": 1},
+            {"name": "Upper division", "units": 6, "subjects": ["CMPT"], "minimum_level": 300},
+            {"name": "Writing", "units": 3, "designation": "W"}
+        ]
+    }"#;
+    assert_eq!(
+        DegreeRequirements::from_json(json.as_bytes()).unwrap(),
+        DegreeRequirements::from_toml(REQUIREMENTS).unwrap()
+    );
+}
+
+#[test]
+fn requirements_must_be_satisfiable() {
+    let empty = "[[requirements]]\nname = \"Nothing\"\n";
+    assert!(DegreeRequirements::from_toml(empty).is_err());
+    let too_many = "[[requirements]]\nname = \"Two\"\ncourses = [\"CMPT 120\"]\nchoose = 2\n";
+    assert!(DegreeRequirements::from_toml(too_many).is_err());
+    let unknown = "[[requirements]]\nname = \"Typo\"\ncourse = [\"CMPT 120\"]\n";
+    assert!(DegreeRequirements::from_toml(unknown).is_err());
+}
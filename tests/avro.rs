@@ -0,0 +1,47 @@
+//! Tests that Avro files carry their schema and read back every row.
+#![cfg(feature = "avro")]
+
+mod common;
+
+use std::fs::File;
+
+use apache_avro::Reader;
+use scrape_sfu_transcript::export::avro::AvroExporter;
+use scrape_sfu_transcript::export::{long_records, Exporter};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct TranscriptRow {
+    year: Option<i32>,
+    subject: String,
+    designations: Vec<String>,
+    printed_term_gpa: Option<f64>,
+    career: Option<String>,
+    schema_version: i64,
+}
+
+#[test]
+fn rows_read_back_with_the_embedded_schema() {
+    let path =
+        std::env::temp_dir().join(format!("scrape-sfu-transcript-{}.avro", std::process::id()));
+    let student = common::edge_cases();
+    let mut exporter = AvroExporter::new(&path).unwrap();
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+
+    let reader = Reader::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.user_metadata()["schema_version"], b"8");
+    let rows: Vec<TranscriptRow> = reader.into_deser_iter().collect::<Result<_, _>>().unwrap();
+    let expected: Vec<_> = long_records("7", &student).collect();
+    assert_eq!(rows.len(), expected.len());
+    for (row, record) in rows.iter().zip(&expected) {
+        assert_eq!(row.year.is_none(), record.is_transfer);
+        assert_eq!(row.subject, record.course.subject);
+        assert_eq!(row.designations.len(), record.course.designations.len());
+        assert_eq!(row.printed_term_gpa, record.printed_term_gpa);
+        assert_eq!(row.career.as_deref(), record.career);
+        assert_eq!(row.schema_version, 8);
+    }
+    std::fs::remove_file(path).unwrap();
+}
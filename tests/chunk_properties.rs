@@ -0,0 +1,51 @@
+//! Property tests of chunk simplification, which every index into the chunk
+//! tree downstream relies on.
+
+use proptest::prelude::*;
+use scrape_sfu_transcript::Chunk;
+
+// Strings padded with whitespace on either side, as text runs often are.
+fn text() -> impl Strategy<Value = String> {
+    "[ \t]{0,2}[A-Za-z0-9.:+-]{0,6}[ \t]{0,2}"
+}
+
+fn chunk() -> impl Strategy<Value = Chunk> {
+    text()
+        .prop_map(Chunk::String)
+        .prop_recursive(6, 64, 5, |inner| {
+            prop::collection::vec(inner, 0..5).prop_map(Chunk::Chunks)
+        })
+}
+
+fn has_singleton(chunk: &Chunk) -> bool {
+    chunk
+        .get_contained()
+        .is_some_and(|v| v.len() == 1 || v.iter().any(has_singleton))
+}
+
+proptest! {
+    #[test]
+    fn simplify_is_idempotent(chunk in chunk()) {
+        let once = chunk.simplify();
+        prop_assert_eq!(once.clone().simplify(), once);
+    }
+
+    #[test]
+    fn simplify_keeps_trimmed_strings_in_order(chunk in chunk()) {
+        let expected: Vec<String> = chunk.strings().iter().map(|s| s.trim().to_string()).collect();
+        let simplified = chunk.simplify();
+        prop_assert_eq!(simplified.strings(), expected);
+    }
+
+    #[test]
+    fn simplify_leaves_no_single_element_chunks(chunk in chunk()) {
+        prop_assert!(!has_singleton(&chunk.simplify()));
+    }
+
+    #[test]
+    fn simplify_keeps_columns(columns in prop::collection::vec(chunk(), 2..8)) {
+        let count = columns.len();
+        let simplified = Chunk::Chunks(columns).simplify();
+        prop_assert_eq!(simplified.get_contained().map(<[Chunk]>::len), Some(count));
+    }
+}
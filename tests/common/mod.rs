@@ -0,0 +1,64 @@
+//! Synthetic students shared by the integration tests, and the round trip
+//! of rendering one as a transcript PDF and parsing it back.
+#![allow(dead_code)]
+
+use scrape_sfu_transcript::grades;
+use scrape_sfu_transcript::synthetic::{render, student, Layout, StudentConfig};
+use scrape_sfu_transcript::{ParseOptions, StudentInfo, Transcript, TranscriptError};
+
+// A student with every record the parser treats specially.
+pub fn edge_cases() -> StudentInfo {
+    student(&StudentConfig {
+        edge_cases: true,
+        ..StudentConfig::default()
+    })
+}
+
+// A regular student over the given number of terms.
+pub fn with_terms(terms: usize) -> StudentInfo {
+    student(&StudentConfig {
+        terms,
+        ..StudentConfig::default()
+    })
+}
+
+// A student with one course in each term and the given number of transfer
+// credits, for tests that fill in the courses themselves.
+pub fn one_course_per_term(terms: usize, transfers: usize) -> StudentInfo {
+    student(&StudentConfig {
+        terms,
+        courses_per_term: 1,
+        transfers,
+        ..StudentConfig::default()
+    })
+}
+
+// A student taking each of the given (subject, number, grade) courses alone
+// in a term of its own, with a transfer credit for each given (subject,
+// number). The term GPAs follow the grades.
+pub fn taking(courses: &[(&str, &str, &str)], transfers: &[(&str, &str)]) -> StudentInfo {
+    let mut student = one_course_per_term(courses.len(), transfers.len());
+    for (semester, &(subject, id, grade)) in student.semesters.iter_mut().zip(courses) {
+        let course = &mut semester.courses[0];
+        course.subject = subject.to_string();
+        course.id = id.to_string();
+        course.grade = grade.to_string();
+        course.grade_points = grades::grade_points(grade);
+        course.outcome = grades::outcome(grade);
+        semester.gpa = grades::gpa(&semester.courses);
+    }
+    for (transfer, &(subject, id)) in student.transfers.iter_mut().zip(transfers) {
+        transfer.course.subject = subject.to_string();
+        transfer.course.id = id.to_string();
+    }
+    student
+}
+
+// Renders the student as a transcript PDF in the default layout and parses
+// it back with the given options.
+pub fn reparse(
+    student: &StudentInfo,
+    options: &ParseOptions,
+) -> Result<StudentInfo, TranscriptError> {
+    Transcript::from_document_with(&render(student, &Layout::default()), options)
+}
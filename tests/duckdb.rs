@@ -0,0 +1,71 @@
+//! Tests that DuckDB databases hold the typed long layout rows.
+#![cfg(feature = "duckdb")]
+
+mod common;
+
+use duckdb::Connection;
+use scrape_sfu_transcript::export::duckdb::{DuckDbExporter, TABLE};
+use scrape_sfu_transcript::export::{long_records, Exporter};
+
+#[test]
+fn rows_are_typed_and_staging_is_dropped() {
+    let path = std::env::temp_dir().join(format!(
+        "scrape-sfu-transcript-{}.duckdb",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let student = common::edge_cases();
+    let mut exporter = DuckDbExporter::new(&path).unwrap();
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+
+    let conn = Connection::open(&path).unwrap();
+    let count: usize = conn
+        .query_row(&format!("SELECT COUNT(*) FROM {TABLE}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(count, long_records("7", &student).count());
+
+    let (semester, designated) = student
+        .semesters
+        .iter()
+        .flat_map(|semester| {
+            semester
+                .courses
+                .iter()
+                .map(move |course| (semester, course))
+        })
+        .find(|(_, course)| !course.designations.is_empty())
+        .unwrap();
+    let (designations, transfers): (String, usize) = conn
+        .query_row(
+            &format!(
+                "SELECT array_to_string(designations, ' '), \
+                 (SELECT COUNT(*) FROM {TABLE} WHERE year IS NULL) \
+                 FROM {TABLE} WHERE year = ? AND term = ? AND subject = ? AND course_id = ?"
+            ),
+            [
+                &semester.year,
+                &semester.term,
+                &designated.subject,
+                &designated.id,
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    let tags: Vec<&str> = designated.designations.iter().map(|d| d.tag()).collect();
+    assert_eq!(designations, tags.join(" "));
+    assert_eq!(transfers, student.transfers.len());
+
+    let tables: usize = conn
+        .query_row(
+            "SELECT COUNT(*) FROM duckdb_tables() WHERE table_name LIKE '%staging'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(tables, 0);
+    std::fs::remove_file(path).unwrap();
+}
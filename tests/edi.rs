@@ -0,0 +1,64 @@
+//! Tests that TS130 output is a well-formed X12 interchange.
+#![cfg(feature = "export")]
+
+mod common;
+
+use scrape_sfu_transcript::export::edi::EdiExporter;
+use scrape_sfu_transcript::export::Exporter;
+
+#[test]
+fn each_student_is_a_transaction() {
+    let mut student = common::edge_cases();
+    "Data*Structures~".clone_into(&mut student.semesters[0].courses[0].title);
+    let mut output = Vec::new();
+    let mut exporter = EdiExporter::new(&mut output);
+    exporter.write_student("7", &student).unwrap();
+    exporter.write_student("8", &student).unwrap();
+    exporter.finish().unwrap();
+    let edi = String::from_utf8(output).unwrap();
+
+    let segments: Vec<&str> = edi.lines().collect();
+    assert!(segments.iter().all(|segment| segment.ends_with('~')));
+    assert_eq!(segments[0].len(), 106);
+    assert!(segments[1].starts_with("GS*ED*"));
+    assert_eq!(segments[segments.len() - 2], "GE*2*1~");
+    assert_eq!(segments[segments.len() - 1], "IEA*1*000000001~");
+    assert!(edi.contains("*Data Structures ~"));
+
+    // Each SE counts the segments of its transaction, from ST to SE.
+    let starts: Vec<usize> = (0..segments.len())
+        .filter(|&i| segments[i].starts_with("ST*130*"))
+        .collect();
+    assert_eq!(starts.len(), 2);
+    for (number, &start) in starts.iter().enumerate() {
+        let end = start
+            + segments[start..]
+                .iter()
+                .position(|segment| segment.starts_with("SE*"))
+                .unwrap();
+        let control = format!("{:04}", number + 1);
+        let trailer = format!("SE*{}*{control}~", end - start + 1);
+        assert_eq!(segments[end], trailer);
+        assert_eq!(segments[start], format!("ST*130*{control}~"));
+        assert_eq!(
+            segments[start + 1].split('*').nth(2),
+            Some(["7", "8"][number])
+        );
+
+        let courses = segments[start..end]
+            .iter()
+            .filter(|segment| segment.starts_with("CRS*"))
+            .count();
+        let expected: usize = student.semesters.iter().map(|s| s.courses.len()).sum();
+        assert_eq!(courses, expected + student.transfers.len());
+
+        // Transfer courses are credited with the units granted for them.
+        let transfers: Vec<&str> = segments[start..end]
+            .iter()
+            .filter(|segment| segment.starts_with("CRS*T*"))
+            .copied()
+            .collect();
+        assert_eq!(transfers.len(), student.transfers.len());
+        assert!(transfers.iter().all(|t| t.split('*').nth(4) == Some("3")));
+    }
+}
@@ -0,0 +1,70 @@
+//! Tests that equivalency tables give transfer credits their SFU courses.
+#![cfg(feature = "export")]
+
+use scrape_sfu_transcript::equivalency::EquivalencyTable;
+use scrape_sfu_transcript::synthetic::{student, StudentConfig};
+use scrape_sfu_transcript::Equivalent;
+
+const TABLE: &str = "institution,subject,number,sfu_subject,sfu_number
+Synthetic College 1, cmpt ,100,CMPT,120
+Synthetic College 2,MATH,101,MATH,151
+,MATH,101,MATH,100
+";
+
+fn equivalent(subject: &str, id: &str) -> Option<Equivalent> {
+    Some(Equivalent {
+        subject: subject.to_string(),
+        id: id.to_string(),
+    })
+}
+
+#[test]
+fn institutions_fall_back_to_any_institution() {
+    let table = EquivalencyTable::from_csv(TABLE.as_bytes()).unwrap();
+    assert_eq!(
+        table
+            .equivalent(Some("synthetic college 1"), "CMPT", "100")
+            .cloned(),
+        equivalent("CMPT", "120")
+    );
+    assert_eq!(
+        table
+            .equivalent(Some("Synthetic College 2"), "MATH", "101")
+            .cloned(),
+        equivalent("MATH", "151")
+    );
+    assert_eq!(
+        table.equivalent(Some("Elsewhere"), "MATH", "101").cloned(),
+        equivalent("MATH", "100")
+    );
+    assert_eq!(
+        table.equivalent(None, "MATH", "101").cloned(),
+        equivalent("MATH", "100")
+    );
+    assert!(table.equivalent(Some("Elsewhere"), "CMPT", "100").is_none());
+}
+
+#[test]
+fn transfers_get_their_equivalents() {
+    let mut student = student(&StudentConfig {
+        transfers: 3,
+        ..StudentConfig::default()
+    });
+    let table = EquivalencyTable::from_csv(TABLE.as_bytes()).unwrap();
+    assert_eq!(table.apply(&mut student), 2);
+    let equivalents: Vec<Option<Equivalent>> = student
+        .transfers
+        .iter()
+        .map(|transfer| transfer.equivalent.clone())
+        .collect();
+    assert_eq!(
+        equivalents,
+        [equivalent("CMPT", "120"), equivalent("MATH", "151"), None]
+    );
+}
+
+#[test]
+fn missing_columns_are_errors() {
+    let table = "institution,subject,number\nLangara,CPSC,1150\n";
+    assert!(EquivalencyTable::from_csv(table.as_bytes()).is_err());
+}
@@ -0,0 +1,83 @@
+//! Tests of the GPA trajectory layout.
+#![cfg(feature = "export")]
+
+use scrape_sfu_transcript::export::csv::{CsvOptions, GpaCsvExporter};
+use scrape_sfu_transcript::export::Exporter;
+use scrape_sfu_transcript::grades::GradeScale;
+use scrape_sfu_transcript::synthetic::{student, StudentConfig};
+
+fn trajectory(config: &StudentConfig, scale: Option<&GradeScale>) -> Vec<Vec<String>> {
+    let mut student = student(config);
+    if let Some(scale) = scale {
+        student.apply_grade_scale(scale);
+    }
+    let mut output = Vec::new();
+    let mut exporter = GpaCsvExporter::new(&mut output, CsvOptions::default());
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').map(str::to_string).collect())
+        .collect()
+}
+
+#[test]
+fn each_term_has_its_gpas_and_the_printed_ones_agree() {
+    let config = StudentConfig {
+        terms: 3,
+        ..StudentConfig::default()
+    };
+    let semesters = student(&config).semesters;
+    let rows = trajectory(&config, None);
+    assert_eq!(rows.len(), 3);
+    for (index, (row, semester)) in rows.iter().zip(&semesters).enumerate() {
+        assert_eq!(
+            row[..4],
+            [
+                "7",
+                &(index + 1).to_string(),
+                &semester.year,
+                &semester.term
+            ]
+        );
+        assert_eq!(row[4], format!("{:.2}", semester.gpa.unwrap()));
+        assert_eq!(row[5], format!("{:.2}", semester.cgpa.unwrap()));
+    }
+}
+
+#[test]
+fn gpas_follow_the_grade_scale() {
+    let config = StudentConfig {
+        terms: 2,
+        ..StudentConfig::default()
+    };
+    let flat = GradeScale::from_json(
+        r#"{"A+": 1, "A": 1, "A-": 1, "B+": 1, "B": 1,
+        "B-": 1, "C+": 1, "C": 1, "C-": 1, "D": 1, "F": 1}"#
+            .as_bytes(),
+    )
+    .unwrap();
+    for row in trajectory(&config, Some(&flat)) {
+        assert_eq!(row[4..6], ["1.00", "1.00"]);
+    }
+}
+
+#[test]
+fn cumulative_gpas_count_only_the_latest_attempt() {
+    let config = StudentConfig {
+        edge_cases: true,
+        ..StudentConfig::default()
+    };
+    let semesters = student(&config).semesters;
+    assert!(semesters
+        .iter()
+        .flat_map(|s| &s.courses)
+        .any(|c| c.attempt == 2));
+    for (row, semester) in trajectory(&config, None).iter().zip(&semesters) {
+        let cgpa = semester.cgpa.map_or(String::new(), |cgpa| format!("{cgpa:.2}"));
+        assert_eq!(row[5], cgpa);
+    }
+}
@@ -0,0 +1,89 @@
+//! Tests that the leak check finds the identifying text of students in
+//! output and nothing else.
+#![cfg(feature = "export")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use scrape_sfu_transcript::export::json::JsonExporter;
+use scrape_sfu_transcript::export::Exporter;
+use scrape_sfu_transcript::leak::{LeakCheck, LeakScanner};
+use scrape_sfu_transcript::model::Identity;
+use scrape_sfu_transcript::StudentInfo;
+
+fn export(student: &StudentInfo, new_id: &str) -> Vec<String> {
+    let check = Rc::new(RefCell::new(LeakCheck::new()));
+    check.borrow_mut().add_student("a.pdf", student);
+    let mut exporter = JsonExporter::new(LeakScanner::new(Vec::new(), Rc::clone(&check)));
+    let result = exporter
+        .write_student(new_id, student)
+        .and_then(|()| exporter.finish());
+    let found = check.borrow().found().to_vec();
+    assert_eq!(result.is_err(), !found.is_empty());
+    found
+}
+
+#[test]
+fn anonymized_output_passes() {
+    let student = common::edge_cases();
+    assert!(export(&student, "anon-0001").is_empty());
+}
+
+#[test]
+fn the_real_id_is_found() {
+    let student = common::edge_cases();
+    let found = export(&student, &student.id);
+    assert_eq!(found, ["the student ID from a.pdf"]);
+}
+
+#[test]
+fn leaking_writes_are_not_passed_on() {
+    let student = common::edge_cases();
+    let check = Rc::new(RefCell::new(LeakCheck::new()));
+    check.borrow_mut().add_student("a.pdf", &student);
+    let mut output = Vec::new();
+    let mut scanner = LeakScanner::new(&mut output, Rc::clone(&check));
+    scanner.write_all(b"anon-0001,").unwrap();
+    assert!(scanner.write_all(student.id.as_bytes()).is_err());
+    assert_eq!(output, b"anon-0001,");
+}
+
+#[test]
+fn text_split_between_writes_is_found() {
+    let student = common::edge_cases();
+    let mut check = LeakCheck::new();
+    check.add_student("a.pdf", &student);
+    let name = student.identity.name.clone().unwrap();
+    let (start, end) = name.split_at(name.len() / 2);
+    check.scan(format!("{{\"notes\": \"{start}").as_bytes());
+    check.scan(format!("{end}\"}}").as_bytes());
+    assert_eq!(check.found(), ["the name from a.pdf"]);
+}
+
+#[test]
+fn rereading_finds_the_birth_date() {
+    let student = common::edge_cases();
+    let mut check = LeakCheck::new();
+    check.add_student("a.pdf", &student);
+    let birth_date = student.identity.birth_date.clone().unwrap();
+    let output = format!("\0\0binary {birth_date} data\0");
+    check.scan_reader(output.as_bytes()).unwrap();
+    assert_eq!(check.found(), ["the birth date from a.pdf"]);
+}
+
+#[test]
+fn identities_of_unparsed_transcripts_are_found() {
+    let student = common::edge_cases();
+    let mut check = LeakCheck::new();
+    check.add_identity("a.pdf", &student.id, &Identity::default());
+    check.add_identity("b.pdf", "0", &student.identity);
+    let name = student.identity.name.clone().unwrap();
+    check.scan(format!("1.1\tName: {name}\n2.0\t{}", student.id).as_bytes());
+    assert_eq!(
+        check.found(),
+        ["the student ID from a.pdf", "the name from b.pdf"]
+    );
+}
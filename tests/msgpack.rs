@@ -0,0 +1,25 @@
+//! Tests that MessagePack output carries the same records as JSON.
+#![cfg(feature = "export")]
+
+mod common;
+
+use scrape_sfu_transcript::export::json::MessagePackExporter;
+use scrape_sfu_transcript::export::{AnonymizedStudent, Exporter};
+
+#[test]
+fn each_student_decodes_to_its_json_object() {
+    let student = common::edge_cases();
+    let mut output = Vec::new();
+    let mut exporter = MessagePackExporter::new(&mut output);
+    exporter.write_student("7", &student).unwrap();
+    exporter.write_student("8", &student).unwrap();
+    exporter.finish().unwrap();
+
+    let mut reader = output.as_slice();
+    for id in ["7", "8"] {
+        let decoded: serde_json::Value = rmp_serde::from_read(&mut reader).unwrap();
+        let expected = serde_json::to_value(AnonymizedStudent::new(id, &student)).unwrap();
+        assert_eq!(decoded, expected);
+    }
+    assert!(reader.is_empty());
+}
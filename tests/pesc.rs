@@ -0,0 +1,86 @@
+//! Tests that PESC College Transcript output maps the model into the
+//! standard elements, and reads back into it.
+#![cfg(feature = "export")]
+
+mod common;
+
+use scrape_sfu_transcript::export::pesc::PescExporter;
+use scrape_sfu_transcript::export::Exporter;
+use scrape_sfu_transcript::{ParseOptions, Semester, Transcript};
+
+#[test]
+fn each_student_is_a_document() {
+    let mut student = common::edge_cases();
+    "Women's Studies & <Gender>".clone_into(&mut student.semesters[0].courses[0].title);
+    let mut output = Vec::new();
+    let mut exporter = PescExporter::new(&mut output);
+    exporter.write_student("7", &student).unwrap();
+    exporter.write_student("8", &student).unwrap();
+    exporter.finish().unwrap();
+    let xml = String::from_utf8(output).unwrap();
+
+    assert_eq!(xml.matches("<?xml version=\"1.0\"").count(), 2);
+    assert_eq!(xml.matches("</ColTrn:CollegeTranscript>").count(), 2);
+    assert!(xml.contains("<SchoolAssignedPersonID>7</SchoolAssignedPersonID>"));
+    assert!(xml.contains("<SchoolAssignedPersonID>8</SchoolAssignedPersonID>"));
+    assert!(xml.contains("<CourseTitle>Women&apos;s Studies &amp; &lt;Gender&gt;</CourseTitle>"));
+
+    let courses: usize = student.semesters.iter().map(|s| s.courses.len()).sum();
+    let per_document = courses + student.transfers.len();
+    assert_eq!(xml.matches("<Course>").count(), 2 * per_document);
+    assert_eq!(
+        xml.matches("<AcademicSession>").count(),
+        2 * student.semesters.len()
+    );
+    assert_eq!(
+        xml.matches("<AcademicAward>").count(),
+        2 * student.credentials.len()
+    );
+
+    // Transfer courses are credited with the units granted for them.
+    let credited = student.transfers.iter().filter(|t| t.units.is_some());
+    let earned = student
+        .semesters
+        .iter()
+        .flat_map(|s| &s.courses)
+        .filter(|c| c.units_earned.is_some());
+    assert_eq!(
+        xml.matches("<CourseCreditEarned>").count(),
+        2 * (earned.count() + credited.count())
+    );
+
+    let first = &student.semesters[0];
+    let name = format!("<SessionName>{} {}</SessionName>", first.year, first.term);
+    assert!(xml.contains(&name));
+}
+
+#[test]
+fn documents_read_back_into_the_model() {
+    let student = common::edge_cases();
+    let mut output = Vec::new();
+    let mut exporter = PescExporter::new(&mut output);
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+
+    let options = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let read = Transcript::from_bytes_with(&output, &options).unwrap();
+    assert_eq!(read.id, "7");
+    assert_eq!(read.credentials, student.credentials);
+    assert_eq!(read.transfers.len(), student.transfers.len());
+    assert_eq!(read.semesters.len(), student.semesters.len());
+    for (read, semester) in read.semesters.iter().zip(&student.semesters) {
+        assert_eq!((&read.year, &read.term), (&semester.year, &semester.term));
+        assert_eq!(read.gpa, semester.gpa);
+        assert_eq!(read.cgpa, semester.cgpa);
+        let courses = |s: &Semester| -> Vec<(String, String, String)> {
+            s.courses
+                .iter()
+                .map(|c| (c.subject.clone(), c.id.clone(), c.grade.clone()))
+                .collect()
+        };
+        assert_eq!(courses(read), courses(semester));
+    }
+}
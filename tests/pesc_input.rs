@@ -0,0 +1,123 @@
+//! Tests that PESC College Transcripts from other institutions are read into
+//! the model.
+
+use scrape_sfu_transcript::{pesc, ErrorCategory, ParseOptions, TermType, TranscriptError};
+
+const TRANSCRIPT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ct:CollegeTranscript xmlns:ct="urn:org:pesc:message:CollegeTranscript:v1.6.0">
+  <TransmissionData><DocumentID>X1</DocumentID></TransmissionData>
+  <Student>
+    <Person>
+      <AgencyAssignedID>301234567</AgencyAssignedID>
+      <Birth><BirthDate>2000-01-02</BirthDate></Birth>
+      <Name><FirstName>Pat</FirstName><LastName>Doe</LastName></Name>
+    </Person>
+    <AcademicRecord>
+      <School><OrganizationName>Partner College</OrganizationName></School>
+      <AcademicAward>
+        <AcademicAwardTitle>Associate of Arts</AcademicAwardTitle>
+        <AcademicAwardDate>2022-05-01</AcademicAwardDate>
+      </AcademicAward>
+      <AcademicProgram><AcademicProgramName>ARTS</AcademicProgramName></AcademicProgram>
+      <AcademicSession>
+        <AcademicSessionDetail><SessionName>2021 fall</SessionName></AcademicSessionDetail>
+        <Course>
+          <CourseCreditValue>3</CourseCreditValue>
+          <CourseCreditEarned>3</CourseCreditEarned>
+          <CourseAcademicGrade>B</CourseAcademicGrade>
+          <CourseSubjectAbbreviation>ENGL</CourseSubjectAbbreviation>
+          <CourseNumber>101</CourseNumber>
+          <CourseTitle>Composition</CourseTitle>
+        </Course>
+        <NoteMessage>Late withdrawal approved</NoteMessage>
+      </AcademicSession>
+      <AcademicSession>
+        <AcademicSessionDetail><SessionDesignator>2021-01</SessionDesignator></AcademicSessionDetail>
+        <School><OrganizationName>Abroad University</OrganizationName></School>
+        <Course>
+          <CourseCreditValue>4</CourseCreditValue>
+          <CourseAcademicGrade>Z</CourseAcademicGrade>
+          <CourseSubjectAbbreviation>HIST</CourseSubjectAbbreviation>
+          <CourseNumber>200</CourseNumber>
+        </Course>
+      </AcademicSession>
+      <Course>
+        <CourseCreditEarned>3</CourseCreditEarned>
+        <CourseAcademicGrade>CR</CourseAcademicGrade>
+        <CourseSubjectAbbreviation>MATH</CourseSubjectAbbreviation>
+        <CourseNumber>100</CourseNumber>
+      </Course>
+    </AcademicRecord>
+    <AcademicRecord>
+      <School><OrganizationName>Other College</OrganizationName></School>
+      <AcademicSession>
+        <Course>
+          <CourseCreditEarned>3</CourseCreditEarned>
+          <CourseAcademicGrade>A</CourseAcademicGrade>
+          <CourseSubjectAbbreviation>ENGL</CourseSubjectAbbreviation>
+          <CourseNumber>101</CourseNumber>
+        </Course>
+      </AcademicSession>
+    </AcademicRecord>
+  </Student>
+</ct:CollegeTranscript>
+"#;
+
+#[test]
+fn records_map_onto_the_model() {
+    let student = pesc::from_str(TRANSCRIPT, &ParseOptions::default()).unwrap();
+    assert_eq!(student.id, "301234567");
+    assert_eq!(student.identity.name.as_deref(), Some("Doe, Pat"));
+    assert_eq!(student.identity.birth_date.as_deref(), Some("2000-01-02"));
+    assert_eq!(student.plans[0].name, "ARTS");
+    assert_eq!(student.credentials[0].program, "Associate of Arts");
+    assert_eq!(
+        student.credentials[0].conferred.as_deref(),
+        Some("2022-05-01")
+    );
+
+    // Terms are put in order, whichever way they are named.
+    let terms: Vec<(&str, &str)> = student
+        .semesters
+        .iter()
+        .map(|s| (s.year.as_str(), s.term.as_str()))
+        .collect();
+    assert_eq!(terms, [("2021", "Spring"), ("2021", "Fall")]);
+    let exchange = &student.semesters[0];
+    assert_eq!(exchange.term_type, TermType::Exchange);
+    assert_eq!(
+        exchange.host_institution.as_deref(),
+        Some("Abroad University")
+    );
+    assert!(!exchange.courses[0].grade_valid);
+    assert_eq!(student.semesters[1].gpa, Some(3.0));
+    assert_eq!(student.notes[0].term.as_deref(), Some("Fall"));
+
+    // Courses outside of a term and those from other institutions are
+    // transfer credits, and count as earlier attempts.
+    let transfers: Vec<(&str, Option<&str>)> = student
+        .transfers
+        .iter()
+        .map(|t| (t.course.subject.as_str(), t.school.as_deref()))
+        .collect();
+    assert_eq!(transfers, [("MATH", None), ("ENGL", Some("Other College"))]);
+    assert_eq!(student.semesters[1].courses[0].attempt, 2);
+}
+
+#[test]
+fn unknown_grades_fail_when_strict() {
+    let options = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let err = pesc::from_str(TRANSCRIPT, &options).unwrap_err();
+    assert!(matches!(err, TranscriptError::UnknownGrade { .. }));
+}
+
+#[test]
+fn malformed_documents_are_unreadable() {
+    let err = pesc::from_str("<Student>", &ParseOptions::default()).unwrap_err();
+    assert_eq!(err.category(), ErrorCategory::Unreadable);
+    let err = pesc::from_str("<Transcript/>", &ParseOptions::default()).unwrap_err();
+    assert!(matches!(err, TranscriptError::MissingSection("Student")));
+}
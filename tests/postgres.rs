@@ -0,0 +1,196 @@
+//! Tests that loading into PostgreSQL upserts the rows of each student.
+//! These need a server to load into, given by the URL in
+//! `SFU_TRANSCRIPT_TEST_POSTGRES`, and pass trivially without one.
+#![cfg(feature = "postgres")]
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use postgres::{Client, NoTls};
+use scrape_sfu_transcript::export::postgres::PostgresExporter;
+use scrape_sfu_transcript::export::{long_records, Exporter};
+use scrape_sfu_transcript::leak::LeakCheck;
+
+#[test]
+fn later_transcripts_update_and_extend_the_rows() {
+    let Ok(url) = std::env::var("SFU_TRANSCRIPT_TEST_POSTGRES") else {
+        return;
+    };
+    let table = format!("transcript_rows_test_{}", std::process::id());
+    let mut exporter = PostgresExporter::connect(&url, &table).unwrap();
+    exporter.write_student("7", &common::with_terms(3)).unwrap();
+
+    let mut later = common::with_terms(5);
+    later.semesters[0].courses[0].grade = "A-".to_string();
+    exporter.write_student("7", &later).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    let count: i64 = client
+        .query_one(&format!("SELECT COUNT(*) FROM \"{table}\""), &[])
+        .unwrap()
+        .get(0);
+    let expected = long_records("7", &later).count();
+    assert_eq!(count, i64::try_from(expected).unwrap());
+    let course = &later.semesters[0].courses[0];
+    let grade: String = client
+        .query_one(
+            &format!(
+                "SELECT grade FROM \"{table}\" WHERE year = 2017 AND term = 'Fall' \
+                 AND subject = $1 AND course_id = $2"
+            ),
+            &[&course.subject, &course.id],
+        )
+        .unwrap()
+        .get(0);
+    assert_eq!(grade, "A-");
+    client
+        .batch_execute(&format!("DROP TABLE \"{table}\""))
+        .unwrap();
+}
+
+#[test]
+fn transfers_from_different_schools_are_kept_apart() {
+    let Ok(url) = std::env::var("SFU_TRANSCRIPT_TEST_POSTGRES") else {
+        return;
+    };
+    let table = format!("transcript_transfers_test_{}", std::process::id());
+    let mut student = common::one_course_per_term(1, 2);
+    for (transfer, school) in student.transfers.iter_mut().zip(["Douglas", "Langara"]) {
+        transfer.course.subject = "ENGL".to_string();
+        transfer.course.id = "1XX".to_string();
+        transfer.school = Some(school.to_string());
+    }
+    let mut exporter = PostgresExporter::connect(&url, &table).unwrap();
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    let count: i64 = client
+        .query_one(
+            &format!("SELECT COUNT(*) FROM \"{table}\" WHERE is_transfer"),
+            &[],
+        )
+        .unwrap()
+        .get(0);
+    assert_eq!(count, 2);
+    client
+        .batch_execute(&format!("DROP TABLE \"{table}\""))
+        .unwrap();
+}
+
+#[test]
+fn repeated_transfer_credits_are_kept_apart() {
+    let Ok(url) = std::env::var("SFU_TRANSCRIPT_TEST_POSTGRES") else {
+        return;
+    };
+    let table = format!("transcript_repeats_test_{}", std::process::id());
+    let mut student = common::one_course_per_term(1, 2);
+    for transfer in &mut student.transfers {
+        transfer.course.subject = "ENGL".to_string();
+        transfer.course.id = "1XX".to_string();
+        transfer.school = None;
+    }
+    let mut exporter = PostgresExporter::connect(&url, &table).unwrap();
+    exporter.write_student("7", &student).unwrap();
+    // Loading the same transcript again updates the rows in place.
+    exporter.write_student("7", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    let occurrences: Vec<i64> = client
+        .query(
+            &format!("SELECT occurrence FROM \"{table}\" WHERE is_transfer ORDER BY occurrence"),
+            &[],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(occurrences, [1, 2]);
+    client
+        .batch_execute(&format!("DROP TABLE \"{table}\""))
+        .unwrap();
+}
+
+#[test]
+fn students_with_identifying_text_are_rolled_back() {
+    let Ok(url) = std::env::var("SFU_TRANSCRIPT_TEST_POSTGRES") else {
+        return;
+    };
+    let table = format!("transcript_leaks_test_{}", std::process::id());
+    let student = common::with_terms(2);
+    let leaks = Rc::new(RefCell::new(LeakCheck::new()));
+    leaks.borrow_mut().add_student("a.pdf", &student);
+    let mut exporter = PostgresExporter::connect(&url, &table)
+        .unwrap()
+        .with_leak_check(Rc::clone(&leaks));
+    exporter.write_student("7", &student).unwrap();
+    assert!(exporter.write_student(&student.id, &student).is_err());
+    drop(exporter);
+
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    let leaked: i64 = client
+        .query_one(
+            &format!("SELECT COUNT(*) FROM \"{table}\" WHERE student_id = $1"),
+            &[&student.id],
+        )
+        .unwrap()
+        .get(0);
+    assert_eq!(leaked, 0);
+    client
+        .batch_execute(&format!("DROP TABLE \"{table}\""))
+        .unwrap();
+}
+
+#[test]
+fn tables_loaded_before_columns_were_added_gain_them() {
+    let Ok(url) = std::env::var("SFU_TRANSCRIPT_TEST_POSTGRES") else {
+        return;
+    };
+    let table = format!("transcript_upgrade_test_{}", std::process::id());
+    let student = common::with_terms(2);
+    PostgresExporter::connect(&url, &table).unwrap();
+    let mut client = Client::connect(&url, NoTls).unwrap();
+    let added = [
+        "printed_term_gpa",
+        "printed_cumulative_gpa",
+        "career",
+        "academic_group",
+        "equivalent_subject",
+        "equivalent_number",
+    ];
+    let drops = added.map(|column| format!("DROP COLUMN {column}"));
+    client
+        .batch_execute(&format!("ALTER TABLE \"{table}\" {}", drops.join(", ")))
+        .unwrap();
+
+    let mut exporter = PostgresExporter::connect(&url, &table).unwrap();
+    exporter.write_student("7", &student).unwrap();
+    drop(exporter);
+    let row = client
+        .query_one(
+            &format!("SELECT COUNT(printed_term_gpa), COUNT(career) FROM \"{table}\""),
+            &[],
+        )
+        .unwrap();
+    let records: Vec<_> = long_records("7", &student).collect();
+    let printed = records.iter().filter(|r| r.printed_term_gpa.is_some());
+    let careers = records.iter().filter(|r| r.career.is_some());
+    assert_eq!(
+        row.get::<_, i64>(0),
+        i64::try_from(printed.count()).unwrap()
+    );
+    assert_eq!(
+        row.get::<_, i64>(1),
+        i64::try_from(careers.count()).unwrap()
+    );
+    client
+        .batch_execute(&format!("DROP TABLE \"{table}\""))
+        .unwrap();
+}
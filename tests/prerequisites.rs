@@ -0,0 +1,94 @@
+//! Tests that prerequisite rules find courses taken before their
+//! prerequisites.
+
+mod common;
+
+use scrape_sfu_transcript::prerequisites::{PrerequisiteRules, UnmetPrerequisites};
+use scrape_sfu_transcript::Equivalent;
+
+const RULES: &str = r#"{
+    "minimum_grade": "C-",
+    "courses": {
+        "CMPT 225": [["CMPT 125", "cmpt 135"], ["MACM 101"]],
+        "cmpt  307": [["CMPT 225"]]
+    }
+}"#;
+
+fn unmet(year: &str, term: &str, course: &str, missing: &[&[&str]]) -> UnmetPrerequisites {
+    let (subject, id) = course.split_once(' ').unwrap();
+    UnmetPrerequisites {
+        year: year.to_string(),
+        term: term.to_string(),
+        subject: subject.to_string(),
+        id: id.to_string(),
+        missing: missing
+            .iter()
+            .map(|group| group.iter().map(ToString::to_string).collect())
+            .collect(),
+    }
+}
+
+#[test]
+fn prerequisites_must_be_completed_in_an_earlier_term() {
+    let rules = PrerequisiteRules::from_json(RULES.as_bytes()).unwrap();
+    let student = common::taking(
+        &[
+            ("CMPT", "225", "B"),
+            ("CMPT", "135", "D"),
+            ("CMPT", "307", "A"),
+        ],
+        &[("MACM", "101")],
+    );
+    assert_eq!(
+        rules.check(&student),
+        [unmet(
+            "2017",
+            "Fall",
+            "CMPT 225",
+            &[&["CMPT 125", "CMPT 135"]]
+        )]
+    );
+
+    // A D is below the minimum grade, and a failed course is not checked.
+    let student = common::taking(
+        &[
+            ("CMPT", "135", "D"),
+            ("CMPT", "225", "A"),
+            ("CMPT", "307", "F"),
+        ],
+        &[("MACM", "101")],
+    );
+    assert_eq!(
+        rules.check(&student),
+        [unmet(
+            "2018",
+            "Spring",
+            "CMPT 225",
+            &[&["CMPT 125", "CMPT 135"]]
+        )]
+    );
+}
+
+#[test]
+fn transfer_equivalents_meet_prerequisites() {
+    let rules = PrerequisiteRules::from_json(RULES.as_bytes()).unwrap();
+    let mut student = common::taking(
+        &[("CMPT", "125", "B"), ("CMPT", "225", "B")],
+        &[("MATH", "100")],
+    );
+    assert_eq!(
+        rules.check(&student),
+        [unmet("2018", "Spring", "CMPT 225", &[&["MACM 101"]])]
+    );
+    student.transfers[0].equivalent = Some(Equivalent {
+        subject: "MACM".to_string(),
+        id: "101".to_string(),
+    });
+    assert!(rules.check(&student).is_empty());
+}
+
+#[test]
+fn minimum_grades_must_have_grade_points() {
+    let rules = r#"{"minimum_grade": "P", "courses": {}}"#;
+    assert!(PrerequisiteRules::from_json(rules.as_bytes()).is_err());
+}
@@ -0,0 +1,97 @@
+//! Tests that rare quasi-identifiers are suppressed across a batch and
+//! common ones are kept, and that terms are coarsened consistently.
+
+use scrape_sfu_transcript::privacy::{coarsen_terms, suppress_rare, TermGranularity, SUPPRESSED};
+use scrape_sfu_transcript::synthetic::{student, StudentConfig};
+use scrape_sfu_transcript::StudentInfo;
+
+fn synthetic(id: &str, edge_cases: bool) -> StudentInfo {
+    student(&StudentConfig {
+        id: id.to_string(),
+        edge_cases,
+        ..StudentConfig::default()
+    })
+}
+
+fn plan_names(student: &StudentInfo) -> Vec<&str> {
+    student
+        .plans
+        .iter()
+        .map(|plan| plan.name.as_str())
+        .collect()
+}
+
+#[test]
+fn values_held_by_too_few_students_are_suppressed() {
+    let mut students = vec![
+        synthetic("301000001", false),
+        synthetic("301000002", false),
+        synthetic("301000003", true),
+    ];
+    assert!(!students[2].credentials.is_empty());
+    let suppressed = suppress_rare(&mut students, 2);
+
+    assert_eq!(plan_names(&students[0]), ["CMPTMAJ"]);
+    assert_eq!(plan_names(&students[2]), ["CMPTMAJ", SUPPRESSED]);
+    assert!(students[2]
+        .credentials
+        .iter()
+        .all(|credential| credential.program == SUPPRESSED));
+    assert_eq!(suppressed, 1 + students[2].credentials.len());
+}
+
+#[test]
+fn rare_admission_terms_lose_their_plan() {
+    let mut students = vec![
+        synthetic("301000001", false),
+        synthetic("301000002", false),
+        synthetic("301000003", false),
+    ];
+    students[2].semesters.remove(0);
+    suppress_rare(&mut students, 2);
+
+    assert_eq!(plan_names(&students[0]), ["CMPTMAJ"]);
+    assert_eq!(plan_names(&students[1]), ["CMPTMAJ"]);
+    assert_eq!(plan_names(&students[2]), [SUPPRESSED]);
+}
+
+#[test]
+fn nothing_is_suppressed_in_a_uniform_batch() {
+    let mut students = vec![synthetic("301000001", false); 3];
+    let expected = students.clone();
+    assert_eq!(suppress_rare(&mut students, 3), 0);
+    assert_eq!(students, expected);
+}
+
+fn periods(student: &StudentInfo) -> Vec<(&str, &str)> {
+    student
+        .semesters
+        .iter()
+        .map(|semester| (semester.year.as_str(), semester.term.as_str()))
+        .collect()
+}
+
+#[test]
+fn academic_years_start_in_the_fall() {
+    let mut student = synthetic("301000001", true);
+    coarsen_terms(&mut student, TermGranularity::AcademicYear);
+    let periods = periods(&student);
+    assert_eq!(periods[..3], [("2017", "Academic Year"); 3]);
+    assert_eq!(periods[3], ("2018", "Academic Year"));
+    assert!(student
+        .milestones
+        .iter()
+        .all(|milestone| milestone.term == "Academic Year"));
+}
+
+#[test]
+fn calendar_years_keep_the_year_of_each_term() {
+    let original = synthetic("301000001", true);
+    let mut student = original.clone();
+    coarsen_terms(&mut student, TermGranularity::Year);
+    for (coarse, semester) in periods(&student).iter().zip(&original.semesters) {
+        assert_eq!(*coarse, (semester.year.as_str(), "Year"));
+    }
+    assert_eq!(student.semesters.len(), original.semesters.len());
+    assert_eq!(student.credentials[0].conferred.as_deref(), Some("2021"));
+}
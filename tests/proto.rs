@@ -0,0 +1,45 @@
+//! Tests that protobuf output decodes back into one message per student.
+#![cfg(feature = "proto")]
+
+mod common;
+
+use prost::Message;
+use scrape_sfu_transcript::export::proto::{Designation, ProtoExporter, StudentInfo};
+use scrape_sfu_transcript::export::Exporter;
+
+#[test]
+fn messages_are_length_delimited() {
+    let student = common::edge_cases();
+    let mut output = Vec::new();
+    let mut exporter = ProtoExporter::new(&mut output);
+    exporter.write_student("7", &student).unwrap();
+    exporter.write_student("8", &student).unwrap();
+    exporter.finish().unwrap();
+
+    let mut buffer = output.as_slice();
+    let first = StudentInfo::decode_length_delimited(&mut buffer).unwrap();
+    let second = StudentInfo::decode_length_delimited(&mut buffer).unwrap();
+    assert!(buffer.is_empty());
+    assert_eq!((first.id.as_str(), second.id.as_str()), ("7", "8"));
+    assert_eq!(first.schema_version, 8);
+    assert_eq!(first.semesters.len(), student.semesters.len());
+    assert_eq!(first.notes.len(), student.notes.len());
+
+    let (semester, course) = student
+        .semesters
+        .iter()
+        .enumerate()
+        .find_map(|(i, s)| {
+            let j = s.courses.iter().position(|c| !c.designations.is_empty())?;
+            Some((i, j))
+        })
+        .unwrap();
+    let decoded = &first.semesters[semester].courses[course];
+    assert_eq!(
+        decoded.subject,
+        student.semesters[semester].courses[course].subject
+    );
+    assert!(decoded
+        .designations()
+        .all(|designation| designation != Designation::Unspecified));
+}
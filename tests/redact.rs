@@ -0,0 +1,81 @@
+//! Tests that redacted transcripts lose the identifying text but keep their
+//! records.
+
+mod common;
+
+use lopdf::Document;
+use scrape_sfu_transcript::redact::{identifying_text, keeps_records, redact};
+use scrape_sfu_transcript::synthetic::{render, Layout};
+use scrape_sfu_transcript::{StudentInfo, Transcript};
+
+fn synthetic() -> (StudentInfo, Document) {
+    let student = common::edge_cases();
+    let layout = Layout {
+        lines_per_page: 20,
+        ..Layout::default()
+    };
+    let document = render(&student, &layout);
+    (student, document)
+}
+
+fn shows(document: &Document, text: &str) -> bool {
+    Transcript::page_chunks(document)
+        .unwrap()
+        .iter()
+        .flatten()
+        .flat_map(|chunk| chunk.strings())
+        .any(|s| s.contains(text))
+}
+
+#[test]
+fn records_survive_redaction() {
+    let (student, mut document) = synthetic();
+    let pages = document.get_pages().len();
+    assert!(redact(&mut document, &[&student.id], "7").unwrap() >= pages);
+    assert!(!shows(&document, &student.id));
+
+    let mut bytes = Vec::new();
+    document.save_to(&mut bytes).unwrap();
+    let expected = StudentInfo {
+        id: "7".to_string(),
+        ..student
+    };
+    assert_eq!(Transcript::from_bytes(&bytes).unwrap(), expected);
+}
+
+#[test]
+fn redacting_the_name_keeps_the_records() {
+    let (student, mut document) = synthetic();
+    let name = student.identity.name.clone().unwrap();
+    redact(&mut document, &[&student.id, &name], "7").unwrap();
+    assert!(!shows(&document, &name));
+
+    let redacted = Transcript::from_document(&document).unwrap();
+    assert_eq!(redacted.identity.name.as_deref(), Some("7"));
+    assert!(keeps_records(&student, &redacted, "7"));
+}
+
+#[test]
+fn the_name_and_birth_date_are_redacted_by_default() {
+    let (student, mut document) = synthetic();
+    redact(&mut document, &identifying_text(&student), "7").unwrap();
+    let identity = &student.identity;
+    for text in [
+        &student.id,
+        identity.name.as_ref().unwrap(),
+        identity.birth_date.as_ref().unwrap(),
+    ] {
+        assert!(!shows(&document, text));
+    }
+
+    let redacted = Transcript::from_document(&document).unwrap();
+    assert!(keeps_records(&student, &redacted, "7"));
+}
+
+#[test]
+fn text_the_font_cannot_show_is_removed() {
+    let (student, mut document) = synthetic();
+    redact(&mut document, &[&student.id], "\u{4e03}").unwrap();
+    assert!(!shows(&document, &student.id));
+    assert!(!shows(&document, "\u{4e03}"));
+}
@@ -0,0 +1,78 @@
+//! Tests that risk rules flag students for early alerts.
+
+mod common;
+
+use scrape_sfu_transcript::grades;
+use scrape_sfu_transcript::risk::{RiskFlag, RiskRules, StudentRisk};
+
+const RULES: &str = r#"
+[[rules]]
+name = "Low term GPA twice"
+condition = "term_gpa_below"
+threshold = 2
+times = 2
+
+[[rules]]
+name = "Repeated core course failed"
+condition = "failed"
+courses = ["cmpt 120", "CMPT 125"]
+times = 2
+
+[[rules]]
+name = "Probation"
+condition = "not_good_standing"
+"#;
+
+fn flag(rule: &str, count: usize) -> RiskFlag {
+    RiskFlag {
+        rule: rule.to_string(),
+        count,
+    }
+}
+
+#[test]
+fn rules_flag_conditions_that_recur() {
+    let rules = RiskRules::from_toml(RULES).unwrap();
+    let mut student = common::taking(
+        &[("CMPT", "120", "F"), ("CMPT", "120", "F"), ("CMPT", "125", "B")],
+        &[],
+    );
+    assert_eq!(
+        rules.flags(&student),
+        [
+            flag("Low term GPA twice", 2),
+            flag("Repeated core course failed", 2)
+        ]
+    );
+
+    student.semesters[1].courses[0].grade = "C".to_string();
+    student.semesters[1].courses[0].outcome = grades::outcome("C");
+    student.semesters[1].gpa = Some(2.0);
+    assert!(rules.flags(&student).is_empty());
+
+    student.semesters[2].is_good_standing = false;
+    let risk = StudentRisk::new("1".to_string(), rules.flags(&student));
+    assert!(risk.at_risk);
+    assert_eq!(risk.flags, [flag("Probation", 1)]);
+}
+
+#[test]
+fn json_and_toml_define_the_same_rules() {
+    let json = r#"{"rules": [
+        {"name": "Low term GPA twice", "condition": "term_gpa_below", "threshold": 2.0, "times": 2},
+        {"name": "Repeated core course failed", "condition": "failed", "courses": ["CMPT 120", "CMPT 125"], "times": 2},
+        {"name": "Probation", "condition": "not_good_standing"}
+    ]}"#;
+    assert_eq!(
+        RiskRules::from_json(json.as_bytes()).unwrap(),
+        RiskRules::from_toml(RULES).unwrap()
+    );
+}
+
+#[test]
+fn rules_must_be_able_to_flag_some_students() {
+    let never = "[[rules]]\nname = \"Always\"\ncondition = \"withdrawn\"\ntimes = 0\n";
+    assert!(RiskRules::from_toml(never).is_err());
+    let unknown = "[[rules]]\nname = \"Typo\"\ncondition = \"gpa_under\"\n";
+    assert!(RiskRules::from_toml(unknown).is_err());
+}
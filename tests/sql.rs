@@ -0,0 +1,76 @@
+//! Tests that SQL dumps load back into a database with every row intact.
+#![cfg(feature = "export")]
+
+mod common;
+
+use rusqlite::Connection;
+use scrape_sfu_transcript::export::sql::{SqlExporter, TABLE};
+use scrape_sfu_transcript::export::{long_records, Exporter};
+use scrape_sfu_transcript::{Equivalent, StudentInfo};
+
+fn dump(students: &[StudentInfo]) -> String {
+    let mut output = Vec::new();
+    let mut exporter = SqlExporter::new(&mut output);
+    for (i, student) in students.iter().enumerate() {
+        exporter.write_student(&i.to_string(), student).unwrap();
+    }
+    exporter.finish().unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn dumps_load_into_sqlite() {
+    let mut student = common::edge_cases();
+    "Women's Studies".clone_into(&mut student.semesters[0].courses[0].title);
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&dump(&[student.clone()])).unwrap();
+
+    let count: usize = conn
+        .query_row(&format!("SELECT COUNT(*) FROM {TABLE}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(count, long_records("0", &student).count());
+    let titles: usize = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {TABLE} WHERE title = 'Women''s Studies'"),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(titles, 1);
+}
+
+#[test]
+fn empty_batches_still_create_the_table() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&dump(&[])).unwrap();
+    let count: usize = conn
+        .query_row(&format!("SELECT COUNT(*) FROM {TABLE}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn transfer_credits_keep_their_equivalents() {
+    let mut student = common::edge_cases();
+    student.transfers[0].equivalent = Some(Equivalent {
+        subject: "CMPT".to_string(),
+        id: "120".to_string(),
+    });
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&dump(&[student])).unwrap();
+    let equivalents: Vec<(String, String)> = conn
+        .prepare(&format!(
+            "SELECT equivalent_subject, equivalent_number FROM {TABLE} \
+             WHERE equivalent_subject IS NOT NULL"
+        ))
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(equivalents, [("CMPT".to_string(), "120".to_string())]);
+}
@@ -0,0 +1,326 @@
+//! Regression tests that parse synthetic transcripts and compare the result
+//! with the student each was rendered from.
+
+mod common;
+
+use scrape_sfu_transcript::model::{GroupUnits, HonourRoll, Outcome, StudentInfo, TermType};
+use scrape_sfu_transcript::synthetic::{render, student, Layout, StudentConfig};
+use scrape_sfu_transcript::{ErrorCategory, ParseOptions, Transcript, TranscriptError, Vocabulary};
+
+fn assert_round_trip(expected: &StudentInfo, layout: &Layout) {
+    let mut document = render(expected, layout);
+    assert_eq!(&Transcript::from_document(&document).unwrap(), expected);
+
+    let mut bytes = Vec::new();
+    document.save_to(&mut bytes).unwrap();
+    assert_eq!(&Transcript::from_bytes(&bytes).unwrap(), expected);
+}
+
+#[test]
+fn regular_transcript() {
+    assert_round_trip(&student(&StudentConfig::default()), &Layout::default());
+}
+
+#[test]
+fn without_transfers() {
+    let config = StudentConfig {
+        transfers: 0,
+        ..StudentConfig::default()
+    };
+    assert_round_trip(&student(&config), &Layout::default());
+}
+
+#[test]
+fn edge_case_transcript() {
+    assert_round_trip(&common::edge_cases(), &Layout::default());
+}
+
+#[test]
+fn terms_broken_across_pages() {
+    let layout = Layout {
+        lines_per_page: 5,
+        ..Layout::default()
+    };
+    assert_round_trip(&common::edge_cases(), &layout);
+}
+
+#[test]
+fn transfer_split_across_pages() {
+    let config = StudentConfig {
+        transfers: 4,
+        ..StudentConfig::default()
+    };
+    let layout = Layout {
+        split_transfer: true,
+        ..Layout::default()
+    };
+    assert_round_trip(&student(&config), &layout);
+    assert_round_trip(&common::edge_cases(), &layout);
+}
+
+#[test]
+fn long_transcript() {
+    let config = StudentConfig {
+        terms: 30,
+        courses_per_term: 6,
+        transfers: 12,
+        edge_cases: true,
+        ..StudentConfig::default()
+    };
+    assert_round_trip(&student(&config), &Layout::default());
+}
+
+#[test]
+fn edge_cases_are_generated() {
+    let student = common::edge_cases();
+    let types: Vec<TermType> = student.semesters.iter().map(|s| s.term_type).collect();
+    assert!(types.contains(&TermType::CoOp));
+    assert!(types.contains(&TermType::Exchange));
+    assert!(student.semesters.iter().any(|s| !s.is_good_standing));
+    assert!(student
+        .semesters
+        .iter()
+        .any(|s| s.honour_roll == Some(HonourRoll::DeansHonourRoll)));
+    let courses = || student.semesters.iter().flat_map(|s| &s.courses);
+    assert!(courses().any(|c| c.attempt == 2));
+    assert!(courses().any(|c| c.grade.is_empty()));
+    assert!(courses().any(|c| !c.designations.is_empty()));
+    assert_eq!(student.plans.len(), 2);
+    assert!(student.transfers.iter().any(|t| t.school.is_none()));
+    assert_eq!(student.credentials.len(), 1);
+    assert_eq!(student.milestones.len(), 1);
+    assert!(student.notes.iter().any(|note| note.term.is_none()));
+    assert!(student.notes.iter().any(|note| note.term.is_some()));
+}
+
+#[test]
+fn new_grades_are_recognized_with_a_profile() {
+    let mut student = student(&StudentConfig::default());
+    student.semesters[0].courses[0].grade = "XP".to_string();
+    let document = render(&student, &Layout::default());
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    assert!(Transcript::from_document_with(&document, &strict).is_err());
+
+    let profile = Vocabulary::from_json(r#"{"grades": ["XP"]}"#.as_bytes()).unwrap();
+    let mut vocabulary = Vocabulary::default();
+    vocabulary.extend(profile);
+    let options = ParseOptions {
+        vocabulary,
+        ..strict
+    };
+    let parsed = Transcript::from_document_with(&document, &options).unwrap();
+    assert_eq!(parsed.semesters[0].courses[0].grade, "XP");
+    assert!(parsed.semesters[0].courses[0].grade_valid);
+}
+
+#[test]
+fn profile_grades_carry_their_outcome_and_points() {
+    let mut student = student(&StudentConfig::default());
+    student.semesters[0].courses[0].grade = "XP".to_string();
+    let profile =
+        r#"{"grades": ["XP"], "outcomes": {"XP": "completed"}, "grade_points": {"XP": 4.33}}"#;
+    let mut vocabulary = Vocabulary::default();
+    vocabulary.extend(Vocabulary::from_json(profile.as_bytes()).unwrap());
+    let options = ParseOptions {
+        vocabulary,
+        ..ParseOptions::default()
+    };
+    let parsed = common::reparse(&student, &options).unwrap();
+    let course = &parsed.semesters[0].courses[0];
+    assert_eq!(course.outcome, Outcome::Completed);
+    assert_eq!(course.grade_points, Some(4.33));
+    let gpa = parsed.semesters[0].gpa;
+
+    // Without them, a grade only listed by the profile is unclassified.
+    let profile = Vocabulary::from_json(r#"{"grades": ["XP"]}"#.as_bytes()).unwrap();
+    let mut vocabulary = Vocabulary::default();
+    vocabulary.extend(profile);
+    let options = ParseOptions {
+        vocabulary,
+        ..ParseOptions::default()
+    };
+    let parsed = common::reparse(&student, &options).unwrap();
+    let course = &parsed.semesters[0].courses[0];
+    assert!(course.grade_valid);
+    assert_eq!(course.outcome, Outcome::Unknown);
+    assert_eq!(course.grade_points, None);
+    assert_ne!(parsed.semesters[0].gpa, gpa);
+}
+
+#[test]
+fn unknown_grades_are_kept_and_flagged() {
+    let mut student = student(&StudentConfig::default());
+    student.semesters[1].courses[0].grade = "XP".to_string();
+    let parsed = common::reparse(&student, &ParseOptions::default()).unwrap();
+    let course = &parsed.semesters[1].courses[0];
+    assert_eq!(course.grade, "XP");
+    assert!(!course.grade_valid);
+    assert_eq!(parsed.warnings.len(), 1);
+    assert!(parsed.warnings[0].contains("XP"));
+    assert!(parsed.semesters[0].courses.iter().all(|c| c.grade_valid));
+}
+
+#[test]
+fn cooperative_courses_are_not_coop_terms() {
+    let mut student = student(&StudentConfig::default());
+    let course = &mut student.semesters[1].courses[0];
+    "Cooperative Game Theory".clone_into(&mut course.title);
+    course.grade = "XP".to_string();
+    let parsed = common::reparse(&student, &ParseOptions::default()).unwrap();
+    assert_eq!(parsed.semesters[1].term_type, TermType::Academic);
+    assert!(!parsed.semesters[1].courses[0].grade_valid);
+    assert_eq!(parsed.warnings.len(), 1);
+}
+
+#[test]
+fn printed_gpas_are_kept_apart_from_computed_ones() {
+    let mut student = student(&StudentConfig::default());
+    student.semesters[0].printed_gpa = Some(1.23);
+    let parsed = common::reparse(&student, &ParseOptions::default()).unwrap();
+    assert_eq!(parsed.semesters[0].printed_gpa, Some(1.23));
+    assert_eq!(parsed.semesters[0].gpa, student.semesters[0].gpa);
+    assert_eq!(
+        parsed.semesters[1].printed_cgpa,
+        student.semesters[1].printed_cgpa
+    );
+    assert!(parsed.semesters[1].printed_cgpa.is_some());
+}
+
+#[test]
+fn printed_gpas_are_checked_within_a_tolerance() {
+    let mut student = student(&StudentConfig::default());
+    let options = ParseOptions {
+        gpa_tolerance: Some(0.01),
+        ..ParseOptions::default()
+    };
+    let document = render(&student, &Layout::default());
+    assert!(Transcript::from_document_with(&document, &options).is_ok());
+    assert!(Transcript::validate_with(&document, &options).is_empty());
+
+    student.semesters[0].printed_gpa = Some(1.23);
+    let document = render(&student, &Layout::default());
+    assert!(Transcript::from_document(&document).is_ok());
+    assert!(Transcript::from_document_with(&document, &options).is_err());
+    let problems = Transcript::validate_with(&document, &options);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("printed 1.23"));
+
+    let lenient = ParseOptions {
+        lenient: true,
+        ..options
+    };
+    let parsed = Transcript::from_document_with(&document, &lenient).unwrap();
+    assert_eq!(parsed.warnings, problems);
+}
+
+#[test]
+fn printed_cgpas_count_only_the_latest_attempt() {
+    let mut student = student(&StudentConfig {
+        terms: 2,
+        courses_per_term: 2,
+        ..StudentConfig::default()
+    });
+    let grades = [["F", "A"], ["B", "A"]];
+    for (semester, grades) in student.semesters.iter_mut().zip(grades) {
+        for (course, grade) in semester.courses.iter_mut().zip(grades) {
+            course.grade = grade.to_string();
+        }
+    }
+    let failed = student.semesters[0].courses[0].clone();
+    let repeat = &mut student.semesters[1].courses[1];
+    repeat.subject = failed.subject;
+    repeat.id = failed.id;
+    student.unit_totals.clear();
+    // As printed by SFU, where the retaken A replaces the earlier F.
+    student.semesters[0].printed_gpa = Some(2.0);
+    student.semesters[0].printed_cgpa = Some(2.0);
+    student.semesters[1].printed_gpa = Some(3.5);
+    student.semesters[1].printed_cgpa = Some(3.67);
+    let options = ParseOptions {
+        gpa_tolerance: Some(0.01),
+        ..ParseOptions::default()
+    };
+    let parsed = common::reparse(&student, &options).unwrap();
+    assert_eq!(parsed.semesters[0].cgpa, Some(2.0));
+    assert_eq!(parsed.semesters[1].cgpa, Some(11.0 / 3.0));
+}
+
+#[test]
+fn unit_totals_are_read_from_the_end_of_the_transcript() {
+    let student = student(&StudentConfig::default());
+    assert!(!student.unit_totals.is_empty());
+    let parsed = common::reparse(&student, &ParseOptions::default()).unwrap();
+    assert_eq!(parsed.unit_totals, student.unit_totals);
+    assert!(parsed.credentials.is_empty());
+}
+
+#[test]
+fn dropped_rows_fail_the_unit_totals() {
+    let mut student = student(&StudentConfig::default());
+    student.unit_totals[0].units += 3.0;
+    let document = render(&student, &Layout::default());
+    let err = Transcript::from_document(&document).unwrap_err();
+    assert_eq!(err.category(), ErrorCategory::Validation);
+    let problems = Transcript::validate(&document);
+    assert_eq!(problems, [err.to_string()]);
+
+    let lenient = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let parsed = Transcript::from_document_with(&document, &lenient).unwrap();
+    assert_eq!(parsed.warnings, problems);
+    assert_eq!(parsed.semesters, student.semesters);
+}
+
+#[test]
+fn unit_totals_count_the_latest_attempt_and_transfer_units() {
+    let mut student = student(&StudentConfig {
+        terms: 2,
+        courses_per_term: 2,
+        transfers: 2,
+        ..StudentConfig::default()
+    });
+    for (transfer, id) in student.transfers.iter_mut().zip(["900", "901"]) {
+        transfer.course.id = id.to_string();
+    }
+    let first = student.semesters[0].courses[0].clone();
+    let repeat = &mut student.semesters[1].courses[1];
+    repeat.subject = first.subject;
+    repeat.id = first.id;
+    // Two transfer credits of 3 units each, and three distinct courses of 3
+    // units each once the repeated one is counted only once.
+    student.unit_totals = vec![
+        GroupUnits {
+            group: "Applied Sciences".to_string(),
+            units: 6.0,
+        },
+        GroupUnits {
+            group: "Science".to_string(),
+            units: 9.0,
+        },
+    ];
+    let document = render(&student, &Layout::default());
+    let parsed = Transcript::from_document(&document).unwrap();
+    assert_eq!(parsed.units_earned(), 15.0);
+    assert_eq!(parsed.unit_totals, student.unit_totals);
+
+    // Totals that count both attempts do not match.
+    student.unit_totals[1].units = 12.0;
+    let err = common::reparse(&student, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(err, TranscriptError::UnitTotalMismatch { .. }));
+}
+
+#[test]
+fn terms_keep_the_career_of_their_program() {
+    let mut student = student(&StudentConfig::default());
+    let graduate = student.semesters.len() - 2;
+    for semester in &mut student.semesters[graduate..] {
+        semester.career = Some("Graduate".to_string());
+        semester.academic_group = Some("Graduate Studies".to_string());
+    }
+    assert_round_trip(&student, &Layout::default());
+}
@@ -0,0 +1,56 @@
+//! Tests of the per-term course load metrics and their table.
+#![cfg(feature = "export")]
+
+mod common;
+
+use scrape_sfu_transcript::export::csv::{CsvOptions, TermLoadCsvExporter};
+use scrape_sfu_transcript::export::Exporter;
+use scrape_sfu_transcript::synthetic::{student, StudentConfig};
+use scrape_sfu_transcript::{grades, TermLoad};
+
+#[test]
+fn loads_count_courses_units_and_withdrawals() {
+    let mut student = student(&StudentConfig {
+        terms: 2,
+        courses_per_term: 3,
+        ..StudentConfig::default()
+    });
+    let withdrawn = &mut student.semesters[1].courses[0];
+    withdrawn.grade = "WD".to_string();
+    withdrawn.outcome = grades::outcome("WD");
+    withdrawn.units_earned = Some(0.0);
+    student.semesters[1].courses[1].units_attempted = None;
+    assert_eq!(
+        student.semesters[1].load(),
+        TermLoad {
+            courses: 3,
+            units_attempted: 6.0,
+            units_earned: 6.0,
+            withdrawals: 1,
+        }
+    );
+
+    let mut output = Vec::new();
+    let options = CsvOptions {
+        delimiter: b'\t',
+        header: true,
+    };
+    let mut exporter = TermLoadCsvExporter::new(&mut output, options);
+    exporter.write_student("9", &student).unwrap();
+    exporter.finish().unwrap();
+    drop(exporter);
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("Student ID\tYear\tTerm\tCourses"));
+    assert!(lines[2].starts_with("9\t2018\tSpring\t3\t6.00\t6.00\t1\t"));
+}
+
+#[test]
+fn empty_terms_have_no_load() {
+    let mut student = common::with_terms(1);
+    student.semesters[0].courses.clear();
+    let load = student.semesters[0].load();
+    assert_eq!(load, TermLoad::default());
+    assert!(load.units_attempted.is_sign_positive());
+}
@@ -0,0 +1,66 @@
+//! Tests that vaults give back the real IDs sealed in them to the holder of
+//! the secret key, and to no one else.
+#![cfg(feature = "vault")]
+
+use std::path::PathBuf;
+
+use scrape_sfu_transcript::vault::{Identity, Vault};
+
+fn vault_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "scrape-sfu-transcript-vault-{}-{name}.csv",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn sealed_ids_are_revealed_after_saving() {
+    let path = vault_path("saved");
+    let identity = Identity::generate();
+    let mut vault = Vault::load(&path).unwrap();
+    vault.seal(&identity.to_public(), "1", "301234567");
+    vault.seal(&identity.to_public(), "2", "301234568");
+    vault.save().unwrap();
+
+    let vault = Vault::load(&path).unwrap();
+    assert_eq!(vault.ids().collect::<Vec<_>>(), ["1", "2"]);
+    assert_eq!(
+        vault.reveal(&identity, "2").unwrap().as_deref(),
+        Some("301234568")
+    );
+    assert_eq!(vault.reveal(&identity, "3").unwrap(), None);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("30123456"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn other_identities_cannot_reveal() {
+    let path = vault_path("other");
+    let mut vault = Vault::load(&path).unwrap();
+    vault.seal(&Identity::generate().to_public(), "1", "301234567");
+    assert!(vault.reveal(&Identity::generate(), "1").is_err());
+}
+
+#[test]
+fn moved_entries_are_rejected() {
+    let path = vault_path("moved");
+    let identity = Identity::generate();
+    let mut vault = Vault::load(&path).unwrap();
+    vault.seal(&identity.to_public(), "1", "301234567");
+    vault.seal(&identity.to_public(), "2", "301234568");
+    vault.save().unwrap();
+
+    // Swaps the anonymized IDs of the two rows.
+    let text = std::fs::read_to_string(&path).unwrap();
+    let swapped = text
+        .replace("\n1,", "\nx,")
+        .replace("\n2,", "\n1,")
+        .replace("\nx,", "\n2,");
+    std::fs::write(&path, swapped).unwrap();
+    let vault = Vault::load(&path).unwrap();
+    assert!(vault.reveal(&identity, "1").is_err());
+    std::fs::remove_file(path).unwrap();
+}